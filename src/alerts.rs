@@ -0,0 +1,297 @@
+use crate::config::ThresholdConfig;
+use crate::homeassistant::{device_trigger_discovery_config, device_trigger_topic, DeviceInfo};
+use crate::mqtt_client::MqttPayload;
+use crate::sensors::SystemSensor;
+use crate::utils::matches_pattern;
+use rumqttc::QoS;
+use serde_json::json;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Limit {
+    High,
+    Low,
+}
+
+impl Limit {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Limit::High => "high",
+            Limit::Low => "low",
+        }
+    }
+}
+
+// Home Assistant device triggers for thresholds with device_trigger set
+#[derive(Debug)]
+struct DeviceTriggers {
+    discovery_prefix: String,
+    device_info: DeviceInfo,
+    qos: QoS, // Of the retained trigger discovery configs
+    discovered: HashSet<String>,
+}
+
+// Remembers which thresholds are currently triggered so an alert is only published
+// when it starts and when it clears, not on every cycle
+#[derive(Debug, Default)]
+pub struct AlertTracker {
+    active: HashSet<String>,
+    device_triggers: Option<DeviceTriggers>,
+}
+
+impl AlertTracker {
+    pub fn with_device_triggers(discovery_prefix: &str, device_info: &DeviceInfo, discovery_qos: QoS) -> Self {
+        Self {
+            active: HashSet::new(),
+            device_triggers: Some(DeviceTriggers {
+                discovery_prefix: discovery_prefix.to_string(),
+                device_info: device_info.clone(),
+                qos: discovery_qos,
+                discovered: HashSet::new(),
+            }),
+        }
+    }
+
+    pub fn check(&mut self, sensors: &[SystemSensor], thresholds: &[ThresholdConfig], qos: QoS) -> Vec<MqttPayload> {
+        let mut alerts = Vec::new();
+
+        for (index, threshold) in thresholds.iter().enumerate() {
+            for sensor in sensors {
+                // Text sensors have no meaningful value to compare
+                if sensor.extra.is_some() || !matches_pattern(&threshold.sensor_pattern, &sensor.name) {
+                    continue;
+                }
+                let limits = [(Limit::High, threshold.high_value), (Limit::Low, threshold.low_value)];
+                for (limit, limit_value) in limits {
+                    let Some(limit_value) = limit_value else {
+                        continue;
+                    };
+                    let key = format!("{}:{}:{:?}", index, sensor.name, limit);
+                    let action = trigger_action(sensor, limit);
+                    if threshold.device_trigger
+                        && let Some(discovery) = self.discover_trigger(&sensor.name, &action)
+                    {
+                        alerts.push(discovery);
+                    }
+                    let payload = self.update(key.clone(), sensor, limit, limit_value, threshold.hysteresis);
+                    if let Some(payload) = payload {
+                        alerts.push(MqttPayload {
+                            topic: threshold.alert_topic.clone(),
+                            payload: payload.to_string(),
+                            retain: false,
+                            qos,
+                            payload_bytes: None,
+                        });
+                        // Triggers fire on the way up only, recovering is not an event
+                        if threshold.device_trigger && self.active.contains(&key) {
+                            alerts.extend(self.fire_trigger(sensor, &action, qos));
+                        }
+                    }
+                }
+            }
+        }
+
+        alerts
+    }
+
+    // Trigger discovery is sent as soon as a sensor matches, so automations can be set up
+    // before the limit is ever crossed
+    fn discover_trigger(&mut self, sensor_name: &str, action: &str) -> Option<MqttPayload> {
+        let triggers = self.device_triggers.as_mut()?;
+        if !triggers.discovered.insert(format!("{}:{}", sensor_name, action)) {
+            return None;
+        }
+        Some(device_trigger_discovery_config(
+            sensor_name,
+            action,
+            &triggers.discovery_prefix,
+            &triggers.device_info,
+            triggers.qos,
+        ))
+    }
+
+    fn fire_trigger(&self, sensor: &SystemSensor, action: &str, qos: QoS) -> Option<MqttPayload> {
+        let triggers = self.device_triggers.as_ref()?;
+        let payload = json!({
+            "action": action,
+            "device_id": triggers.device_info.identifiers.first(),
+            "sensor": sensor.name,
+            "value": sensor.value,
+        });
+        Some(MqttPayload {
+            topic: device_trigger_topic(&triggers.discovery_prefix, &triggers.device_info.name, &sensor.name),
+            payload: payload.to_string(),
+            retain: false,
+            qos,
+            payload_bytes: None,
+        })
+    }
+
+    fn update(
+        &mut self,
+        key: String,
+        sensor: &SystemSensor,
+        limit: Limit,
+        limit_value: f64,
+        hysteresis: f64,
+    ) -> Option<serde_json::Value> {
+        let value = sensor.value;
+        if self.active.contains(&key) {
+            let recovered = match limit {
+                Limit::High => value < limit_value - hysteresis,
+                Limit::Low => value > limit_value + hysteresis,
+            };
+            if !recovered {
+                return None;
+            }
+            self.active.remove(&key);
+            Some(json!({ "sensor": sensor.name, "value": value, "state": "ok" }))
+        } else {
+            let crossed = match limit {
+                Limit::High => value > limit_value,
+                Limit::Low => value < limit_value,
+            };
+            if !crossed {
+                return None;
+            }
+            self.active.insert(key);
+            Some(json!({
+                "sensor": sensor.name,
+                "value": value,
+                "threshold": limit_value,
+                "state": "triggered",
+            }))
+        }
+    }
+}
+
+// "temperature_high", "cpu_usage_low": the sensor type as written in the config plus the limit
+fn trigger_action(sensor: &SystemSensor, limit: Limit) -> String {
+    let sensor_type = serde_json::to_value(&sensor.sensor_type)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_else(|| "sensor".to_string());
+    format!("{}_{}", sensor_type, limit.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sensors::SystemSensorType;
+    use serde_json::Value;
+    use std::collections::HashMap;
+
+    fn sensor(value: f64) -> SystemSensor {
+        SystemSensor {
+            name: "k10temp_1".to_string(),
+            label: None,
+            value,
+            unit: "°C".to_string(),
+            sensor_type: SystemSensorType::Temperature,
+            attributes: HashMap::new(),
+            extra: None,
+        }
+    }
+
+    fn states(alerts: &[MqttPayload]) -> Vec<String> {
+        alerts
+            .iter()
+            .map(|alert| {
+                let payload: Value = serde_json::from_str(&alert.payload).unwrap();
+                payload["state"].as_str().unwrap().to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_high_threshold_with_hysteresis() {
+        let thresholds = [ThresholdConfig {
+            sensor_pattern: "k10temp_*".to_string(),
+            high_value: Some(85.0),
+            low_value: None,
+            alert_topic: "orbiq/alerts".to_string(),
+            hysteresis: 2.0,
+            device_trigger: false,
+        }];
+        let mut tracker = AlertTracker::default();
+        let mut check = |value| tracker.check(&[sensor(value)], &thresholds, QoS::AtLeastOnce);
+
+        assert!(check(80.0).is_empty());
+
+        let triggered = check(87.5);
+        assert_eq!(states(&triggered), ["triggered"]);
+        assert_eq!(triggered[0].topic, "orbiq/alerts");
+        let payload: Value = serde_json::from_str(&triggered[0].payload).unwrap();
+        assert_eq!(payload["threshold"], 85.0);
+
+        // Still triggered, and dipping below the limit but within the hysteresis doesn't clear it
+        assert!(check(88.0).is_empty());
+        assert!(check(84.0).is_empty());
+        assert_eq!(states(&check(82.5)), ["ok"]);
+        assert!(check(82.0).is_empty());
+    }
+
+    #[test]
+    fn test_low_threshold() {
+        let thresholds = [ThresholdConfig {
+            sensor_pattern: "k10temp_1".to_string(),
+            high_value: None,
+            low_value: Some(10.0),
+            alert_topic: "orbiq/alerts".to_string(),
+            hysteresis: 1.0,
+            device_trigger: false,
+        }];
+        let mut tracker = AlertTracker::default();
+        let mut check = |value| tracker.check(&[sensor(value)], &thresholds, QoS::AtLeastOnce);
+
+        assert_eq!(states(&check(5.0)), ["triggered"]);
+        assert!(check(10.5).is_empty());
+        assert_eq!(states(&check(11.5)), ["ok"]);
+    }
+
+    #[test]
+    fn test_device_trigger() {
+        let thresholds = [ThresholdConfig {
+            sensor_pattern: "k10temp_*".to_string(),
+            high_value: Some(85.0),
+            low_value: None,
+            alert_topic: "orbiq/alerts".to_string(),
+            hysteresis: 2.0,
+            device_trigger: true,
+        }];
+        let device_info = DeviceInfo::from_config(&crate::config::DeviceConfig {
+            name: "server".to_string(),
+            ..crate::config::DeviceConfig::default()
+        });
+        let mut tracker = AlertTracker::with_device_triggers("homeassistant", &device_info, QoS::AtLeastOnce);
+        let mut check = |value| tracker.check(&[sensor(value)], &thresholds, QoS::AtLeastOnce);
+        let topics = |payloads: &[MqttPayload]| payloads.iter().map(|p| p.topic.clone()).collect::<Vec<_>>();
+
+        // Discovered on the first match, once
+        let first = check(80.0);
+        assert_eq!(
+            topics(&first),
+            ["homeassistant/device_automation/orbiq_server/k10temp_1_temperature_high/config"]
+        );
+        assert!(first[0].retain);
+        let config: Value = serde_json::from_str(&first[0].payload).unwrap();
+        assert_eq!(config["automation_type"], "trigger");
+        assert_eq!(config["type"], "temperature_high");
+        assert_eq!(config["subtype"], "k10temp_1");
+        assert_eq!(config["topic"], "homeassistant/device_automation/orbiq_server/k10temp_1/action");
+
+        let crossed = check(87.0);
+        assert_eq!(
+            topics(&crossed),
+            ["orbiq/alerts", "homeassistant/device_automation/orbiq_server/k10temp_1/action"]
+        );
+        let action: Value = serde_json::from_str(&crossed[1].payload).unwrap();
+        assert_eq!(action["action"], "temperature_high");
+        assert_eq!(action["device_id"], "orbiq_server");
+
+        // Edge triggered: nothing while above, and recovering only clears the alert
+        assert!(check(88.0).is_empty());
+        assert_eq!(topics(&check(80.0)), ["orbiq/alerts"]);
+        assert_eq!(topics(&check(87.0)).len(), 2);
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use crate::hwmon_devices::{discover_hwmon_devices, HwmonDevice};
+use crate::sensors::{CollectFuture, SensorCollector, SystemSensor};
+use crate::sensors::SystemSensorType::Power;
+use crate::sysfs::read_number;
+
+const AMDGPU_DEVICE: &str = "amdgpu";
+// Older amdgpu kernels only report an averaged power1_average, which power_sensor skips
+const POWER_AVERAGE_FILE: &str = "power1_average";
+const MICROWATT_TO_WATT: f64 = 1_000_000.0;
+
+// amdgpu power on kernels that only report power1_average
+pub struct AmdgpuCollector {
+    pub hwmon_cache_ttl: Duration,
+    pub read_timeout: Duration,
+}
+
+impl SensorCollector for AmdgpuCollector {
+    fn collect(&self) -> CollectFuture<'_> {
+        Box::pin(collect_amdgpu(self.hwmon_cache_ttl, self.read_timeout))
+    }
+
+    fn name(&self) -> &str {
+        "amdgpu"
+    }
+}
+
+async fn collect_amdgpu(hwmon_cache_ttl: Duration, read_timeout: Duration) -> Vec<SystemSensor> {
+    let devices = match discover_hwmon_devices(hwmon_cache_ttl) {
+        Ok(devices) => devices,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to discover hwmon devices");
+            return Vec::new();
+        }
+    };
+
+    let mut sensors = Vec::new();
+    for device in devices.iter().filter(|device| device.name == AMDGPU_DEVICE) {
+        if let Some(sensor) = read_average_power(device, read_timeout).await {
+            sensors.push(sensor);
+        }
+    }
+    sensors
+}
+
+async fn read_average_power(device: &HwmonDevice, read_timeout: Duration) -> Option<SystemSensor> {
+    let microwatts = read_number(&device.path.join(POWER_AVERAGE_FILE), read_timeout).await?;
+    Some(SystemSensor {
+        name: format!("{}_1_power_average", device.name),
+        label: Some("AMD GPU Power".to_string()),
+        value: microwatts / MICROWATT_TO_WATT,
+        unit: "W".to_string(),
+        sensor_type: Power,
+        attributes: HashMap::new(),
+        extra: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[tokio::test]
+    async fn test_read_average_power() {
+        let dir = std::env::temp_dir().join("orbiq_amdgpu_sensor_test");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("power1_average"), "42000000\n").unwrap();
+
+        let device = HwmonDevice {
+            path: dir.clone(),
+            name: "amdgpu".to_string(),
+        };
+        let sensor = read_average_power(&device, Duration::from_millis(500)).await;
+        fs::remove_dir_all(&dir).unwrap();
+
+        let sensor = sensor.unwrap();
+        assert_eq!(sensor.name, "amdgpu_1_power_average");
+        assert_eq!(sensor.value, 42.0);
+        assert_eq!(sensor.unit, "W");
+    }
+}
@@ -0,0 +1,67 @@
+use crate::config::CalibrationEntry;
+use crate::sensors::SystemSensor;
+
+// Applies the first entry whose sensor_name matches exactly
+pub fn apply_calibration(sensor: &mut SystemSensor, cal: &[CalibrationEntry]) {
+    if let Some(entry) = cal.iter().find(|entry| entry.sensor_name == sensor.name) {
+        sensor.value = sensor.value * entry.scale + entry.offset;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sensors::SystemSensorType;
+    use std::collections::HashMap;
+
+    fn temperature(value: f64) -> SystemSensor {
+        SystemSensor {
+            name: "k10temp_1".to_string(),
+            label: None,
+            value,
+            unit: "°C".to_string(),
+            sensor_type: SystemSensorType::Temperature,
+            attributes: HashMap::new(),
+            extra: None,
+        }
+    }
+
+    #[test]
+    fn test_apply_calibration_offset() {
+        let cal = [CalibrationEntry {
+            sensor_name: "k10temp_1".to_string(),
+            offset: -2.0,
+            scale: 1.0,
+        }];
+        let mut sensor = temperature(47.5);
+        apply_calibration(&mut sensor, &cal);
+        assert_eq!(sensor.value, 45.5);
+    }
+
+    #[test]
+    fn test_apply_calibration_scale_then_offset() {
+        let cal = [CalibrationEntry {
+            sensor_name: "k10temp_1".to_string(),
+            offset: 1.0,
+            scale: 2.0,
+        }];
+        let mut sensor = temperature(10.0);
+        apply_calibration(&mut sensor, &cal);
+        assert_eq!(sensor.value, 21.0);
+    }
+
+    #[test]
+    fn test_apply_calibration_without_entry() {
+        let cal = [CalibrationEntry {
+            sensor_name: "k10temp_2".to_string(),
+            offset: -2.0,
+            scale: 1.0,
+        }];
+        let mut sensor = temperature(47.5);
+        apply_calibration(&mut sensor, &cal);
+        assert_eq!(sensor.value, 47.5);
+
+        apply_calibration(&mut sensor, &[]);
+        assert_eq!(sensor.value, 47.5);
+    }
+}
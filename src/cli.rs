@@ -0,0 +1,86 @@
+use crate::config::LogLevel;
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+// Command line options, these override whatever the config file says
+#[derive(Debug, Clone, Default, Parser)]
+#[command(version, about)]
+pub struct CliArgs {
+    /// Config file to load instead of searching the default locations
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// MQTT broker hostname or IP address
+    #[arg(long, value_name = "HOST")]
+    pub broker: Option<String>,
+
+    /// MQTT broker port
+    #[arg(long, value_name = "PORT")]
+    pub port: Option<u16>,
+
+    /// Device name used for the Home Assistant device and MQTT topics
+    #[arg(long, value_name = "NAME")]
+    pub device_name: Option<String>,
+
+    /// Seconds between sensor updates
+    #[arg(long, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub update_interval: Option<u64>,
+
+    /// Log verbosity
+    #[arg(long, value_name = "LEVEL", value_enum)]
+    pub log_level: Option<LogLevel>,
+
+    /// Validate the config, collect sensors once and print what would be published without connecting to MQTT
+    #[arg(long, visible_alias = "validate")]
+    pub dry_run: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Print a fully annotated default config file
+    GenerateConfig {
+        /// Write the config to this file instead of stdout
+        #[arg(long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    /// Write the effective config (config file, environment and command line overrides) as plain TOML
+    SaveConfig {
+        /// File to write
+        #[arg(long, value_name = "PATH")]
+        output: PathBuf,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_overrides() {
+        let args = CliArgs::try_parse_from([
+            "orbiq_system_monitor",
+            "--broker",
+            "192.168.1.10",
+            "--device-name",
+            "server-rack-1",
+            "--log-level",
+            "debug",
+        ])
+        .unwrap();
+
+        assert_eq!(args.broker.as_deref(), Some("192.168.1.10"));
+        assert_eq!(args.device_name.as_deref(), Some("server-rack-1"));
+        assert_eq!(args.log_level, Some(LogLevel::Debug));
+        assert!(args.config.is_none());
+        assert!(!args.dry_run);
+        assert!(CliArgs::try_parse_from(["orbiq_system_monitor", "--validate"]).unwrap().dry_run);
+
+        let args = CliArgs::try_parse_from(["orbiq_system_monitor", "generate-config"]).unwrap();
+        assert!(matches!(args.command, Some(Command::GenerateConfig { output: None })));
+        assert!(CliArgs::try_parse_from(["orbiq_system_monitor", "--update-interval", "0"]).is_err());
+        assert!(CliArgs::try_parse_from(["orbiq_system_monitor", "save-config"]).is_err());
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::task::JoinSet;
+use crate::config::CommandSensorConfig;
+use crate::naming::sanitize_sensor_name;
+use crate::sensors::{CollectFuture, SensorCollector, SystemSensor, SystemSensorType};
+
+// Commands currently failing, so a broken script is logged once rather than every cycle
+static FAILING_COMMANDS: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+// Scripts printing a single number, e.g. `check_ups_battery.sh` -> "87"
+pub struct CommandCollector {
+    pub commands: Vec<CommandSensorConfig>,
+}
+
+impl SensorCollector for CommandCollector {
+    fn collect(&self) -> CollectFuture<'_> {
+        Box::pin(collect_commands(&self.commands))
+    }
+
+    fn name(&self) -> &str {
+        "command"
+    }
+}
+
+// Commands run concurrently; a hanging script only costs its own timeout
+async fn collect_commands(commands: &[CommandSensorConfig]) -> Vec<SystemSensor> {
+    let mut tasks = JoinSet::new();
+    for (index, command) in commands.iter().cloned().enumerate() {
+        tasks.spawn(async move { (index, run_command(&command).await, command) });
+    }
+
+    let mut results = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        match result {
+            Ok(result) => results.push(result),
+            Err(e) => tracing::error!(error = %e, "Command sensor task failed"),
+        }
+    }
+    // Keep the config order, tasks finish in any order
+    results.sort_by_key(|(index, _, _)| *index);
+
+    let mut sensors = Vec::new();
+    for (_, result, command) in results {
+        match result {
+            Ok(value) => {
+                mark_recovered(&command.name);
+                sensors.push(command_sensor(&command, value));
+            }
+            Err(e) => warn_command(&command.name, &e),
+        }
+    }
+    sensors
+}
+
+async fn run_command(command: &CommandSensorConfig) -> Result<f64, String> {
+    let output = Command::new(&command.command).args(&command.args).kill_on_drop(true).output();
+    let output = match tokio::time::timeout(Duration::from_millis(command.timeout_ms), output).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => return Err(format!("failed to run {}: {}", command.command, e)),
+        Err(_) => return Err(format!("{} timed out after {} ms", command.command, command.timeout_ms)),
+    };
+    if !output.status.success() {
+        return Err(format!("{} exited with {}", command.command, output.status));
+    }
+    parse_output(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_output(stdout: &str) -> Result<f64, String> {
+    let stdout = stdout.trim();
+    stdout
+        .parse()
+        .map_err(|_| format!("expected a number, got {:?}", stdout))
+}
+
+fn command_sensor(command: &CommandSensorConfig, value: f64) -> SystemSensor {
+    SystemSensor {
+        name: sanitize_sensor_name(&command.name),
+        label: None,
+        value,
+        unit: command.unit.clone(),
+        // Checked when the config is loaded
+        sensor_type: command_sensor_type(&command.sensor_type).unwrap_or(SystemSensorType::Generic),
+        attributes: HashMap::new(),
+        extra: None,
+    }
+}
+
+// Any sensor type by its config name, e.g. "temperature" or "fan", or "generic"
+pub fn command_sensor_type(name: &str) -> Option<SystemSensorType> {
+    serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+}
+
+fn warn_command(name: &str, error: &str) {
+    let failing = FAILING_COMMANDS.get_or_init(|| Mutex::new(HashSet::new()));
+    let mut failing = failing.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if failing.insert(name.to_string()) {
+        tracing::warn!(sensor = %name, error = %error, "Command sensor failed, skipping it until it succeeds");
+    }
+}
+
+fn mark_recovered(name: &str) {
+    let failing = FAILING_COMMANDS.get_or_init(|| Mutex::new(HashSet::new()));
+    let mut failing = failing.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if failing.remove(name) {
+        tracing::info!(sensor = %name, "Command sensor is working again");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(name: &str, command: &str, args: &[&str]) -> CommandSensorConfig {
+        CommandSensorConfig {
+            name: name.to_string(),
+            command: command.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            unit: "%".to_string(),
+            sensor_type: "battery_percent".to_string(),
+            timeout_ms: 1000,
+        }
+    }
+
+    #[test]
+    fn test_parse_output() {
+        assert_eq!(parse_output("87\n").unwrap(), 87.0);
+        assert_eq!(parse_output("  -1.5 ").unwrap(), -1.5);
+        assert!(parse_output("OK").is_err());
+        assert!(parse_output("").is_err());
+    }
+
+    #[test]
+    fn test_command_sensor_type() {
+        assert_eq!(command_sensor_type("temperature"), Some(SystemSensorType::Temperature));
+        assert_eq!(command_sensor_type("generic"), Some(SystemSensorType::Generic));
+        assert_eq!(command_sensor_type("thermometer"), None);
+    }
+
+    #[tokio::test]
+    async fn test_collect_commands() {
+        let commands = [
+            command("ups_battery", "echo", &["87"]),
+            command("broken", "false", &[]),
+            command("slow", "sleep", &["5"]),
+            command("not_a_number", "echo", &["OK"]),
+        ];
+        let commands = commands
+            .into_iter()
+            .map(|c| CommandSensorConfig { timeout_ms: 200, ..c })
+            .collect::<Vec<_>>();
+
+        let sensors = collect_commands(&commands).await;
+        assert_eq!(sensors.len(), 1);
+        assert_eq!(sensors[0].name, "ups_battery");
+        assert_eq!(sensors[0].value, 87.0);
+        assert_eq!(sensors[0].unit, "%");
+        assert_eq!(sensors[0].sensor_type, SystemSensorType::BatteryPercent);
+    }
+}
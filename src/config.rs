@@ -1,17 +1,118 @@
+use crate::cli::CliArgs;
+use crate::command_sensor::command_sensor_type;
+use crate::sensors::SystemSensorType;
+use crate::utils::matches_pattern;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::Path;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+// device.tags entry that publishes every message with at least QoS 1
+const HIGH_PRIORITY_TAG: &str = "high-priority";
+
+// Longer pauses between discovery messages make startup take minutes on hosts with many sensors
+const MAX_DISCOVERY_DELAY_MS: u64 = 10_000;
+// Brokers disconnect clients after 1.5x the keep alive, shorter values cause spurious reconnects
+const MIN_KEEP_ALIVE_SECS: u64 = 5;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(default)]
 pub struct MqttConfig {
+    pub enabled: bool, // Disable to only serve metrics over HTTP
     pub broker: String,
     pub port: u16,
     pub username: Option<String>,
     pub password: Option<String>,
+    pub brokers: Vec<MqttBrokerConfig>, // Failover list, replaces broker/port when not empty
     #[serde(skip)] // Don't serialize/deserialize client_id - it's auto-generated
     pub client_id: String,
-    pub keep_alive_secs: u64,
+    pub keep_alive_secs: u64, // MQTT PINGREQ interval, not TCP keepalive
+    pub tcp_keepalive_secs: Option<u64>, // Socket level TCP keepalive, rejected until the client supports it
+    pub reconnect_min_secs: u64, // First reconnect delay, doubled after every failed attempt
+    pub reconnect_max_secs: u64,
+    pub reconnect_jitter_factor: f64, // Delays vary randomly by up to this fraction
+    pub tls: TlsConfig,
+    pub last_will_qos: QosLevel,
+    pub last_will_retain: bool,
+    pub use_device_availability: bool, // Sensors also follow the device-level (last will) availability topic
+    pub qos: QosConfig,
+    pub discovery_prefix: String, // Root of every published topic
+    pub publish_batch_size: usize, // Sensors published before pausing for batch_delay_ms
+    pub batch_delay_ms: u64,
+    pub payload_format: PayloadFormat,
+    pub availability_mode: AvailabilityMode,
+    pub discovery_ttl_secs: u64, // Discovery and availability are re-sent after this long, 0 never
+    pub protocol_version: ProtocolVersion,
+    pub user_properties: BTreeMap<String, String>, // Attached to every publish, MQTT 5 only
+}
+
+// One entry of [[mqtt.brokers]]; without credentials of its own the [mqtt] ones are used
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct MqttBrokerConfig {
+    pub broker: String,
+    #[serde(default = "default_mqtt_port")]
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+fn default_mqtt_port() -> u16 {
+    1883
+}
+
+// Frequent state updates can use QoS 0 to reduce broker load, discovery and
+// availability should stay at 1 so Home Assistant doesn't miss them
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct QosConfig {
+    pub state_qos: QosLevel,
+    pub discovery_qos: QosLevel,
+    pub availability_qos: QosLevel,
+}
+
+// MQTT QoS level, written as 0, 1 or 2 in the config file
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(try_from = "u8", into = "u8")]
+#[allow(clippy::enum_variant_names)] // Named after rumqttc::QoS
+pub enum QosLevel {
+    AtMostOnce,
+    AtLeastOnce,
+    ExactlyOnce,
+}
+
+impl TryFrom<u8> for QosLevel {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(QosLevel::AtMostOnce),
+            1 => Ok(QosLevel::AtLeastOnce),
+            2 => Ok(QosLevel::ExactlyOnce),
+            other => Err(format!("invalid QoS level {}, expected 0, 1 or 2", other)),
+        }
+    }
+}
+
+impl From<QosLevel> for u8 {
+    fn from(qos: QosLevel) -> Self {
+        match qos {
+            QosLevel::AtMostOnce => 0,
+            QosLevel::AtLeastOnce => 1,
+            QosLevel::ExactlyOnce => 2,
+        }
+    }
+}
+
+// TLS is used as soon as any of these is set
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
+pub struct TlsConfig {
+    pub ca_cert: Option<PathBuf>,     // PEM CA certificate used to verify the broker
+    pub client_cert: Option<PathBuf>, // PEM client certificate for mutual TLS
+    pub client_key: Option<PathBuf>,  // PEM private key belonging to client_cert
+    pub insecure: bool,               // Skip broker certificate verification (self-signed certs)
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -24,6 +125,386 @@ pub struct DeviceConfig {
     pub manufacturer: String,
     pub sw_version: Option<String>,
     pub hw_version: Option<String>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct SensorsConfig {
+    pub mqtt_attributes: Vec<MqttAttributeConfig>,
+    pub enable_temperature: bool,
+    pub temperature_limits: bool, // Also publish tempN_max / tempN_crit
+    pub enable_fan: bool,
+    pub fan_pwm: bool, // Also publish pwmN duty cycles of hwmon devices
+    pub per_core_cpu: bool,
+    pub voltage: bool,
+    pub cpu_frequency: bool,
+    pub psi: bool, // Pressure stall information from /proc/pressure
+    pub thermal_zones: bool, // Temperatures from /sys/class/thermal zones that have no hwmon device
+    pub smart: bool, // SMART attributes of smart_devices via smartctl
+    pub network_interfaces: Vec<String>, // Empty means every interface except loopback
+    pub include_loopback: bool,
+    pub disk_io_partitions: bool, // Also publish I/O rates for partitions, not just whole disks
+    pub disk_inodes: bool, // Inode usage per mount point
+    pub low_entropy_threshold: u64,
+    pub enable_iio_sensors: bool,
+    pub fan: FanConfig,
+    pub labels: HashMap<String, String>, // Sensor name -> label shown in Home Assistant
+    pub max_value_age_secs: Option<u64>, // Overrides publish.force_publish_interval_secs
+    pub read_timeout_ms: u64, // Per-file read timeout for sysfs/procfs sensors
+    pub dedup_strategy: DedupStrategy,
+    pub temperature_unit: TemperatureUnit,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum StartupCheckMode {
+    Warn, // Log failed startup checks only
+    Fail, // Exit when an error-level startup check fails
+}
+
+// Which reading wins when several sensors end up with the same name
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DedupStrategy {
+    #[default]
+    First,
+    Last,
+    Max,
+    Min,
+}
+
+// Whether every sensor has its own availability topic or all share the device's
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AvailabilityMode {
+    #[default]
+    PerSensor,
+    Device,
+}
+
+// MQTT protocol spoken with the broker
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+pub enum ProtocolVersion {
+    #[default]
+    #[serde(rename = "v3.1.1")]
+    V311,
+    #[serde(rename = "v5")]
+    V5,
+}
+
+// Encoding of sensor state payloads; discovery and availability are always JSON
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadFormat {
+    #[default]
+    Json,
+    Msgpack,
+}
+
+// Unit every temperature sensor is published in; readings are collected in Celsius
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+pub enum TemperatureUnit {
+    #[default]
+    C,
+    F,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RpmToPercentMethod {
+    Hwmon,  // Use the fanN_min / fanN_max files exposed by the driver
+    Manual, // Use fan_min_rpm / fan_max_rpm from the config
+    None,   // Don't publish fan percentage sensors
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct FanConfig {
+    pub rpm_to_percent_method: RpmToPercentMethod,
+    pub fan_min_rpm: f64,
+    pub fan_max_rpm: f64,
+    pub overrides: Vec<FanOverride>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct FanOverride {
+    pub sensor_name: String,
+    pub fan_min_rpm: Option<f64>,
+    pub fan_max_rpm: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
+pub struct FiltersConfig {
+    pub disabled_types: Vec<SystemSensorType>,
+    pub include_sensors: Option<Vec<String>>,
+    pub exclude_sensors: Vec<String>,
+    pub suppress_zero_types: Vec<SystemSensorType>, // e.g. stopped or unconnected fans
+    pub value_ranges: Vec<ValueRangeConfig>,
+}
+
+// Sensors matching the pattern are dropped when their value is outside [min, max]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ValueRangeConfig {
+    pub sensor_pattern: String,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+// Publishes an alert to alert_topic when a matching sensor crosses high_value or low_value
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ThresholdConfig {
+    pub sensor_pattern: String,
+    pub high_value: Option<f64>,
+    pub low_value: Option<f64>,
+    pub alert_topic: String,
+    #[serde(default = "default_hysteresis")]
+    pub hysteresis: f64, // How far the value must recover before the alert clears
+    #[serde(default)]
+    pub device_trigger: bool, // Also fire a Home Assistant device trigger when a limit is crossed
+}
+
+fn default_hysteresis() -> f64 {
+    2.0
+}
+
+// Corrects a sensor reading as (raw_value * scale) + offset
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CalibrationEntry {
+    pub sensor_name: String, // Exact sensor name, not a pattern
+    #[serde(default)]
+    pub offset: f64,
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+// Settings for sensors matching the pattern: their own publish interval instead of
+// update_interval_secs and/or the Home Assistant entity category
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
+pub struct SensorOverrideConfig {
+    pub name_pattern: String,
+    pub update_interval_secs: Option<u64>,
+    pub entity_category: Option<EntityCategory>,
+}
+
+// Home Assistant shows diagnostic entities apart from the main dashboard. Its "config"
+// category is only accepted for entities that can be changed, not for sensors
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EntityCategory {
+    Diagnostic,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessMetric {
+    Cpu,         // Summed CPU usage, can exceed 100% on multi-core machines
+    MemoryMb,    // Summed resident memory
+    ThreadCount, // Summed threads
+}
+
+// Processes whose name contains `name` are summed into proc_<name>_* sensors
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct WatchProcessConfig {
+    pub name: String,
+    #[serde(default = "default_process_metrics")]
+    pub metrics: Vec<ProcessMetric>,
+}
+
+fn default_process_metrics() -> Vec<ProcessMetric> {
+    vec![ProcessMetric::Cpu, ProcessMetric::MemoryMb]
+}
+
+// Home Assistant name for one sensor, replacing its hwmon label or generated name
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LabelOverride {
+    pub name: String,
+    pub label: String,
+}
+
+// DS18B20 probe on the 1-Wire bus, identified by its directory name in /sys/bus/w1/devices
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct OneWireSensorConfig {
+    pub id: String,
+    pub label: Option<String>,
+}
+
+// Script printing a single number, run every cycle
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CommandSensorConfig {
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub unit: String,
+    #[serde(default = "default_command_sensor_type")]
+    pub sensor_type: String, // A sensor type name such as "temperature", or "generic"
+    #[serde(default = "default_command_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_command_sensor_type() -> String {
+    "generic".to_string()
+}
+
+fn default_command_timeout_ms() -> u64 {
+    5000
+}
+
+// Number read from a JSON file another tool writes, found by a dot-separated path
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct JsonFileSensorConfig {
+    pub name: String,
+    pub path: PathBuf,
+    pub json_path: String, // e.g. "temperatures.cpu.value", numeric segments index arrays
+    #[serde(default)]
+    pub unit: String,
+    pub label: Option<String>,
+}
+
+// Host whose hwmon sensors are read over ssh; the key must already be authorized there
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RemoteDeviceConfig {
+    pub name: String, // Prefix of the remote sensor names
+    pub ssh_host: String,
+    pub ssh_user: String,
+    pub ssh_key_path: PathBuf,
+    #[serde(default = "default_ssh_port")]
+    pub ssh_port: u16,
+}
+
+fn default_ssh_port() -> u16 {
+    22
+}
+
+// Block device read with `smartctl` when sensors.smart is enabled
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SmartDeviceConfig {
+    pub path: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MqttAttributeConfig {
+    pub sensor_pattern: String,
+    pub attribute_name: String,
+    pub mqtt_topic: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct LoggingConfig {
+    pub level: LogLevel,
+}
+
+impl From<LogLevel> for tracing::Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => tracing::Level::ERROR,
+            LogLevel::Warn => tracing::Level::WARN,
+            LogLevel::Info => tracing::Level::INFO,
+            LogLevel::Debug => tracing::Level::DEBUG,
+            LogLevel::Trace => tracing::Level::TRACE,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct PublishConfig {
+    pub delta_threshold: f64,             // Minimum change before a new value is published, 0.0 publishes every change
+    pub force_publish_interval_secs: u64, // Unchanged values are republished after this long
+    pub aggregate_topic: Option<String>, // Every cycle's values as one JSON object
+    pub aggregate_only: bool, // Skip the per-sensor topics and discovery, needs aggregate_topic
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct PrometheusConfig {
+    pub enabled: bool,
+    pub port: u16, // Serves /metrics on all interfaces
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct HttpApiConfig {
+    pub enabled: bool,
+    pub port: u16, // Serves /sensors and /health on all interfaces
+}
+
+// HTTP (url, token, org, bucket) takes precedence over UDP (udp_host, udp_port)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct InfluxDbConfig {
+    pub enabled: bool,
+    pub url: Option<String>, // e.g. http://localhost:8086 for the v2 API
+    pub token: Option<String>,
+    pub org: Option<String>,
+    pub bucket: Option<String>,
+    pub udp_host: Option<String>,
+    pub udp_port: u16,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct StatsdConfig {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub prefix: String,
+    pub tags: Vec<String>, // DogStatsD tags added to every gauge, e.g. "env:prod"
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct SmoothingConfig {
+    pub ema_alpha: f64, // 0.0 < alpha <= 1.0, 1.0 disables smoothing
+    pub overrides: Vec<SmoothingOverrideConfig>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
+pub struct HistoryConfig {
+    pub window_size: usize, // Readings kept per sensor for min/max/avg, 0 disables history
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct PingConfig {
+    pub targets: Vec<String>, // Host names or addresses, each published as ping_<target>_latency_ms
+    pub interval_secs: u64,
+    pub timeout_ms: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct DaemonSettingsConfig {
+    pub self_monitoring: bool, // Publish the daemon's own MQTT counters as sensors
+    pub shutdown_timeout_secs: u64, // Time given to the offline messages before exiting anyway
+}
+
+// Smoothing for sensors matching the pattern and/or type; the first matching entry wins
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SmoothingOverrideConfig {
+    pub sensor_pattern: Option<String>,
+    pub sensor_type: Option<SystemSensorType>,
+    pub ema_alpha: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -31,23 +512,86 @@ pub struct DeviceConfig {
 pub struct DaemonConfig {
     pub mqtt: MqttConfig,
     pub device: DeviceConfig,
+    pub sensors: SensorsConfig,
+    pub filters: FiltersConfig,
+    pub logging: LoggingConfig,
+    pub publish: PublishConfig,
+    pub smoothing: SmoothingConfig,
+    pub history: HistoryConfig,
+    pub ping: PingConfig,
+    pub daemon: DaemonSettingsConfig,
+    pub prometheus: PrometheusConfig,
+    pub http_api: HttpApiConfig,
+    pub influxdb: InfluxDbConfig,
+    pub statsd: StatsdConfig,
     pub update_interval_secs: u64,
     pub discovery_delay_ms: u64,
+    pub auto_scale_units: bool,
+    pub hwmon_device_cache_secs: u64,
+    pub state_file: Option<PathBuf>, // Remembers discovered sensors across restarts
+    pub startup_check_mode: StartupCheckMode,
+    pub sensor_overrides: Vec<SensorOverrideConfig>,
+    pub thresholds: Vec<ThresholdConfig>,
+    pub calibration: Vec<CalibrationEntry>,
+    pub watch_processes: Vec<WatchProcessConfig>,
+    pub smart_devices: Vec<SmartDeviceConfig>,
+    pub onewire_sensors: Vec<OneWireSensorConfig>,
+    pub remote_devices: Vec<RemoteDeviceConfig>,
+    pub sensor_labels: Vec<LabelOverride>,
+    pub command_sensors: Vec<CommandSensorConfig>,
+    pub json_file_sensors: Vec<JsonFileSensorConfig>,
+    #[serde(skip)] // Path the config was loaded from, None when running on defaults
+    pub source_path: Option<PathBuf>,
 }
 
 impl Default for MqttConfig {
     fn default() -> Self {
         Self {
+            enabled: true,
             broker: "localhost".to_string(),
             port: 1883,
             username: None,
             password: None,
+            brokers: Vec::new(),
             client_id: "orbiq-default".to_string(), // Will be overridden
             keep_alive_secs: 30,
+            reconnect_min_secs: 1,
+            reconnect_max_secs: 60,
+            reconnect_jitter_factor: 0.25,
+            tcp_keepalive_secs: None,
+            tls: TlsConfig::default(),
+            last_will_qos: QosLevel::AtLeastOnce,
+            last_will_retain: true,
+            use_device_availability: false,
+            qos: QosConfig::default(),
+            discovery_prefix: "homeassistant".to_string(),
+            publish_batch_size: 10,
+            batch_delay_ms: 5,
+            payload_format: PayloadFormat::Json,
+            availability_mode: AvailabilityMode::PerSensor,
+            discovery_ttl_secs: 3600,
+            protocol_version: ProtocolVersion::V311,
+            user_properties: BTreeMap::new(),
         }
     }
 }
 
+impl Default for QosConfig {
+    fn default() -> Self {
+        Self {
+            state_qos: QosLevel::AtLeastOnce,
+            discovery_qos: QosLevel::AtLeastOnce,
+            availability_qos: QosLevel::AtLeastOnce,
+        }
+    }
+}
+
+impl TlsConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.ca_cert.is_some() || self.client_cert.is_some() || self.client_key.is_some() || self.insecure
+    }
+}
+
 impl Default for DeviceConfig {
     fn default() -> Self {
         Self {
@@ -56,6 +600,48 @@ impl Default for DeviceConfig {
             manufacturer: "OrbIQ".to_string(), // Hardcoded
             sw_version: Some(env!("CARGO_PKG_VERSION").to_string()),
             hw_version: Some("1.0".to_string()),
+            tags: Vec::new(),
+        }
+    }
+}
+
+impl Default for FanConfig {
+    fn default() -> Self {
+        Self {
+            rpm_to_percent_method: RpmToPercentMethod::None,
+            fan_min_rpm: 300.0,
+            fan_max_rpm: 3000.0,
+            overrides: Vec::new(),
+        }
+    }
+}
+
+impl Default for SensorsConfig {
+    fn default() -> Self {
+        Self {
+            mqtt_attributes: Vec::new(),
+            enable_temperature: true,
+            temperature_limits: false,
+            enable_fan: true,
+            fan_pwm: false,
+            per_core_cpu: false,
+            voltage: false,
+            cpu_frequency: false,
+            psi: true,
+            thermal_zones: true,
+            smart: false,
+            network_interfaces: Vec::new(),
+            include_loopback: false,
+            disk_io_partitions: false,
+            disk_inodes: false,
+            low_entropy_threshold: 128,
+            enable_iio_sensors: true,
+            fan: FanConfig::default(),
+            labels: HashMap::new(),
+            max_value_age_secs: None,
+            read_timeout_ms: 500,
+            dedup_strategy: DedupStrategy::First,
+            temperature_unit: TemperatureUnit::C,
         }
     }
 }
@@ -65,77 +651,981 @@ impl Default for DaemonConfig {
         Self {
             mqtt: MqttConfig::default(),
             device: DeviceConfig::default(),
+            sensors: SensorsConfig::default(),
+            filters: FiltersConfig::default(),
+            logging: LoggingConfig::default(),
+            publish: PublishConfig::default(),
+            smoothing: SmoothingConfig::default(),
+            history: HistoryConfig::default(),
+            ping: PingConfig::default(),
+            daemon: DaemonSettingsConfig::default(),
+            prometheus: PrometheusConfig::default(),
+            http_api: HttpApiConfig::default(),
+            influxdb: InfluxDbConfig::default(),
+            statsd: StatsdConfig::default(),
             update_interval_secs: 30,
             discovery_delay_ms: 100,
+            auto_scale_units: false,
+            hwmon_device_cache_secs: 60,
+            state_file: None,
+            startup_check_mode: StartupCheckMode::Warn,
+            sensor_overrides: Vec::new(),
+            thresholds: Vec::new(),
+            calibration: Vec::new(),
+            watch_processes: Vec::new(),
+            smart_devices: Vec::new(),
+            onewire_sensors: Vec::new(),
+            remote_devices: Vec::new(),
+            sensor_labels: Vec::new(),
+            command_sensors: Vec::new(),
+            json_file_sensors: Vec::new(),
+            source_path: None,
         }
     }
 }
 
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: LogLevel::Info,
+        }
+    }
+}
+
+impl Default for DaemonSettingsConfig {
+    fn default() -> Self {
+        Self {
+            self_monitoring: false,
+            shutdown_timeout_secs: 10,
+        }
+    }
+}
+
+impl Default for PublishConfig {
+    fn default() -> Self {
+        Self {
+            delta_threshold: 0.0,
+            force_publish_interval_secs: 300,
+            aggregate_topic: None,
+            aggregate_only: false,
+        }
+    }
+}
+
+impl Default for PrometheusConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 9100,
+        }
+    }
+}
+
+impl Default for HttpApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 8080,
+        }
+    }
+}
+
+impl Default for InfluxDbConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: None,
+            token: None,
+            org: None,
+            bucket: None,
+            udp_host: None,
+            udp_port: 8089,
+        }
+    }
+}
+
+impl Default for StatsdConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "localhost".to_string(),
+            port: 8125,
+            prefix: "orbiq".to_string(),
+            tags: Vec::new(),
+        }
+    }
+}
+
+impl Default for PingConfig {
+    fn default() -> Self {
+        Self {
+            targets: Vec::new(),
+            interval_secs: 30,
+            timeout_ms: 1000,
+        }
+    }
+}
+
+impl Default for SmoothingConfig {
+    fn default() -> Self {
+        Self {
+            ema_alpha: 1.0,
+            overrides: Vec::new(),
+        }
+    }
+}
+
+// mqtt.username/password, the same in [[mqtt.brokers]], and influxdb.token
+const CREDENTIAL_KEYS: &[&str] = &["username", "password", "token"];
+
+fn remove_credentials(value: &mut toml::Value) {
+    match value {
+        toml::Value::Table(table) => {
+            for key in CREDENTIAL_KEYS {
+                table.remove(*key);
+            }
+            table.iter_mut().for_each(|(_, value)| remove_credentials(value));
+        }
+        toml::Value::Array(items) => items.iter_mut().for_each(remove_credentials),
+        _ => {}
+    }
+}
+
 impl DaemonConfig {
+    // Hex encoded SHA256 of the effective configuration without credentials, since it is
+    // published. Hashed as a toml::Value, whose tables are sorted, so the random order of
+    // HashMaps like sensors.labels can't change it between runs
+    pub fn fingerprint(&self) -> Option<String> {
+        let mut value = toml::Value::try_from(self).ok()?;
+        remove_credentials(&mut value);
+        let digest = Sha256::digest(value.to_string().as_bytes());
+        Some(digest.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+
+    // A "high-priority" device tag lifts every message of the device to at least QoS 1
+    fn device_qos(&self, qos: QosLevel) -> QosLevel {
+        if qos == QosLevel::AtMostOnce && self.device.tags.iter().any(|tag| tag == HIGH_PRIORITY_TAG) {
+            QosLevel::AtLeastOnce
+        } else {
+            qos
+        }
+    }
+
+    pub fn state_qos(&self) -> QosLevel {
+        self.device_qos(self.mqtt.qos.state_qos)
+    }
+
+    pub fn discovery_qos(&self) -> QosLevel {
+        self.device_qos(self.mqtt.qos.discovery_qos)
+    }
+
+    pub fn availability_qos(&self) -> QosLevel {
+        self.device_qos(self.mqtt.qos.availability_qos)
+    }
+
+    pub fn last_will_qos(&self) -> QosLevel {
+        self.device_qos(self.mqtt.last_will_qos)
+    }
+
+    // Writes the effective configuration; client_id, the device model/manufacturer and the
+    // source path are never serialized
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigError> {
+        let content =
+            toml::to_string_pretty(self).map_err(|e| ConfigError::Serialize(path.as_ref().to_path_buf(), e))?;
+        write_to_file_atomic(path.as_ref(), &content)
+    }
+
+    // Every value that is out of range, so a broken config file can be fixed in one go
+    pub fn validate(&self) -> Vec<ConfigValidationError> {
+        let mut errors = Vec::new();
+        if self.update_interval_secs == 0 {
+            errors.push(ConfigValidationError::OutOfRange {
+                field: "update_interval_secs",
+                message: "must be at least 1".to_string(),
+            });
+        }
+        if self.discovery_delay_ms > MAX_DISCOVERY_DELAY_MS {
+            errors.push(ConfigValidationError::OutOfRange {
+                field: "discovery_delay_ms",
+                message: format!("{} is above the maximum of {}", self.discovery_delay_ms, MAX_DISCOVERY_DELAY_MS),
+            });
+        }
+        // The broker settings don't matter when only serving metrics
+        if self.mqtt.enabled {
+            if self.mqtt.port == 0 {
+                errors.push(ConfigValidationError::OutOfRange {
+                    field: "mqtt.port",
+                    message: "must be between 1 and 65535".to_string(),
+                });
+            }
+            if self.mqtt.broker.trim().is_empty() && self.mqtt.brokers.is_empty() {
+                errors.push(ConfigValidationError::InvalidValue {
+                    field: "mqtt.broker",
+                    message: "must not be empty".to_string(),
+                });
+            }
+            if self.mqtt.keep_alive_secs < MIN_KEEP_ALIVE_SECS {
+                errors.push(ConfigValidationError::OutOfRange {
+                    field: "mqtt.keep_alive_secs",
+                    message: format!("{} is below the minimum of {}", self.mqtt.keep_alive_secs, MIN_KEEP_ALIVE_SECS),
+                });
+            }
+        }
+        if let Some(tcp_keepalive_secs) = self.mqtt.tcp_keepalive_secs {
+            // TCP keepalive is only useful when it detects dead connections before the MQTT keepalive does
+            if tcp_keepalive_secs >= self.mqtt.keep_alive_secs {
+                errors.push(ConfigValidationError::OutOfRange {
+                    field: "mqtt.tcp_keepalive_secs",
+                    message: format!(
+                        "{} must be lower than mqtt.keep_alive_secs ({})",
+                        tcp_keepalive_secs, self.mqtt.keep_alive_secs
+                    ),
+                });
+            }
+            // rumqttc 0.24 neither offers a TCP keepalive option nor exposes its socket
+            errors.push(ConfigValidationError::InvalidValue {
+                field: "mqtt.tcp_keepalive_secs",
+                message: "is not supported by rumqttc 0.24, remove it".to_string(),
+            });
+        }
+        if self.mqtt.reconnect_min_secs == 0 || self.mqtt.reconnect_min_secs > self.mqtt.reconnect_max_secs {
+            errors.push(ConfigValidationError::OutOfRange {
+                field: "mqtt.reconnect_min_secs",
+                message: format!(
+                    "{} must be at least 1 and not above mqtt.reconnect_max_secs ({})",
+                    self.mqtt.reconnect_min_secs, self.mqtt.reconnect_max_secs
+                ),
+            });
+        }
+        if !(0.0..1.0).contains(&self.mqtt.reconnect_jitter_factor) {
+            errors.push(ConfigValidationError::OutOfRange {
+                field: "mqtt.reconnect_jitter_factor",
+                message: format!("{} must be in [0.0, 1.0)", self.mqtt.reconnect_jitter_factor),
+            });
+        }
+        // The prefix is used verbatim as the first topic levels, so wildcards and empty levels are rejected
+        let prefix = &self.mqtt.discovery_prefix;
+        if prefix.is_empty() || prefix.split('/').any(str::is_empty) || prefix.contains(['+', '#']) {
+            errors.push(ConfigValidationError::InvalidValue {
+                field: "mqtt.discovery_prefix",
+                message: format!("\"{}\" is not a valid topic prefix", prefix),
+            });
+        }
+        if self.mqtt.publish_batch_size == 0 {
+            errors.push(ConfigValidationError::OutOfRange {
+                field: "mqtt.publish_batch_size",
+                message: "must be at least 1".to_string(),
+            });
+        }
+        if self.publish.aggregate_only && self.publish.aggregate_topic.is_none() {
+            errors.push(ConfigValidationError::InvalidValue {
+                field: "publish.aggregate_only",
+                message: "needs publish.aggregate_topic, nothing would be published".to_string(),
+            });
+        }
+        let outputs = [
+            self.mqtt.enabled,
+            self.prometheus.enabled,
+            self.http_api.enabled,
+            self.influxdb.enabled,
+            self.statsd.enabled,
+        ];
+        if !outputs.contains(&true) {
+            errors.push(ConfigValidationError::InvalidValue {
+                field: "mqtt.enabled",
+                message: "mqtt, prometheus, http_api, influxdb and statsd output are all disabled, nothing would be published"
+                    .to_string(),
+            });
+        }
+        // Used verbatim as a topic level and in entity ids
+        if self.device.name.is_empty() || !self.device.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            errors.push(ConfigValidationError::InvalidValue {
+                field: "device.name",
+                message: format!("\"{}\" may only contain letters, digits and hyphens", self.device.name),
+            });
+        }
+        if self.sensors.read_timeout_ms == 0 {
+            errors.push(ConfigValidationError::OutOfRange {
+                field: "sensors.read_timeout_ms",
+                message: "must be at least 1".to_string(),
+            });
+        }
+        for sensor_override in self.sensor_overrides.iter().filter(|o| o.update_interval_secs == Some(0)) {
+            errors.push(ConfigValidationError::OutOfRange {
+                field: "sensor_overrides.update_interval_secs",
+                message: format!("must be at least 1 for \"{}\"", sensor_override.name_pattern),
+            });
+        }
+        // tokio's interval panics on a zero period
+        if self.ping.interval_secs == 0 {
+            errors.push(ConfigValidationError::OutOfRange {
+                field: "ping.interval_secs",
+                message: "must be at least 1".to_string(),
+            });
+        }
+        let alphas = std::iter::once(self.smoothing.ema_alpha).chain(self.smoothing.overrides.iter().map(|o| o.ema_alpha));
+        for alpha in alphas.filter(|alpha| !(*alpha > 0.0 && *alpha <= 1.0)) {
+            errors.push(ConfigValidationError::OutOfRange {
+                field: "smoothing.ema_alpha",
+                message: format!("must be in (0.0, 1.0], got {}", alpha),
+            });
+        }
+        for command in &self.command_sensors {
+            if command_sensor_type(&command.sensor_type).is_none() {
+                errors.push(ConfigValidationError::InvalidValue {
+                    field: "command_sensors.sensor_type",
+                    message: format!("unknown sensor_type \"{}\" for \"{}\"", command.sensor_type, command.name),
+                });
+            }
+            if command.timeout_ms == 0 {
+                errors.push(ConfigValidationError::OutOfRange {
+                    field: "command_sensors.timeout_ms",
+                    message: format!("must be at least 1 for \"{}\"", command.name),
+                });
+            }
+        }
+        errors
+    }
+
+    // Settings that load fine but need something outside the daemon to work; logged once
+    // logging is set up, at startup and after every reload
+    pub fn log_warnings(&self) {
+        if self.mqtt.payload_format == PayloadFormat::Msgpack {
+            tracing::warn!(
+                "mqtt.payload_format = \"msgpack\" needs custom Home Assistant template support: \
+                 discovered sensors stay unavailable without a msgpack_decode filter"
+            );
+        }
+    }
+
+    // The first matching override that sets a category wins
+    pub fn entity_category_override(&self, sensor_name: &str) -> Option<EntityCategory> {
+        self.sensor_overrides
+            .iter()
+            .filter(|o| matches_pattern(&o.name_pattern, sensor_name))
+            .find_map(|o| o.entity_category)
+    }
+
+    pub fn max_value_age_secs(&self) -> u64 {
+        self.sensors
+            .max_value_age_secs
+            .unwrap_or(self.publish.force_publish_interval_secs)
+    }
+
+    // validate() as a Result, for the load paths
+    pub fn check(&self) -> Result<(), ConfigError> {
+        let errors = self.validate();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Validation(errors))
+        }
+    }
+
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
         let content = fs::read_to_string(&path)
             .map_err(|e| ConfigError::FileRead(path.as_ref().to_path_buf(), e))?;
 
         let mut config: DaemonConfig = toml::from_str(&content)
-            .map_err(|e| ConfigError::Parse(e))?;
+            .map_err(|e| ConfigError::Parse(path.as_ref().to_path_buf(), e))?;
 
         // Always override these hardcoded values regardless of what's in the config file
         config.device.model = "OrbIQ System Monitor".to_string();
         config.device.manufacturer = "OrbIQ".to_string();
 
+        config.apply_env_overrides()?;
+
         // Always derive client_id from device name
         config.mqtt.client_id = format!("orbiq-{}", config.device.name);
+        config.source_path = Some(path.as_ref().to_path_buf());
 
+        config.check()?;
         Ok(config)
     }
 
-    pub fn load_with_fallback() -> Self {
-        // Try to load from standard locations in order of preference
-        let config_paths = [
-            "/etc/orbiq_system_monitor/config.toml",
-            "/etc/orbiq/config.toml",
-            "./orbiq_system_monitor.toml",
-            "./config.toml",
-        ];
-
-        for path in &config_paths {
-            if Path::new(path).exists() {
-                match Self::load_from_file(path) {
-                    Ok(config) => {
-                        println!("Loaded configuration from: {}", path);
-                        return config;
-                    }
-                    Err(e) => {
-                        eprintln!("Failed to load config from {}: {}", path, e);
-                    }
-                }
+    pub fn load_with_fallback() -> Result<Self, ConfigError> {
+        for path in config_search_paths(|name| std::env::var(name).ok()) {
+            if path.exists() {
+                // A config file that exists but can't be loaded is an error rather than
+                // a reason to silently run with different settings
+                return Self::load_from_file(&path);
             }
         }
 
-        println!("No configuration file found, using defaults");
         let mut default_config = Self::default();
+        default_config.apply_env_overrides()?;
         // Always ensure proper client ID and hardcoded values
         default_config.mqtt.client_id = format!("orbiq-{}", default_config.device.name);
         default_config.device.model = "OrbIQ System Monitor".to_string();
         default_config.device.manufacturer = "OrbIQ".to_string();
-        default_config
+        // The environment can still set invalid values, e.g. ORBIQ_DEVICE_NAME="living room"
+        default_config.check()?;
+        Ok(default_config)
+    }
+
+    // Environment variables take precedence over the config file, so credentials
+    // don't have to be stored in plain text (e.g. in containers)
+    pub fn apply_env_overrides(&mut self) -> Result<(), ConfigError> {
+        self.apply_overrides_from(|name| std::env::var(name).ok())
+    }
+
+    fn apply_overrides_from<F>(&mut self, lookup: F) -> Result<(), ConfigError>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        if let Some(username) = lookup("ORBIQ_MQTT_USERNAME") {
+            self.mqtt.username = Some(username);
+        }
+        if let Some(password) = lookup("ORBIQ_MQTT_PASSWORD") {
+            self.mqtt.password = Some(password);
+        }
+        if let Some(broker) = lookup("ORBIQ_MQTT_BROKER") {
+            self.mqtt.broker = broker;
+        }
+        if let Some(port) = lookup("ORBIQ_MQTT_PORT") {
+            self.mqtt.port = port.trim().parse().map_err(|_| {
+                ConfigError::Invalid(format!("ORBIQ_MQTT_PORT \"{}\" is not a valid port", port))
+            })?;
+        }
+        if let Some(device_name) = lookup("ORBIQ_DEVICE_NAME") {
+            self.device.name = device_name;
+        }
+        Ok(())
     }
 
+    // Command line arguments win over both the config file and the environment, and are
+    // validated like them, e.g. --port 0 or --device-name "a/b"
+    pub fn apply_cli_overrides(&mut self, args: &CliArgs) -> Result<(), ConfigError> {
+        if let Some(broker) = &args.broker {
+            self.mqtt.broker = broker.clone();
+        }
+        if let Some(port) = args.port {
+            self.mqtt.port = port;
+        }
+        if let Some(device_name) = &args.device_name {
+            self.device.name = device_name.clone();
+            // client_id is derived from the device name
+            self.mqtt.client_id = format!("orbiq-{}", device_name);
+        }
+        if let Some(update_interval) = args.update_interval {
+            self.update_interval_secs = update_interval;
+        }
+        if let Some(log_level) = args.log_level {
+            self.logging.level = log_level;
+        }
+        self.check()
+    }
+}
+
+// Config files are searched in this order, the first existing one wins:
+//  1. $XDG_CONFIG_HOME/orbiq/config.toml, or ~/.config/orbiq/config.toml when XDG_CONFIG_HOME is unset
+//  2. <dir>/orbiq/config.toml for every entry of $XDG_CONFIG_DIRS (default: /etc/xdg)
+//  3. /etc/orbiq_system_monitor/config.toml
+//  4. /etc/orbiq/config.toml
+//  5. ./orbiq_system_monitor.toml
+//  6. ./config.toml
+fn config_search_paths<F>(lookup: F) -> Vec<PathBuf>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let non_empty = |name: &str| lookup(name).filter(|value| !value.is_empty());
+    let mut paths = Vec::new();
+
+    let config_home = non_empty("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| non_empty("HOME").map(|home| Path::new(&home).join(".config")));
+    if let Some(config_home) = config_home {
+        paths.push(config_home.join("orbiq/config.toml"));
+    }
+
+    let config_dirs = non_empty("XDG_CONFIG_DIRS").unwrap_or_else(|| "/etc/xdg".to_string());
+    paths.extend(
+        config_dirs
+            .split(':')
+            .filter(|dir| !dir.is_empty())
+            .map(|dir| Path::new(dir).join("orbiq/config.toml")),
+    );
+
+    paths.extend(
+        [
+            "/etc/orbiq_system_monitor/config.toml",
+            "/etc/orbiq/config.toml",
+            "./orbiq_system_monitor.toml",
+            "./config.toml",
+        ]
+        .map(PathBuf::from),
+    );
+    paths
+}
+
+// Write the content to a temporary file next to the target and rename it into place, so
+// a crash never leaves a partially written config behind
+pub fn write_to_file_atomic(path: &Path, content: &str) -> Result<(), ConfigError> {
+    write_atomic_with(path, |file| file.write_all(content.as_bytes()))
+}
+
+fn write_atomic_with<F>(path: &Path, write_content: F) -> Result<(), ConfigError>
+where
+    F: FnOnce(&mut fs::File) -> std::io::Result<()>,
+{
+    let tmp_path = tmp_path_for(path);
+    let to_error = |e| ConfigError::FileWrite(path.to_path_buf(), e);
+
+    let write_result = fs::File::create(&tmp_path).and_then(|mut file| {
+        write_content(&mut file)?;
+        file.sync_all()
+    });
+    if let Err(e) = write_result {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(to_error(e));
+    }
+
+    match fs::rename(&tmp_path, path) {
+        Ok(()) => Ok(()),
+        // Only possible when the target is a mount point of its own; fall back to copy + delete
+        Err(e) if e.kind() == ErrorKind::CrossesDevices => {
+            let copy_result = fs::copy(&tmp_path, path);
+            let _ = fs::remove_file(&tmp_path);
+            copy_result.map(|_| ()).map_err(to_error)
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&tmp_path);
+            Err(to_error(e))
+        }
+    }
+}
+
+fn tmp_path_for(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".tmp");
+    path.with_file_name(file_name)
 }
 
 #[derive(Debug)]
 pub enum ConfigError {
     FileRead(std::path::PathBuf, std::io::Error),
-    Parse(toml::de::Error),
+    FileWrite(std::path::PathBuf, std::io::Error),
+    Parse(std::path::PathBuf, toml::de::Error),
+    Serialize(std::path::PathBuf, toml::ser::Error),
+    Invalid(String),
+    Validation(Vec<ConfigValidationError>),
+    TlsIo(std::path::PathBuf, std::io::Error),
+}
+
+// A single value rejected by DaemonConfig::validate, with the key it was read from
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigValidationError {
+    OutOfRange { field: &'static str, message: String },
+    InvalidValue { field: &'static str, message: String },
+}
+
+impl ConfigValidationError {
+    pub fn field(&self) -> &'static str {
+        match self {
+            ConfigValidationError::OutOfRange { field, .. } | ConfigValidationError::InvalidValue { field, .. } => field,
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigValidationError::OutOfRange { message, .. } | ConfigValidationError::InvalidValue { message, .. } => {
+                write!(f, "{}: {}", self.field(), message)
+            }
+        }
+    }
 }
 
 impl std::fmt::Display for ConfigError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ConfigError::FileRead(path, e) => write!(f, "Failed to read config file {}: {}", path.display(), e),
-            ConfigError::Parse(e) => write!(f, "Failed to parse config: {}", e),
+            ConfigError::FileWrite(path, e) => write!(f, "Failed to write config file {}: {}", path.display(), e),
+            // The toml error already carries line/column and the offending key path
+            ConfigError::Parse(path, e) => write!(f, "Failed to parse config file {}: {}", path.display(), e),
+            ConfigError::Serialize(path, e) => write!(f, "Failed to serialize config for {}: {}", path.display(), e),
+            ConfigError::Invalid(message) => write!(f, "Invalid configuration: {}", message),
+            ConfigError::Validation(errors) => {
+                write!(f, "Invalid configuration:")?;
+                for error in errors {
+                    write!(f, "\n  - {}", error)?;
+                }
+                Ok(())
+            }
+            ConfigError::TlsIo(path, e) => write!(f, "Failed to read TLS file {}: {}", path.display(), e),
         }
     }
 }
 
 impl std::error::Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_qos_level_parsing() {
+        let config: MqttConfig = toml::from_str("last_will_qos = 2").unwrap();
+        assert_eq!(config.last_will_qos, QosLevel::ExactlyOnce);
+        assert_eq!(MqttConfig::default().last_will_qos, QosLevel::AtLeastOnce);
+        assert!(toml::from_str::<MqttConfig>("last_will_qos = 3").is_err());
+
+        let config: MqttConfig = toml::from_str("[qos]\nstate_qos = 0").unwrap();
+        assert_eq!(config.qos.state_qos, QosLevel::AtMostOnce);
+        assert_eq!(config.qos.discovery_qos, QosLevel::AtLeastOnce);
+    }
+
+    #[test]
+    fn test_high_priority_tag_lifts_qos() {
+        let mut config = DaemonConfig::default();
+        config.mqtt.qos.state_qos = QosLevel::AtMostOnce;
+        config.mqtt.qos.availability_qos = QosLevel::AtMostOnce;
+        config.mqtt.qos.discovery_qos = QosLevel::ExactlyOnce;
+        config.mqtt.last_will_qos = QosLevel::AtMostOnce;
+        config.device.tags = vec!["rack-3".to_string()];
+        assert_eq!(config.state_qos(), QosLevel::AtMostOnce);
+        assert_eq!(config.last_will_qos(), QosLevel::AtMostOnce);
+
+        config.device.tags.push("high-priority".to_string());
+        assert_eq!(config.state_qos(), QosLevel::AtLeastOnce);
+        assert_eq!(config.availability_qos(), QosLevel::AtLeastOnce);
+        assert_eq!(config.last_will_qos(), QosLevel::AtLeastOnce);
+        // Never lowered
+        assert_eq!(config.discovery_qos(), QosLevel::ExactlyOnce);
+    }
+
+    #[test]
+    fn test_max_value_age_falls_back_to_force_publish_interval() {
+        let mut config = DaemonConfig::default();
+        config.publish.force_publish_interval_secs = 600;
+        assert_eq!(config.max_value_age_secs(), 600);
+
+        config.sensors.max_value_age_secs = Some(90);
+        assert_eq!(config.max_value_age_secs(), 90);
+    }
+
+    #[test]
+    fn test_parse_error_includes_path_and_location() {
+        let path = std::env::temp_dir().join("orbiq_parse_error_test.toml");
+        fs::write(&path, "[mqtt]\nport = \"not-a-number\"\n").unwrap();
+
+        let message = DaemonConfig::load_from_file(&path).unwrap_err().to_string();
+        fs::remove_file(&path).unwrap();
+
+        assert!(message.contains(&path.display().to_string()));
+        assert!(message.contains("line 2"));
+        assert!(message.contains("port"));
+    }
+
+    fn invalid_fields(config: &DaemonConfig) -> Vec<&'static str> {
+        config.validate().iter().map(|e| e.field()).collect()
+    }
+
+    #[test]
+    fn test_validate_reconnect() {
+        let mut config = DaemonConfig::default();
+        config.mqtt.reconnect_min_secs = 120;
+        assert_eq!(invalid_fields(&config), ["mqtt.reconnect_min_secs"]);
+
+        config.mqtt.reconnect_min_secs = 1;
+        config.mqtt.reconnect_jitter_factor = 1.5;
+        assert_eq!(invalid_fields(&config), ["mqtt.reconnect_jitter_factor"]);
+    }
+
+    #[test]
+    fn test_validate_tcp_keepalive() {
+        let mut config = DaemonConfig::default();
+        config.mqtt.tcp_keepalive_secs = Some(10);
+        let errors = config.validate();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            "mqtt.tcp_keepalive_secs: is not supported by rumqttc 0.24, remove it"
+        );
+
+        config.mqtt.tcp_keepalive_secs = Some(30);
+        assert_eq!(invalid_fields(&config), ["mqtt.tcp_keepalive_secs", "mqtt.tcp_keepalive_secs"]);
+    }
+
+    #[test]
+    fn test_validate_discovery_prefix() {
+        let mut config = DaemonConfig::default();
+        config.mqtt.discovery_prefix = "openhab/devices".to_string();
+        assert!(config.validate().is_empty());
+
+        for invalid in ["", "openhab/", "/openhab", "home/+/x", "#"] {
+            config.mqtt.discovery_prefix = invalid.to_string();
+            assert_eq!(invalid_fields(&config), ["mqtt.discovery_prefix"]);
+        }
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let env: HashMap<&str, &str> = HashMap::from([
+            ("ORBIQ_MQTT_USERNAME", "env-user"),
+            ("ORBIQ_MQTT_PASSWORD", "env-secret"),
+            ("ORBIQ_MQTT_PORT", "8883"),
+            ("ORBIQ_DEVICE_NAME", "rack-1"),
+        ]);
+        let mut config = DaemonConfig::default();
+        config.mqtt.username = Some("file-user".to_string());
+        config
+            .apply_overrides_from(|name| env.get(name).map(|value| value.to_string()))
+            .unwrap();
+
+        assert_eq!(config.mqtt.username.as_deref(), Some("env-user"));
+        assert_eq!(config.mqtt.password.as_deref(), Some("env-secret"));
+        assert_eq!(config.mqtt.broker, "localhost");
+        assert_eq!(config.mqtt.port, 8883);
+        assert_eq!(config.device.name, "rack-1");
+
+        let result = config.apply_overrides_from(|name| (name == "ORBIQ_MQTT_PORT").then(|| "mqtt".to_string()));
+        assert!(matches!(result, Err(ConfigError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_cli_overrides() {
+        let args = CliArgs {
+            broker: Some("192.168.1.10".to_string()),
+            device_name: Some("server-rack-1".to_string()),
+            update_interval: Some(10),
+            ..CliArgs::default()
+        };
+        let mut config = DaemonConfig::default();
+        config.apply_cli_overrides(&args).unwrap();
+
+        assert_eq!(config.mqtt.broker, "192.168.1.10");
+        assert_eq!(config.mqtt.port, 1883);
+        assert_eq!(config.device.name, "server-rack-1");
+        assert_eq!(config.mqtt.client_id, "orbiq-server-rack-1");
+        assert_eq!(config.update_interval_secs, 10);
+        assert_eq!(config.logging.level, LogLevel::Info);
+
+        for args in [
+            CliArgs { port: Some(0), ..CliArgs::default() },
+            CliArgs { device_name: Some("a/b".to_string()), ..CliArgs::default() },
+            CliArgs { update_interval: Some(0), ..CliArgs::default() },
+        ] {
+            let result = DaemonConfig::default().apply_cli_overrides(&args);
+            assert!(matches!(result, Err(ConfigError::Validation(_))));
+        }
+    }
+
+    #[test]
+    fn test_config_search_paths() {
+        let env: HashMap<&str, &str> = HashMap::from([
+            ("XDG_CONFIG_HOME", "/home/user/.xdg"),
+            ("XDG_CONFIG_DIRS", "/opt/xdg:/etc/xdg"),
+            ("HOME", "/home/user"),
+        ]);
+        let paths = config_search_paths(|name| env.get(name).map(|value| value.to_string()));
+        assert_eq!(paths[0], PathBuf::from("/home/user/.xdg/orbiq/config.toml"));
+        assert_eq!(paths[1], PathBuf::from("/opt/xdg/orbiq/config.toml"));
+        assert_eq!(paths[2], PathBuf::from("/etc/xdg/orbiq/config.toml"));
+        assert_eq!(paths[3], PathBuf::from("/etc/orbiq_system_monitor/config.toml"));
+        assert_eq!(paths.len(), 7);
+
+        let paths = config_search_paths(|name| (name == "HOME").then(|| "/home/user".to_string()));
+        assert_eq!(paths[0], PathBuf::from("/home/user/.config/orbiq/config.toml"));
+        assert_eq!(paths[1], PathBuf::from("/etc/xdg/orbiq/config.toml"));
+    }
+
+    #[test]
+    fn test_validate_intervals() {
+        let mut config = DaemonConfig::default();
+        config.sensor_overrides.push(SensorOverrideConfig {
+            name_pattern: "cpu_*".to_string(),
+            update_interval_secs: Some(0),
+            ..SensorOverrideConfig::default()
+        });
+        config.sensors.read_timeout_ms = 0;
+        config.mqtt.publish_batch_size = 0;
+        config.ping.interval_secs = 0;
+        assert_eq!(
+            invalid_fields(&config),
+            [
+                "mqtt.publish_batch_size",
+                "sensors.read_timeout_ms",
+                "sensor_overrides.update_interval_secs",
+                "ping.interval_secs"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_outputs() {
+        let mut config = DaemonConfig::default();
+        config.mqtt.enabled = false;
+        assert_eq!(invalid_fields(&config), ["mqtt.enabled"]);
+        config.prometheus.enabled = true;
+        assert!(config.validate().is_empty());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(DaemonConfig::default().validate().is_empty());
+
+        let mut config = DaemonConfig {
+            update_interval_secs: 0,
+            discovery_delay_ms: 20_000,
+            ..DaemonConfig::default()
+        };
+        config.mqtt.port = 0;
+        config.mqtt.broker = " ".to_string();
+        config.mqtt.keep_alive_secs = 2;
+        config.device.name = "living room".to_string();
+        assert_eq!(
+            invalid_fields(&config),
+            [
+                "update_interval_secs",
+                "discovery_delay_ms",
+                "mqtt.port",
+                "mqtt.broker",
+                "mqtt.keep_alive_secs",
+                "device.name"
+            ]
+        );
+
+        // Broker settings are ignored without MQTT
+        config.mqtt.enabled = false;
+        config.prometheus.enabled = true;
+        assert_eq!(config.validate().len(), 3);
+
+        let error = ConfigError::Validation(config.validate());
+        assert_eq!(
+            error.to_string(),
+            "Invalid configuration:\n  - update_interval_secs: must be at least 1\n  - discovery_delay_ms: 20000 is above the maximum of 10000\n  - device.name: \"living room\" may only contain letters, digits and hyphens"
+        );
+
+        config.publish.aggregate_only = true;
+        assert!(config.validate().iter().any(|e| e.field() == "publish.aggregate_only"));
+        config.publish.aggregate_topic = Some("orbiq/server/state".to_string());
+        assert_eq!(config.validate().len(), 3);
+    }
+
+    #[test]
+    fn test_validate_command_sensors() {
+        let config: DaemonConfig = toml::from_str(
+            r#"
+            [[command_sensors]]
+            name = "ups_battery"
+            command = "/usr/local/bin/check_ups_battery.sh"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.command_sensors[0].sensor_type, "generic");
+        assert_eq!(config.command_sensors[0].timeout_ms, 5000);
+        assert!(config.validate().is_empty());
+
+        let mut config = config;
+        config.command_sensors[0].sensor_type = "thermometer".to_string();
+        config.command_sensors[0].timeout_ms = 0;
+        assert_eq!(invalid_fields(&config), ["command_sensors.sensor_type", "command_sensors.timeout_ms"]);
+    }
+
+    #[test]
+    fn test_smoothing_validate() {
+        let mut config = DaemonConfig::default();
+        config.smoothing.ema_alpha = 0.0;
+        assert_eq!(invalid_fields(&config), ["smoothing.ema_alpha"]);
+
+        config.smoothing.ema_alpha = 0.5;
+        config.smoothing.overrides.push(SmoothingOverrideConfig {
+            sensor_pattern: None,
+            sensor_type: Some(SystemSensorType::Fan),
+            ema_alpha: 1.5,
+        });
+        assert_eq!(invalid_fields(&config), ["smoothing.ema_alpha"]);
+    }
+
+    #[test]
+    fn test_fingerprint_changes_with_config() {
+        let config = DaemonConfig::default();
+        let changed = DaemonConfig {
+            update_interval_secs: 60,
+            ..DaemonConfig::default()
+        };
+
+        let hash = config.fingerprint().unwrap();
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, DaemonConfig::default().fingerprint().unwrap());
+        assert_ne!(hash, changed.fingerprint().unwrap());
+    }
+
+    #[test]
+    fn test_fingerprint_is_stable_and_skips_credentials() {
+        let labels: Vec<(String, String)> = (0..32).map(|i| (format!("sensor_{}", i), format!("Label {}", i))).collect();
+        let mut config = DaemonConfig::default();
+        config.sensors.labels = labels.iter().cloned().collect();
+        let mut reordered = DaemonConfig::default();
+        reordered.sensors.labels = labels.iter().rev().cloned().collect();
+        assert_eq!(config.fingerprint(), reordered.fingerprint());
+
+        let hash = config.fingerprint();
+        config.mqtt.username = Some("orbiq".to_string());
+        config.mqtt.password = Some("secret".to_string());
+        config.influxdb.token = Some("token".to_string());
+        config.mqtt.brokers.push(MqttBrokerConfig {
+            broker: "mqtt2.local".to_string(),
+            port: 1883,
+            username: None,
+            password: None,
+        });
+        let with_broker = config.fingerprint();
+        assert_ne!(hash, with_broker);
+        config.mqtt.brokers[0].password = Some("backup".to_string());
+        assert_eq!(with_broker, config.fingerprint());
+        config.mqtt.password = None;
+        assert_eq!(with_broker, config.fingerprint());
+    }
+
+    #[test]
+    fn test_write_to_file_atomic() {
+        let path = std::env::temp_dir().join("orbiq_atomic_write_test.toml");
+        write_to_file_atomic(&path, "update_interval_secs = 10\n").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "update_interval_secs = 10\n");
+        assert!(!tmp_path_for(&path).exists());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_save_to_file_round_trip() {
+        let path = std::env::temp_dir().join("orbiq_save_config_test.toml");
+        let mut config = DaemonConfig {
+            update_interval_secs: 45,
+            ..DaemonConfig::default()
+        };
+        config.device.name = "server-01".to_string();
+        config.mqtt.client_id = "orbiq-server-01".to_string();
+        config.save_to_file(&path).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        let loaded = DaemonConfig::load_from_file(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(!content.contains("client_id"));
+        assert!(!content.contains("manufacturer"));
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.update_interval_secs, 45);
+        assert_eq!(loaded.device.name, "server-01");
+    }
+
+    #[test]
+    fn test_write_atomic_failure_leaves_original_intact() {
+        let path = std::env::temp_dir().join("orbiq_atomic_failure_test.toml");
+        fs::write(&path, "update_interval_secs = 10\n").unwrap();
+
+        let result = write_atomic_with(&path, |file| {
+            file.write_all(b"update_interval")?;
+            Err(std::io::Error::other("simulated failure"))
+        });
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(ConfigError::FileWrite(_, _))));
+        assert_eq!(content, "update_interval_secs = 10\n");
+        assert!(!tmp_path_for(&path).exists());
+    }
+}
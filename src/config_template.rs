@@ -0,0 +1,217 @@
+use crate::config::DaemonConfig;
+use toml::Value;
+
+// One line of the generated config: a table header, a key that always has a default,
+// or an optional key that is written commented out when it has no default
+enum Entry {
+    Table(&'static str, &'static str),
+    Field(&'static str, &'static str),
+    Optional(&'static str, &'static str, &'static str),
+}
+
+use Entry::{Field, Optional, Table};
+
+// Root keys must come before the first table
+const ENTRIES: &[Entry] = &[
+    Field("update_interval_secs", "Seconds between sensor updates (>= 1)"),
+    Field("discovery_delay_ms", "Delay between discovery messages in milliseconds"),
+    Field("auto_scale_units", "Apply SI prefixes (k, M, m) to very large or very small values of W, V, A, J, Hz and B"),
+    Field("hwmon_device_cache_secs", "Seconds the list of hwmon devices is cached before /sys/class/hwmon is rescanned"),
+    Optional("state_file", "Remember which sensors were discovered, so a restart with a resumed broker session skips re-discovery", "\"/var/lib/orbiq/state.json\""),
+    Field("startup_check_mode", "\"warn\" logs failed startup checks, \"fail\" exits when an error-level check fails"),
+    Field("sensor_overrides", "Publish interval (update_interval_secs) and/or Home Assistant entity_category (\"diagnostic\") per sensor name pattern, e.g.\n[[sensor_overrides]]\nname_pattern = \"disk_total_*\"\nupdate_interval_secs = 3600\nentity_category = \"diagnostic\""),
+    Field("thresholds", "Publish an alert when a sensor crosses a limit, e.g.\n[[thresholds]]\nsensor_pattern = \"k10temp_*\"\nhigh_value = 85.0\nalert_topic = \"orbiq/alerts\"\nhysteresis = 2.0\ndevice_trigger = false"),
+    Field("calibration", "Correct readings as (value * scale) + offset, matched by exact sensor name, e.g.\n[[calibration]]\nsensor_name = \"k10temp_1\"\noffset = -2.0\nscale = 1.0"),
+    Field("watch_processes", "Sum CPU (\"cpu\"), memory (\"memory_mb\") or threads (\"thread_count\") of processes whose name contains `name`, e.g.\n[[watch_processes]]\nname = \"postgres\"\nmetrics = [\"cpu\", \"memory_mb\"]"),
+    Field("smart_devices", "Block devices read with smartctl when sensors.smart is enabled, e.g.\n[[smart_devices]]\npath = \"/dev/sda\""),
+    Field("onewire_sensors", "Home Assistant names for DS18B20 1-Wire probes by their /sys/bus/w1/devices id, e.g.\n[[onewire_sensors]]\nid = \"28-0316a2795cff\"\nlabel = \"Outdoor Temperature\""),
+    Field("remote_devices", "Hosts whose hwmon temperatures and fans are read over ssh, published with `name` as prefix, e.g.\n[[remote_devices]]\nname = \"nas\"\nssh_host = \"nas.local\"\nssh_user = \"monitor\"\nssh_key_path = \"/etc/orbiq/id_ed25519\"\nssh_port = 22"),
+    Field("command_sensors", "Scripts printing a single number, run every cycle, e.g.\n[[command_sensors]]\nname = \"ups_battery\"\ncommand = \"/usr/local/bin/check_ups_battery.sh\"\nargs = []\nunit = \"%\"\nsensor_type = \"battery_percent\"\ntimeout_ms = 5000"),
+    Field("json_file_sensors", "Numbers read from JSON files written by other tools, e.g.\n[[json_file_sensors]]\nname = \"ipmi_inlet\"\npath = \"/run/ipmi/sensors.json\"\njson_path = \"temperatures.inlet.value\"\nunit = \"°C\"\nlabel = \"Inlet Temperature\""),
+    Field("sensor_labels", "Home Assistant names for single sensors, same as sensors.labels and winning over it, e.g.\n[[sensor_labels]]\nname = \"nct6797d_1\"\nlabel = \"Motherboard VRM Temperature\""),
+    Table("mqtt", "MQTT broker connection"),
+    Field("mqtt.enabled", "Publish to the MQTT broker; disable to only serve Prometheus metrics"),
+    Field("mqtt.broker", "Broker hostname or IP address"),
+    Field("mqtt.port", "Broker port (1-65535), usually 1883 or 8883 for TLS"),
+    Optional("mqtt.username", "Username, can also be set with ORBIQ_MQTT_USERNAME", "\"orbiq\""),
+    Optional("mqtt.password", "Password, can also be set with ORBIQ_MQTT_PASSWORD", "\"secret\""),
+    Field("mqtt.brokers", "Brokers tried in order instead of broker/port; the next one is used when the current one stays unreachable for reconnect_max_secs, e.g.\n[[mqtt.brokers]]\nbroker = \"mqtt1.local\"\nport = 1883\n[[mqtt.brokers]]\nbroker = \"mqtt2.local\"\nusername = \"backup\"\npassword = \"secret\""),
+    Field("mqtt.discovery_prefix", "Root of every published topic"),
+    Field("mqtt.keep_alive_secs", "MQTT application level keep-alive (PINGREQ interval), not TCP keepalive"),
+    Optional("mqtt.tcp_keepalive_secs", "Socket level TCP keepalive; rejected while the MQTT client (rumqttc 0.24) can't apply it", "15"),
+    Field("mqtt.reconnect_min_secs", "Delay before the first reconnect attempt, doubled after every failure"),
+    Field("mqtt.reconnect_max_secs", "Upper limit of the reconnect delay"),
+    Field("mqtt.reconnect_jitter_factor", "Random variation of the reconnect delay, 0.25 is +-25%"),
+    Field("mqtt.last_will_qos", "QoS (0, 1 or 2) of the last will that marks the device offline on an unclean disconnect"),
+    Field("mqtt.last_will_retain", "Retain the last will message"),
+    Field("mqtt.use_device_availability", "Sensors also become unavailable when the device-level availability topic goes offline"),
+    Field("mqtt.discovery_ttl_secs", "Re-send discovery and availability after this many seconds, recovering from a broker that lost retained messages; 0 sends them once"),
+    Field("mqtt.availability_mode", "\"per_sensor\" publishes availability for every sensor, \"device\" only the shared device-level topic"),
+    Field("mqtt.publish_batch_size", "Sensors published per batch before pausing for batch_delay_ms"),
+    Field("mqtt.batch_delay_ms", "Pause between batches so the client's request queue doesn't fill up; 0 disables it"),
+    Field("mqtt.payload_format", "State payload encoding, \"json\" or \"msgpack\"; msgpack needs custom Home Assistant template support (a msgpack_decode filter), a warning is logged at startup"),
+    Field("mqtt.protocol_version", "MQTT protocol, \"v3.1.1\" or \"v5\""),
+    Table("mqtt.user_properties", "User properties attached to every published message, MQTT 5 only, e.g.\nsite = \"basement\""),
+    Table("mqtt.qos", "QoS (0, 1 or 2) per message category"),
+    Field("mqtt.qos.state_qos", "Sensor state updates, 0 reduces broker load"),
+    Field("mqtt.qos.discovery_qos", "Home Assistant discovery configs"),
+    Field("mqtt.qos.availability_qos", "Online/offline availability messages"),
+    Table("mqtt.tls", "TLS is enabled as soon as any of these options is set"),
+    Optional("mqtt.tls.ca_cert", "PEM CA certificate used to verify the broker", "\"/etc/orbiq_system_monitor/ca.pem\""),
+    Optional("mqtt.tls.client_cert", "PEM client certificate for mutual TLS", "\"/etc/orbiq_system_monitor/client.pem\""),
+    Optional("mqtt.tls.client_key", "PEM private key belonging to client_cert", "\"/etc/orbiq_system_monitor/client.key\""),
+    Field("mqtt.tls.insecure", "Skip broker certificate verification, e.g. for self-signed certificates"),
+    Table("device", "Home Assistant device all sensors belong to"),
+    Field("device.name", "Device name, also used in the MQTT topics (ORBIQ_DEVICE_NAME)"),
+    Optional("device.sw_version", "Software version shown in Home Assistant", "\"1.0\""),
+    Optional("device.hw_version", "Hardware version shown in Home Assistant", "\"1.0\""),
+    Field("device.tags", "Device tags; \"high-priority\" publishes every message with at least QoS 1"),
+    Table("sensors", "Which sensors are collected"),
+    Field("sensors.enable_temperature", "Read hwmon temperature sensors"),
+    Field("sensors.temperature_limits", "Publish the tempN_max / tempN_crit limits of hwmon temperature sensors as <sensor>_max / <sensor>_crit"),
+    Field("sensors.enable_fan", "Read hwmon fan sensors"),
+    Field("sensors.fan_pwm", "Publish the PWM duty cycle (pwmN, 0-255) of hwmon fan controllers as a percentage"),
+    Field("sensors.per_core_cpu", "Publish a usage sensor for every CPU core"),
+    Field("sensors.voltage", "Publish hwmon voltage rails (inN_input)"),
+    Field("sensors.cpu_frequency", "Publish the current frequency of every CPU core (cpufreq)"),
+    Field("sensors.thermal_zones", "Publish /sys/class/thermal zones without a hwmon device as thermal_<type>"),
+    Field("sensors.psi", "Publish pressure stall information (/proc/pressure/{cpu,memory,io}), needs Linux 4.20+"),
+    Field("sensors.smart", "Publish SMART attributes of smart_devices via `smartctl -j -A`, needs smartctl and root"),
+    Field("sensors.network_interfaces", "Interfaces to publish network sensors for; empty means all except loopback"),
+    Field("sensors.include_loopback", "Also publish loopback network sensors when network_interfaces is empty"),
+    Field("sensors.disk_io_partitions", "Publish disk I/O sensors for partitions as well as whole disks"),
+    Field("sensors.disk_inodes", "Publish inode usage (%) per mount point"),
+    Field("sensors.low_entropy_threshold", "Entropy level in bits below which the low entropy alert turns on"),
+    Field("sensors.enable_iio_sensors", "Read temperature and humidity sensors from the IIO subsystem (/sys/bus/iio)"),
+    Field("sensors.temperature_unit", "Unit of every temperature sensor, \"C\" or \"F\""),
+    Field("sensors.dedup_strategy", "Which reading wins when several sensors share a name: \"first\", \"last\", \"max\" or \"min\""),
+    Field("sensors.read_timeout_ms", "Skip a sensor for the current cycle when reading its sysfs/procfs file takes longer than this"),
+    Optional("sensors.max_value_age_secs", "Unchanged values are republished once they are older than this, overrides publish.force_publish_interval_secs", "300"),
+    Field("sensors.mqtt_attributes", "Attach values received on external MQTT topics as sensor attributes, e.g.\n[[sensors.mqtt_attributes]]\nsensor_pattern = \"cpu_*\"\nattribute_name = \"plug_power\"\nmqtt_topic = \"tele/smartplug/power\""),
+    Table("sensors.fan", "Fan speed percentage sensors"),
+    Field("sensors.fan.rpm_to_percent_method", "\"hwmon\" (fanN_min/fanN_max files), \"manual\" (fan_min_rpm/fan_max_rpm) or \"none\""),
+    Field("sensors.fan.fan_min_rpm", "RPM reported as 0% by the manual method"),
+    Field("sensors.fan.fan_max_rpm", "RPM reported as 100% by the manual method"),
+    Field("sensors.fan.overrides", "Per-fan range for the manual method, e.g.\n[[sensors.fan.overrides]]\nsensor_name = \"nct6797_1_fan\"\nfan_min_rpm = 500.0\nfan_max_rpm = 1800.0"),
+    Table("sensors.labels", "Custom names shown in Home Assistant, keyed by sensor name, e.g.\nnct6797d_1 = \"Motherboard VRM Temperature\""),
+    Table("logging", "Log output"),
+    Field("logging.level", "One of \"error\", \"warn\", \"info\", \"debug\" or \"trace\"; RUST_LOG overrides it when set"),
+    Table("publish", "When state updates are sent; discovery and availability messages are always published"),
+    Field("publish.delta_threshold", "Only publish a value when it changed by more than this; 0.0 publishes every change"),
+    Field("publish.force_publish_interval_secs", "Republish unchanged values after this many seconds"),
+    Optional("publish.aggregate_topic", "Also publish every cycle's values as one JSON object, e.g. {\"cpu_usage\": 23.1}", "\"orbiq/myhost/state\""),
+    Field("publish.aggregate_only", "Only publish aggregate_topic, without per-sensor topics and Home Assistant discovery"),
+    Table("prometheus", "HTTP endpoint serving /metrics in the Prometheus text format"),
+    Field("prometheus.enabled", "Serve Prometheus metrics"),
+    Field("prometheus.port", "Port of the metrics endpoint, bound on all interfaces"),
+    Table("http_api", "HTTP API serving the current sensor values as JSON"),
+    Field("http_api.enabled", "Serve GET /sensors, /sensors/{name} and /health"),
+    Field("http_api.port", "Port of the HTTP API, bound on all interfaces"),
+    Table("influxdb", "InfluxDB line protocol output over HTTP (v2 write API) or UDP"),
+    Field("influxdb.enabled", "Write every sensor to InfluxDB each cycle"),
+    Optional("influxdb.url", "Base URL of the v2 API; takes precedence over udp_host", "\"http://localhost:8086\""),
+    Optional("influxdb.token", "API token", "\"my-token\""),
+    Optional("influxdb.org", "Organization, required with url", "\"my-org\""),
+    Optional("influxdb.bucket", "Bucket, required with url", "\"orbiq\""),
+    Optional("influxdb.udp_host", "Host of a UDP line protocol listener", "\"localhost\""),
+    Field("influxdb.udp_port", "Port of the UDP line protocol listener"),
+    Table("statsd", "StatsD gauges over UDP"),
+    Field("statsd.enabled", "Send every sensor as a gauge each cycle"),
+    Field("statsd.host", "StatsD or DogStatsD agent host"),
+    Field("statsd.port", "StatsD agent port"),
+    Field("statsd.prefix", "Gauges are named {prefix}.{device_name}.{sensor_name}"),
+    Field("statsd.tags", "DogStatsD tags added to every gauge, e.g. [\"env:prod\"]"),
+    Table("history", "Rolling statistics over the last readings of every sensor"),
+    Field("history.window_size", "Readings kept per sensor; the state payload gains min_<N>, max_<N> and avg_<N>. 0 disables history"),
+    Table("ping", "ICMP latency sensors, pinged from their own task"),
+    Field("ping.targets", "Host names or addresses, e.g. [\"8.8.8.8\", \"router.lan\"]; a timeout is published as 9999 ms"),
+    Field("ping.interval_secs", "Seconds between pings, independent of update_interval_secs"),
+    Field("ping.timeout_ms", "How long to wait for an echo reply"),
+    Table("daemon", "The daemon itself"),
+    Field("daemon.self_monitoring", "Publish daemon_publish_count and daemon_publish_errors, the MQTT messages published and failed since start"),
+    Field("daemon.shutdown_timeout_secs", "Seconds to wait for the offline messages to be sent on shutdown before exiting anyway"),
+    Table("smoothing", "Exponential moving average for noisy sensors"),
+    Field("smoothing.ema_alpha", "Weight of each new reading, 0.0 < alpha <= 1.0; 1.0 disables smoothing"),
+    Field("smoothing.overrides", "Smoothing per sensor name pattern and/or type, e.g.\n[[smoothing.overrides]]\nsensor_type = \"fan\"\nema_alpha = 0.3"),
+    Table("filters", "Which collected sensors are published"),
+    Field("filters.disabled_types", "Sensor types to drop entirely, e.g. [\"fan\", \"humidity\"]"),
+    Optional("filters.include_sensors", "Glob patterns (* and ?) of sensors to publish; all sensors when unset", "[\"cpu_*\", \"k10temp_*\"]"),
+    Field("filters.exclude_sensors", "Glob patterns of sensors to drop, applied after include_sensors"),
+    Field("filters.suppress_zero_types", "Sensor types dropped while they read exactly 0, e.g. unconnected fans"),
+    Field("filters.value_ranges", "Drop implausible readings from matching sensors, e.g.\n[[filters.value_ranges]]\nsensor_pattern = \"nvme_*\"\nmin = -40.0\nmax = 150.0"),
+];
+
+// The default config as TOML, with every key documented and optional keys commented out
+pub fn generate_config_template() -> Result<String, toml::ser::Error> {
+    let defaults = Value::try_from(DaemonConfig::default())?;
+    let mut output = String::from("# OrbIQ System Monitoring Configuration\n\n");
+
+    for entry in ENTRIES {
+        match entry {
+            Table(path, comment) => {
+                output.push('\n');
+                push_comment(&mut output, comment);
+                output.push_str(&format!("[{}]\n", path));
+            }
+            Field(path, comment) | Optional(path, comment, _) => {
+                push_comment(&mut output, comment);
+                let key = path.rsplit('.').next().unwrap_or(path);
+                match (lookup(&defaults, path), entry) {
+                    (Some(value), _) => output.push_str(&format!("{} = {}\n", key, value)),
+                    (None, Optional(_, _, example)) => output.push_str(&format!("# {} = {}\n", key, example)),
+                    (None, _) => {}
+                }
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+fn push_comment(output: &mut String, comment: &str) {
+    for line in comment.lines() {
+        output.push_str(&format!("# {}\n", line));
+    }
+}
+
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(value, |value, key| value.get(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn documented(path: &str) -> bool {
+        ENTRIES.iter().any(|entry| match entry {
+            Table(p, _) | Field(p, _) | Optional(p, _, _) => *p == path,
+        })
+    }
+
+    // Every key of the serialized default config needs an entry, or it would be missing from the output
+    fn assert_documented(value: &Value, prefix: &str) {
+        let Some(table) = value.as_table() else {
+            return;
+        };
+        for (key, value) in table {
+            let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+            assert!(documented(&path), "config key {} is not documented", path);
+            assert_documented(value, &path);
+        }
+    }
+
+    #[test]
+    fn test_every_default_key_is_documented() {
+        let defaults = Value::try_from(DaemonConfig::default()).unwrap();
+        assert_documented(&defaults, "");
+    }
+
+    #[test]
+    fn test_template_loads_as_default_config() {
+        let template = generate_config_template().unwrap();
+        let parsed: DaemonConfig = toml::from_str(&template).unwrap();
+
+        assert_eq!(parsed.fingerprint(), DaemonConfig::default().fingerprint());
+        assert!(template.contains("# username = \"orbiq\""));
+    }
+}
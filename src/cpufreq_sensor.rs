@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use crate::sensors::{CollectFuture, SensorCollector, SystemSensor};
+use crate::sysfs::read_number;
+use crate::sensors::SystemSensorType::CpuFrequency;
+
+const CPU_BASE_PATH: &str = "/sys/devices/system/cpu";
+const SCALING_CUR_FREQ_FILE: &str = "cpufreq/scaling_cur_freq";
+const KHZ_TO_MHZ: f64 = 1000.0;
+
+// Current frequency of every core
+pub struct CpuFrequencyCollector {
+    pub read_timeout: Duration,
+}
+
+impl SensorCollector for CpuFrequencyCollector {
+    fn collect(&self) -> CollectFuture<'_> {
+        Box::pin(collect_all_cpu_frequencies(self.read_timeout))
+    }
+
+    fn name(&self) -> &str {
+        "cpu_frequency"
+    }
+}
+
+// VMs and some ARM boards have no cpufreq driver, which simply yields no sensors
+async fn collect_all_cpu_frequencies(read_timeout: Duration) -> Vec<SystemSensor> {
+    let Ok(entries) = fs::read_dir(CPU_BASE_PATH) else {
+        return Vec::new();
+    };
+
+    let mut cores: Vec<(u32, f64)> = Vec::new();
+    for entry in entries.flatten() {
+        let Some(core) = extract_core_number(&entry.file_name().to_string_lossy()) else {
+            continue;
+        };
+        if let Some(frequency) = read_frequency_mhz(&entry.path().join(SCALING_CUR_FREQ_FILE), read_timeout).await {
+            cores.push((core, frequency));
+        }
+    }
+    cores.sort_by_key(|(core, _)| *core);
+
+    cores
+        .into_iter()
+        .map(|(core, frequency)| SystemSensor {
+            name: format!("cpu_core_{}_freq_mhz", core),
+            label: None,
+            value: frequency,
+            unit: "MHz".to_string(),
+            sensor_type: CpuFrequency,
+            attributes: HashMap::new(),
+            extra: None,
+        })
+        .collect()
+}
+
+// "cpu12" -> 12; skips cpufreq, cpuidle and friends
+fn extract_core_number(dir_name: &str) -> Option<u32> {
+    dir_name.strip_prefix("cpu")?.parse().ok()
+}
+
+async fn read_frequency_mhz(path: &Path, read_timeout: Duration) -> Option<f64> {
+    let frequency_khz = read_number(path, read_timeout).await?;
+    Some((frequency_khz / KHZ_TO_MHZ).round())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const READ_TIMEOUT: Duration = Duration::from_millis(500);
+
+    #[test]
+    fn test_extract_core_number() {
+        assert_eq!(extract_core_number("cpu0"), Some(0));
+        assert_eq!(extract_core_number("cpu12"), Some(12));
+        assert_eq!(extract_core_number("cpufreq"), None);
+        assert_eq!(extract_core_number("cpuidle"), None);
+    }
+
+    #[tokio::test]
+    async fn test_read_frequency_mhz() {
+        let dir = std::env::temp_dir().join("orbiq_cpufreq_test");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("scaling_cur_freq"), "3400123\n").unwrap();
+
+        let frequency = read_frequency_mhz(&dir.join("scaling_cur_freq"), READ_TIMEOUT).await;
+        let missing = read_frequency_mhz(&dir.join("missing"), READ_TIMEOUT).await;
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(frequency, Some(3400.0));
+        assert_eq!(missing, None);
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use crate::config::SensorsConfig;
+use crate::sensors::{CollectFuture, SensorCollector, SystemSensor, SystemSensorType};
+use crate::sysfs::read_attribute;
+
+const DISKSTATS_PATH: &str = "/proc/diskstats";
+const SYS_CLASS_BLOCK_PATH: &str = "/sys/class/block";
+const SECTOR_SIZE_BYTES: f64 = 512.0;
+const BYTES_PER_KB: f64 = 1024.0;
+
+// Counters from a single /proc/diskstats line
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DiskStats {
+    reads_completed: u64,
+    sectors_read: u64,
+    writes_completed: u64,
+    sectors_written: u64,
+    weighted_io_ms: u64,
+}
+
+static PREVIOUS_READINGS: OnceLock<Mutex<HashMap<String, (DiskStats, Instant)>>> = OnceLock::new();
+
+// Per-disk throughput and latency from /proc/diskstats
+pub struct DiskIoCollector {
+    pub sensors_config: SensorsConfig,
+    pub read_timeout: Duration,
+}
+
+impl SensorCollector for DiskIoCollector {
+    fn collect(&self) -> CollectFuture<'_> {
+        Box::pin(collect_disk_io_stats(&self.sensors_config, self.read_timeout))
+    }
+
+    fn name(&self) -> &str {
+        "disk_io"
+    }
+}
+
+async fn collect_disk_io_stats(sensors_config: &SensorsConfig, read_timeout: Duration) -> Vec<SystemSensor> {
+    let Some(content) = read_attribute(Path::new(DISKSTATS_PATH), read_timeout).await else {
+        tracing::warn!(path = DISKSTATS_PATH, "Failed to read disk statistics");
+        return Vec::new();
+    };
+
+    let readings = PREVIOUS_READINGS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut readings = readings.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let now = Instant::now();
+    let mut sensors = Vec::new();
+
+    for (device, stats) in parse_diskstats(&content) {
+        if !should_monitor(&device, sensors_config) {
+            continue;
+        }
+
+        let previous = readings.insert(device.clone(), (stats, now));
+        let rates = match previous {
+            // Rates use the measured wall-clock time so jitter in the update loop doesn't cause spikes
+            Some((previous, at)) => disk_rates(&previous, &stats, now.duration_since(at).as_secs_f64()),
+            // Nothing to compare against on the first cycle
+            None => DiskRates::default(),
+        };
+
+        sensors.push(disk_sensor(&device, "read_kbps", rates.read_kbps, "KB/s", SystemSensorType::DiskRead));
+        sensors.push(disk_sensor(&device, "write_kbps", rates.write_kbps, "KB/s", SystemSensorType::DiskWrite));
+        sensors.push(disk_sensor(&device, "read_iops", rates.read_iops, "IOPS", SystemSensorType::DiskReadIops));
+        sensors.push(disk_sensor(&device, "write_iops", rates.write_iops, "IOPS", SystemSensorType::DiskWriteIops));
+        sensors.push(disk_sensor(&device, "io_wait_ms", rates.io_wait_ms, "ms", SystemSensorType::DiskIoWait));
+    }
+
+    sensors
+}
+
+// Loop and ram devices are never interesting, partitions only when asked for
+fn should_monitor(device: &str, sensors_config: &SensorsConfig) -> bool {
+    if device.starts_with("loop") || device.starts_with("ram") {
+        return false;
+    }
+
+    sensors_config.disk_io_partitions || !is_partition(device)
+}
+
+fn is_partition(device: &str) -> bool {
+    Path::new(SYS_CLASS_BLOCK_PATH).join(device).join("partition").exists()
+}
+
+fn parse_diskstats(content: &str) -> Vec<(String, DiskStats)> {
+    content.lines().filter_map(parse_diskstats_line).collect()
+}
+
+// Format: major minor name reads merged sectors_read ms_reading writes merged sectors_written
+// ms_writing in_flight ms_io weighted_ms_io ...
+fn parse_diskstats_line(line: &str) -> Option<(String, DiskStats)> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let field = |index: usize| fields.get(index)?.parse::<u64>().ok();
+
+    let name = fields.get(2)?.to_string();
+    let stats = DiskStats {
+        reads_completed: field(3)?,
+        sectors_read: field(5)?,
+        writes_completed: field(7)?,
+        sectors_written: field(9)?,
+        weighted_io_ms: field(13)?,
+    };
+
+    Some((name, stats))
+}
+
+// Counters are unsigned long, so they wrap at 32 bits on 32-bit kernels
+fn counter_delta(previous: u64, current: u64) -> u64 {
+    if current >= previous {
+        current - previous
+    } else if previous <= u32::MAX as u64 {
+        (u32::MAX as u64 - previous) + current + 1
+    } else {
+        0
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct DiskRates {
+    read_kbps: f64,
+    write_kbps: f64,
+    read_iops: f64,
+    write_iops: f64,
+    io_wait_ms: f64,
+}
+
+fn disk_rates(previous: &DiskStats, current: &DiskStats, elapsed_secs: f64) -> DiskRates {
+    if elapsed_secs <= 0.0 {
+        return DiskRates::default();
+    }
+
+    let per_second = |before: u64, now: u64, scale: f64| {
+        let rate = counter_delta(before, now) as f64 * scale / elapsed_secs;
+        (rate * 10.0).round() / 10.0
+    };
+    let kb_per_sector = SECTOR_SIZE_BYTES / BYTES_PER_KB;
+
+    DiskRates {
+        read_kbps: per_second(previous.sectors_read, current.sectors_read, kb_per_sector),
+        write_kbps: per_second(previous.sectors_written, current.sectors_written, kb_per_sector),
+        read_iops: per_second(previous.reads_completed, current.reads_completed, 1.0),
+        write_iops: per_second(previous.writes_completed, current.writes_completed, 1.0),
+        io_wait_ms: average_io_wait(previous, current),
+    }
+}
+
+// Average time per completed I/O in the window, 0 when the disk was idle
+fn average_io_wait(previous: &DiskStats, current: &DiskStats) -> f64 {
+    let completed = counter_delta(previous.reads_completed, current.reads_completed)
+        + counter_delta(previous.writes_completed, current.writes_completed);
+    if completed == 0 {
+        return 0.0;
+    }
+
+    let waited = counter_delta(previous.weighted_io_ms, current.weighted_io_ms) as f64;
+    ((waited / completed as f64) * 100.0).round() / 100.0
+}
+
+fn disk_sensor(device: &str, suffix: &str, value: f64, unit: &str, sensor_type: SystemSensorType) -> SystemSensor {
+    SystemSensor {
+        name: format!("disk_{}_{}", device, suffix),
+        label: None,
+        value,
+        unit: unit.to_string(),
+        sensor_type,
+        attributes: HashMap::new(),
+        extra: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_diskstats_line() {
+        let line = "   8       0 sda 1000 20 4096 300 500 10 2048 200 0 400 500 0 0 0 0";
+        let (name, stats) = parse_diskstats_line(line).unwrap();
+
+        assert_eq!(name, "sda");
+        assert_eq!(stats.reads_completed, 1000);
+        assert_eq!(stats.writes_completed, 500);
+        assert_eq!(stats.sectors_read, 4096);
+        assert_eq!(stats.sectors_written, 2048);
+        assert_eq!(stats.weighted_io_ms, 500);
+        assert!(parse_diskstats_line("8 0 sda").is_none());
+    }
+
+    #[test]
+    fn test_counter_delta() {
+        assert_eq!(counter_delta(100, 250), 150);
+        assert_eq!(counter_delta(u32::MAX as u64 - 9, 5), 15);
+        assert_eq!(counter_delta(u64::MAX - 9, 5), 0);
+    }
+
+    #[test]
+    fn test_disk_rates() {
+        let previous = DiskStats {
+            reads_completed: 100,
+            sectors_read: 0,
+            writes_completed: 50,
+            sectors_written: 1000,
+            weighted_io_ms: 0,
+        };
+        let current = DiskStats {
+            reads_completed: 300,
+            sectors_read: 4096,
+            writes_completed: 55,
+            sectors_written: 1000,
+            weighted_io_ms: 0,
+        };
+
+        // 4096 sectors of 512 bytes over 2 seconds
+        let rates = disk_rates(&previous, &current, 2.0);
+        assert_eq!(rates.read_kbps, 1024.0);
+        assert_eq!(rates.write_kbps, 0.0);
+        assert_eq!(rates.read_iops, 100.0);
+        assert_eq!(rates.write_iops, 2.5);
+        assert_eq!(disk_rates(&previous, &current, 0.0), DiskRates::default());
+    }
+
+    #[test]
+    fn test_average_io_wait() {
+        // Two reads of /proc/diskstats 10 seconds apart; field 12 (ms doing I/O) grows by
+        // 1000 and field 13 (weighted ms) by 2000, so reading the wrong one shows up
+        let before = " 259       0 nvme0n1 184231 52013 11382562 41215 320118 201337 19203472 612083 0 395360 665841 0 0 0 0 12873 12542";
+        let after = " 259       0 nvme0n1 184381 52013 11384610 41515 320368 201390 19211664 613783 0 396360 667841 0 0 0 0 12873 12542";
+        let (_, previous) = parse_diskstats_line(before).unwrap();
+        let (_, current) = parse_diskstats_line(after).unwrap();
+
+        let rates = disk_rates(&previous, &current, 10.0);
+        assert_eq!(rates.read_kbps, 102.4);
+        assert_eq!(rates.write_kbps, 409.6);
+        assert_eq!(rates.read_iops, 15.0);
+        assert_eq!(rates.write_iops, 25.0);
+        // 2000 ms of weighted I/O time over 400 completed I/Os
+        assert_eq!(rates.io_wait_ms, 5.0);
+        assert_eq!(disk_rates(&current, &current, 10.0).io_wait_ms, 0.0);
+    }
+}
@@ -0,0 +1,70 @@
+use crate::mqtt_client::{MqttPayload, MqttSensorTopics};
+use serde_json::{json, Value};
+
+// Everything that would be published in one cycle, as topic/payload pairs
+pub fn dry_run_report(payloads: &[MqttSensorTopics]) -> Value {
+    let messages: Vec<Value> = payloads
+        .iter()
+        .flat_map(|sensor| {
+            [Some(&sensor.discovery), Some(&sensor.state), sensor.availability.as_ref()]
+                .into_iter()
+                .flatten()
+                .map(message_json)
+        })
+        .collect();
+
+    Value::Array(messages)
+}
+
+// JSON payloads are embedded as objects so the output stays readable
+fn message_json(message: &MqttPayload) -> Value {
+    let payload = serde_json::from_str::<Value>(&message.payload)
+        .unwrap_or_else(|_| Value::String(message.payload.clone()));
+
+    json!({
+        "topic": message.topic,
+        "retain": message.retain,
+        "payload": payload,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mqtt_client::PublishMode;
+    use rumqttc::QoS;
+
+    fn payload(topic: &str, payload: &str) -> MqttPayload {
+        MqttPayload {
+            topic: topic.to_string(),
+            payload: payload.to_string(),
+            retain: false,
+            qos: QoS::AtLeastOnce,
+            payload_bytes: None,
+        }
+    }
+
+    #[test]
+    fn test_dry_run_report() {
+        let topics = MqttSensorTopics {
+            name: "cpu_usage".to_string(),
+            value: Some(12.5),
+            publish_mode: PublishMode::OnChange,
+            state: payload("state", r#"{"value":12.5}"#),
+            discovery: payload("config", r#"{"name":"CPU Usage"}"#),
+            availability: Some(payload("availability", "online")),
+        };
+
+        let report = dry_run_report(&[topics]);
+        let topics: Vec<&str> = report
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|message| message["topic"].as_str().unwrap())
+            .collect();
+
+        assert_eq!(topics, ["config", "state", "availability"]);
+        assert_eq!(report[1]["payload"]["value"], 12.5);
+        assert_eq!(report[2]["payload"], "online");
+    }
+}
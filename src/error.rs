@@ -0,0 +1,47 @@
+use crate::config::ConfigError;
+use crate::mqtt_protocol::MqttConnectionError;
+
+pub enum AppError {
+    Config(ConfigError),
+    MqttConnection(Box<MqttConnectionError>),
+    SignalBind(std::io::Error),
+    MetricsBind(std::io::Error),
+    HttpApiBind(std::io::Error),
+    StartupChecks,
+    PublishTask(tokio::task::JoinError),
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Config(e) => write!(f, "Configuration error: {}", e),
+            AppError::MqttConnection(e) => write!(f, "MQTT connection failed: {}", e),
+            AppError::SignalBind(e) => write!(f, "Failed to bind signal handler: {}", e),
+            AppError::MetricsBind(e) => write!(f, "Failed to bind Prometheus metrics endpoint: {}", e),
+            AppError::HttpApiBind(e) => write!(f, "Failed to bind HTTP API: {}", e),
+            AppError::StartupChecks => write!(f, "Startup checks failed"),
+            AppError::PublishTask(e) => write!(f, "Publish task failed: {}", e),
+        }
+    }
+}
+
+// `main` prints the Debug representation of the returned error, so keep it human-readable
+impl std::fmt::Debug for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self, f)
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<ConfigError> for AppError {
+    fn from(e: ConfigError) -> Self {
+        AppError::Config(e)
+    }
+}
+
+impl From<MqttConnectionError> for AppError {
+    fn from(e: MqttConnectionError) -> Self {
+        AppError::MqttConnection(Box::new(e))
+    }
+}
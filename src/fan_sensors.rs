@@ -1,64 +1,135 @@
+use crate::config::{FanConfig, RpmToPercentMethod};
 use crate::hwmon_devices::{discover_hwmon_devices, HwmonDevice};
-use crate::sensors::SystemSensor;
-use crate::sensors::SystemSensorType::Fan;
+use crate::naming::sanitize_sensor_name;
+use crate::sensors::{CollectFuture, SensorCollector, SystemSensor};
+use crate::sysfs::{read_attribute, read_number};
+use crate::sensors::SystemSensorType::{Fan, FanPercent, FanPwm};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use std::time::Duration;
 
 const FAN_FILE_PREFIX: &str = "fan";
 const FAN_FILE_SUFFIX: &str = "_input";
+const PWM_FILE_PREFIX: &str = "pwm";
+const PWM_MAX: f64 = 255.0;
 
-pub fn collect_all_fans() -> Vec<SystemSensor> {
+pub struct FanCollector {
+    pub fan_config: FanConfig,
+    pub fan_pwm: bool,
+    pub hwmon_cache_ttl: Duration,
+    pub read_timeout: Duration,
+}
+
+impl SensorCollector for FanCollector {
+    fn collect(&self) -> CollectFuture<'_> {
+        Box::pin(collect_all_fans(&self.fan_config, self.fan_pwm, self.hwmon_cache_ttl, self.read_timeout))
+    }
+
+    fn name(&self) -> &str {
+        "fan"
+    }
+}
+
+#[tracing::instrument(name = "collect_fans", level = "debug", skip_all)]
+async fn collect_all_fans(
+    fan_config: &FanConfig,
+    fan_pwm: bool,
+    hwmon_cache_ttl: Duration,
+    read_timeout: Duration,
+) -> Vec<SystemSensor> {
     let mut sensors = Vec::new();
 
-    match discover_hwmon_devices() {
+    match discover_hwmon_devices(hwmon_cache_ttl) {
         Ok(devices) => {
             for device in devices {
-                let device_sensors = scan_device_fans(&device);
+                let device_sensors = scan_device_fans(&device, fan_config, read_timeout).await;
                 sensors.extend(device_sensors);
+                if fan_pwm {
+                    sensors.extend(read_pwm_sensors(&device, read_timeout).await);
+                }
             }
         }
         Err(e) => {
-            eprintln!("Failed to discover hwmon devices: {}", e);
+            tracing::warn!(error = %e, "Failed to discover hwmon devices");
         }
     }
 
     sensors
 }
 
-fn scan_device_fans(device: &HwmonDevice) -> Vec<SystemSensor> {
+async fn scan_device_fans(device: &HwmonDevice, fan_config: &FanConfig, read_timeout: Duration) -> Vec<SystemSensor> {
     let mut sensors = Vec::new();
 
     match fs::read_dir(&device.path) {
         Ok(entries) => {
             for entry in entries.flatten() {
-                if let Some(sensor) = process_fan_file(&entry.path(), device) {
+                if let Some(sensor) = process_fan_file(&entry.path(), device, read_timeout).await {
+                    if let Some(percent_sensor) =
+                        fan_percent_sensor(&entry.path(), &sensor, fan_config, read_timeout).await
+                    {
+                        sensors.push(percent_sensor);
+                    }
                     sensors.push(sensor);
                 }
             }
         }
         Err(e) => {
-            eprintln!(
-                "Failed to read device directory {}: {}",
-                device.path.display(),
-                e
-            );
+            tracing::warn!(device = %device.path.display(), error = %e, "Failed to read device directory");
         }
     }
 
     sensors
 }
 
-fn process_fan_file(file_path: &Path, device: &HwmonDevice) -> Option<SystemSensor> {
+// pwmN channels don't necessarily line up with fanN inputs, so every pwmN file is
+// published on its own rather than being paired with a fan
+async fn read_pwm_sensors(device: &HwmonDevice, read_timeout: Duration) -> Vec<SystemSensor> {
+    let Ok(entries) = fs::read_dir(&device.path) else {
+        return Vec::new();
+    };
+
+    let mut sensors = Vec::new();
+    for entry in entries.flatten() {
+        let Some(channel) = extract_pwm_channel(&entry.file_name().to_string_lossy()) else {
+            continue;
+        };
+        let Some(raw) = read_number(&entry.path(), read_timeout).await else {
+            continue;
+        };
+        sensors.push(SystemSensor {
+            name: sanitize_sensor_name(&format!("{}_{}_pwm", device.name, channel)),
+            label: None,
+            value: pwm_to_percent(raw),
+            unit: "%".to_string(),
+            sensor_type: FanPwm,
+            attributes: HashMap::new(),
+            extra: None,
+        });
+    }
+    sensors
+}
+
+// "pwm2" -> 2; pwm2_enable, pwm2_mode and friends are control files, not duty cycles
+fn extract_pwm_channel(filename: &str) -> Option<u32> {
+    filename.strip_prefix(PWM_FILE_PREFIX)?.parse().ok()
+}
+
+fn pwm_to_percent(raw: f64) -> f64 {
+    ((raw / PWM_MAX * 100.0).clamp(0.0, 100.0) * 10.0).round() / 10.0
+}
+
+async fn process_fan_file(file_path: &Path, device: &HwmonDevice, read_timeout: Duration) -> Option<SystemSensor> {
     let filename = file_path.file_name()?.to_string_lossy();
 
     if !is_fan_file(&filename) {
         return None;
     }
 
-    let fan_rpm = read_fan_value(file_path)?;
-    let fan_label = get_fan_label(file_path)?;
+    let fan_rpm = read_fan_value(file_path, read_timeout).await?;
+    let fan_label = get_fan_label(file_path, read_timeout).await?;
     let fan_id = extract_fan_id(&filename)?;
-    let sensor_name = format!("{}_{}_{}", device.name, fan_id, "fan");
+    let sensor_name = sanitize_sensor_name(&format!("{}_{}_{}", device.name, fan_id, "fan"));
 
     Some(SystemSensor {
         name: sensor_name,
@@ -66,20 +137,84 @@ fn process_fan_file(file_path: &Path, device: &HwmonDevice) -> Option<SystemSens
         value: fan_rpm as f64,
         unit: "RPM".parse().unwrap(),
         sensor_type: Fan,
+        attributes: HashMap::new(),
+        extra: None,
+    })
+}
+
+async fn fan_percent_sensor(
+    file_path: &Path,
+    rpm_sensor: &SystemSensor,
+    fan_config: &FanConfig,
+    read_timeout: Duration,
+) -> Option<SystemSensor> {
+    let (min_rpm, max_rpm) = fan_rpm_range(file_path, &rpm_sensor.name, fan_config, read_timeout).await?;
+
+    Some(SystemSensor {
+        name: format!("{}_pct", rpm_sensor.name),
+        label: rpm_sensor.label.as_ref().map(|label| format!("{} Speed", label)),
+        value: rpm_to_percent(rpm_sensor.value, min_rpm, max_rpm),
+        unit: "%".to_string(),
+        sensor_type: FanPercent,
+        attributes: HashMap::new(),
+        extra: None,
     })
 }
 
-fn is_fan_file(filename: &str) -> bool {
+async fn fan_rpm_range(
+    file_path: &Path,
+    sensor_name: &str,
+    fan_config: &FanConfig,
+    read_timeout: Duration,
+) -> Option<(f64, f64)> {
+    match fan_config.rpm_to_percent_method {
+        RpmToPercentMethod::None => None,
+        RpmToPercentMethod::Hwmon => {
+            let max_rpm = read_fan_limit(file_path, "_max", read_timeout).await?;
+            let min_rpm = read_fan_limit(file_path, "_min", read_timeout).await.unwrap_or(0.0);
+            Some((min_rpm, max_rpm))
+        }
+        RpmToPercentMethod::Manual => {
+            let fan_override = fan_config
+                .overrides
+                .iter()
+                .find(|o| o.sensor_name == sensor_name);
+            let min_rpm = fan_override
+                .and_then(|o| o.fan_min_rpm)
+                .unwrap_or(fan_config.fan_min_rpm);
+            let max_rpm = fan_override
+                .and_then(|o| o.fan_max_rpm)
+                .unwrap_or(fan_config.fan_max_rpm);
+            Some((min_rpm, max_rpm))
+        }
+    }
+}
+
+fn rpm_to_percent(rpm: f64, min_rpm: f64, max_rpm: f64) -> f64 {
+    if max_rpm <= min_rpm {
+        return 0.0;
+    }
+    let percent = (rpm - min_rpm) / (max_rpm - min_rpm) * 100.0;
+    (percent.clamp(0.0, 100.0) * 10.0).round() / 10.0
+}
+
+async fn read_fan_limit(file_path: &Path, suffix: &str, read_timeout: Duration) -> Option<f64> {
+    let filename = file_path.file_name()?.to_string_lossy();
+    let limit_path = file_path.with_file_name(filename.replace(FAN_FILE_SUFFIX, suffix));
+    read_number(&limit_path, read_timeout).await
+}
+
+pub fn is_fan_file(filename: &str) -> bool {
     filename.starts_with(FAN_FILE_PREFIX) && filename.ends_with(FAN_FILE_SUFFIX)
 }
 
-fn read_fan_value(file_path: &Path) -> Option<f32> {
-    let fan_raw = fs::read_to_string(file_path).ok()?;
+async fn read_fan_value(file_path: &Path, read_timeout: Duration) -> Option<f32> {
+    let fan_raw = read_attribute(file_path, read_timeout).await?;
     let fan_rpm = fan_raw.trim().parse::<f32>().ok()?;
     Some(fan_rpm)
 }
 
-fn extract_fan_id(filename: &str) -> Option<String> {
+pub fn extract_fan_id(filename: &str) -> Option<String> {
     Some(
         filename
             .replace(FAN_FILE_PREFIX, "")
@@ -88,10 +223,78 @@ fn extract_fan_id(filename: &str) -> Option<String> {
 }
 
 
-fn get_fan_label(file_path: &Path) -> Option<String> {
+async fn get_fan_label(file_path: &Path, read_timeout: Duration) -> Option<String> {
     let filename = file_path.file_name()?.to_string_lossy();
     let label_filename = filename.replace("_input", "_label");
     let label_path = file_path.with_file_name(label_filename);
-    let label_raw = fs::read_to_string(label_path).ok()?;
-    Some(label_raw.trim().to_string())
+    read_attribute(&label_path, read_timeout).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FanOverride;
+
+    const READ_TIMEOUT: Duration = Duration::from_millis(500);
+
+    #[test]
+    fn test_rpm_to_percent() {
+        assert_eq!(rpm_to_percent(1650.0, 300.0, 3000.0), 50.0);
+        assert_eq!(rpm_to_percent(100.0, 300.0, 3000.0), 0.0);
+        assert_eq!(rpm_to_percent(5000.0, 300.0, 3000.0), 100.0);
+        assert_eq!(rpm_to_percent(1000.0, 3000.0, 3000.0), 0.0);
+    }
+
+    #[test]
+    fn test_pwm_helpers() {
+        assert_eq!(extract_pwm_channel("pwm1"), Some(1));
+        assert_eq!(extract_pwm_channel("pwm1_enable"), None);
+        assert_eq!(extract_pwm_channel("fan1_input"), None);
+        assert_eq!(pwm_to_percent(255.0), 100.0);
+        assert_eq!(pwm_to_percent(128.0), 50.2);
+        assert_eq!(pwm_to_percent(0.0), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_fan_rpm_range_none() {
+        let config = FanConfig::default();
+        assert_eq!(fan_rpm_range(Path::new("/nonexistent/fan1_input"), "fan", &config, READ_TIMEOUT).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_fan_rpm_range_manual() {
+        let config = FanConfig {
+            rpm_to_percent_method: RpmToPercentMethod::Manual,
+            overrides: vec![FanOverride {
+                sensor_name: "nct6797_2_fan".to_string(),
+                fan_min_rpm: None,
+                fan_max_rpm: Some(1800.0),
+            }],
+            ..FanConfig::default()
+        };
+        let path = Path::new("/nonexistent/fan1_input");
+
+        assert_eq!(fan_rpm_range(path, "nct6797_1_fan", &config, READ_TIMEOUT).await, Some((300.0, 3000.0)));
+        assert_eq!(fan_rpm_range(path, "nct6797_2_fan", &config, READ_TIMEOUT).await, Some((300.0, 1800.0)));
+    }
+
+    #[tokio::test]
+    async fn test_fan_rpm_range_hwmon() {
+        let dir = std::env::temp_dir().join("orbiq_fan_range_test");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("fan1_input"), "1200\n").unwrap();
+        fs::write(dir.join("fan1_max"), "2400\n").unwrap();
+        fs::write(dir.join("fan2_input"), "1200\n").unwrap();
+
+        let config = FanConfig {
+            rpm_to_percent_method: RpmToPercentMethod::Hwmon,
+            ..FanConfig::default()
+        };
+        let with_max = fan_rpm_range(&dir.join("fan1_input"), "fan1", &config, READ_TIMEOUT).await;
+        let without_max = fan_rpm_range(&dir.join("fan2_input"), "fan2", &config, READ_TIMEOUT).await;
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(with_max, Some((0.0, 2400.0)));
+        assert_eq!(without_max, None);
+    }
 }
@@ -0,0 +1,156 @@
+use crate::config::{FiltersConfig, ValueRangeConfig};
+use crate::sensors::{SystemSensor, SystemSensorType};
+use crate::utils::matches_pattern;
+
+#[derive(Debug, Clone)]
+enum FilterRule {
+    DisabledTypes(Vec<SystemSensorType>),
+    IncludeNames(Vec<String>),
+    ExcludeNames(Vec<String>),
+    SuppressZero(Vec<SystemSensorType>),
+    ValueRange(ValueRangeConfig),
+}
+
+impl FilterRule {
+    fn keep(&self, sensor: &SystemSensor) -> bool {
+        match self {
+            FilterRule::DisabledTypes(types) => !types.contains(&sensor.sensor_type),
+            FilterRule::IncludeNames(patterns) => {
+                patterns.iter().any(|p| matches_pattern(p, &sensor.name))
+            }
+            FilterRule::ExcludeNames(patterns) => {
+                !patterns.iter().any(|p| matches_pattern(p, &sensor.name))
+            }
+            FilterRule::SuppressZero(types) => {
+                !(sensor.value == 0.0 && types.contains(&sensor.sensor_type))
+            }
+            FilterRule::ValueRange(range) => {
+                !matches_pattern(&range.sensor_pattern, &sensor.name)
+                    || (range.min.is_none_or(|min| sensor.value >= min)
+                        && range.max.is_none_or(|max| sensor.value <= max))
+            }
+        }
+    }
+}
+
+// All sensor filtering rules, applied together after collection
+#[derive(Debug, Clone, Default)]
+pub struct SensorFilter {
+    rules: Vec<FilterRule>,
+}
+
+impl SensorFilter {
+    pub fn new_from_config(filters: &FiltersConfig) -> Self {
+        let mut rules = Vec::new();
+
+        if !filters.disabled_types.is_empty() {
+            rules.push(FilterRule::DisabledTypes(filters.disabled_types.clone()));
+        }
+        if let Some(include) = &filters.include_sensors {
+            rules.push(FilterRule::IncludeNames(include.clone()));
+        }
+        if !filters.exclude_sensors.is_empty() {
+            rules.push(FilterRule::ExcludeNames(filters.exclude_sensors.clone()));
+        }
+        if !filters.suppress_zero_types.is_empty() {
+            rules.push(FilterRule::SuppressZero(filters.suppress_zero_types.clone()));
+        }
+        for range in &filters.value_ranges {
+            rules.push(FilterRule::ValueRange(range.clone()));
+        }
+
+        Self { rules }
+    }
+
+    pub fn apply(&self, sensors: Vec<SystemSensor>) -> Vec<SystemSensor> {
+        sensors
+            .into_iter()
+            // Broken drivers occasionally report values that can't be serialized
+            .filter(|sensor| sensor.value.is_finite())
+            .filter(|sensor| self.rules.iter().all(|rule| rule.keep(sensor)))
+            .collect()
+    }
+}
+
+// include_sensors (when set) keeps only matching sensors, exclude_sensors then drops
+// matches from what is left; the remaining [filters] rules are applied as well
+pub fn apply_filters(sensors: Vec<SystemSensor>, config: &FiltersConfig) -> Vec<SystemSensor> {
+    SensorFilter::new_from_config(config).apply(sensors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DaemonConfig;
+    use std::collections::HashMap;
+
+    fn sensor(name: &str, value: f64, sensor_type: SystemSensorType) -> SystemSensor {
+        SystemSensor {
+            name: name.to_string(),
+            label: None,
+            value,
+            unit: "".to_string(),
+            sensor_type,
+            attributes: HashMap::new(),
+            extra: None,
+        }
+    }
+
+    fn names(sensors: &[SystemSensor]) -> Vec<&str> {
+        sensors.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    fn sample_sensors() -> Vec<SystemSensor> {
+        vec![
+            sensor("cpu_usage", 12.0, SystemSensorType::CpuUsage),
+            sensor("k10temp_1", 55.0, SystemSensorType::Temperature),
+            sensor("nvme_1", 255.0, SystemSensorType::Temperature),
+            sensor("nvme_2", 40.0, SystemSensorType::Temperature),
+            sensor("nct6797_1_fan", 0.0, SystemSensorType::Fan),
+            sensor("nct6797_2_fan", 900.0, SystemSensorType::Fan),
+        ]
+    }
+
+    #[test]
+    fn test_empty_filter_keeps_everything() {
+        let filter = SensorFilter::new_from_config(&FiltersConfig::default());
+        assert_eq!(filter.apply(sample_sensors()).len(), 6);
+    }
+
+    #[test]
+    fn test_filter_rules_compose() {
+        let mut config = DaemonConfig::default();
+        config.filters.include_sensors = Some(vec!["nvme_*".to_string(), "*_fan".to_string()]);
+        config.filters.exclude_sensors = vec!["nct6797_2_*".to_string()];
+        config.filters.suppress_zero_types = vec![SystemSensorType::Fan];
+        config.filters.value_ranges = vec![ValueRangeConfig {
+            sensor_pattern: "nvme_*".to_string(),
+            min: Some(-40.0),
+            max: Some(150.0),
+        }];
+
+        let filtered = SensorFilter::new_from_config(&config.filters).apply(sample_sensors());
+        assert_eq!(names(&filtered), vec!["nvme_2"]);
+    }
+
+    #[test]
+    fn test_apply_filters_exclude_wins_over_include() {
+        let filters = FiltersConfig {
+            include_sensors: Some(vec!["nvme_?".to_string(), "cpu_usage".to_string()]),
+            exclude_sensors: vec!["nvme_1".to_string()],
+            ..FiltersConfig::default()
+        };
+
+        let filtered = apply_filters(sample_sensors(), &filters);
+        assert_eq!(names(&filtered), vec!["cpu_usage", "nvme_2"]);
+    }
+
+    #[test]
+    fn test_disabled_types() {
+        let mut config = DaemonConfig::default();
+        config.filters.disabled_types = vec![SystemSensorType::Temperature];
+
+        let filtered = SensorFilter::new_from_config(&config.filters).apply(sample_sensors());
+        assert_eq!(names(&filtered), vec!["cpu_usage", "nct6797_1_fan", "nct6797_2_fan"]);
+    }
+}
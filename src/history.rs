@@ -0,0 +1,87 @@
+use crate::sensors::SystemSensor;
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+
+// Last `window_size` published values of every sensor, keyed by sensor name
+static HISTORY: OnceLock<Mutex<HashMap<String, VecDeque<f64>>>> = OnceLock::new();
+
+// Rolling statistics over the readings currently in a sensor's window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistoryStats {
+    pub window_size: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+impl HistoryStats {
+    // Added next to "value" in the state payload as min_<N>, max_<N> and avg_<N>
+    pub fn add_to(&self, payload: &mut Value) {
+        let n = self.window_size;
+        payload[format!("min_{}", n)] = json!(self.min);
+        payload[format!("max_{}", n)] = json!(self.max);
+        payload[format!("avg_{}", n)] = json!(self.mean);
+    }
+}
+
+// Text sensors have no history; a window size of 0 disables it
+pub fn record_history(sensor: &SystemSensor, window_size: usize) -> Option<HistoryStats> {
+    if window_size == 0 || sensor.extra.is_some() {
+        return None;
+    }
+    let mut history = HISTORY
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|p| p.into_inner());
+    let window = history.entry(sensor.name.clone()).or_default();
+    push_reading(window, sensor.value, window_size)
+}
+
+fn push_reading(window: &mut VecDeque<f64>, value: f64, window_size: usize) -> Option<HistoryStats> {
+    window.push_back(value);
+    while window.len() > window_size {
+        window.pop_front();
+    }
+    let min = window.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = window.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let mean = window.iter().sum::<f64>() / window.len() as f64;
+    Some(HistoryStats {
+        window_size,
+        min,
+        max,
+        mean: (mean * 1000.0).round() / 1000.0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_reading_caps_window() {
+        let mut window = VecDeque::new();
+        push_reading(&mut window, 50.0, 3);
+        push_reading(&mut window, 56.0, 3);
+        push_reading(&mut window, 53.0, 3);
+        let stats = push_reading(&mut window, 52.0, 3).unwrap();
+
+        assert_eq!(window.len(), 3);
+        assert_eq!(stats.min, 52.0);
+        assert_eq!(stats.max, 56.0);
+        assert_eq!(stats.mean, 53.667);
+    }
+
+    #[test]
+    fn test_stats_in_payload() {
+        let stats = HistoryStats {
+            window_size: 10,
+            min: 51.2,
+            max: 57.1,
+            mean: 54.0,
+        };
+        let mut payload = json!({ "value": 54.3 });
+        stats.add_to(&mut payload);
+        assert_eq!(payload, json!({ "value": 54.3, "min_10": 51.2, "max_10": 57.1, "avg_10": 54.0 }));
+    }
+}
@@ -1,6 +1,23 @@
+use crate::config::{AvailabilityMode, EntityCategory, PayloadFormat};
+use crate::history::HistoryStats;
 use crate::mqtt_client::MqttPayload;
 use crate::sensors::{SystemSensor, SystemSensorType};
+use rumqttc::QoS;
 use serde_json::json;
+use sha2::{Digest, Sha256};
+
+const MAX_OBJECT_ID_LEN: usize = 64;
+const OBJECT_ID_HASH_LEN: usize = 8;
+// Sensor name suffixes published by smart_sensor
+const SMART_ATTRIBUTE_NAMES: [(&str, &str); 7] = [
+    ("power_on_hours", "Power On Hours"),
+    ("health_passed", "SMART Health"),
+    ("reallocated_sectors", "Reallocated Sectors"),
+    ("pending_sectors", "Pending Sectors"),
+    ("offline_uncorrectable", "Offline Uncorrectable Sectors"),
+    ("media_errors", "Media Errors"),
+    ("percentage_used", "Wear"),
+];
 
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct DeviceInfo {
@@ -14,6 +31,7 @@ pub struct DeviceInfo {
 
 #[derive(Debug, Clone)]
 pub struct Topic {
+    discovery_prefix: String,
     sensor_name: String,
     device_name: String,
     sub_topic: String,
@@ -39,17 +57,32 @@ impl DeviceInfo {
 }
 
 // Generate friendly names for temperature sensors
-fn generate_friendly_name(sensor_name: &str) -> String {
+pub fn generate_friendly_name(sensor_name: &str, label: Option<&str>) -> String {
+    if let Some(label) = label {
+        return label.to_string();
+    }
     match sensor_name {
+        name if name.starts_with("onewire_") => format!(
+            "1-Wire {} Temperature",
+            name.trim_start_matches("onewire_").trim_end_matches("_temperature")
+        ),
+        name if name.starts_with("thermal_") => format!(
+            "{} Temperature",
+            name.trim_start_matches("thermal_").replace("_", " ")
+        ),
+        name if name.starts_with("smart_") => format!(
+            "{} Temperature",
+            name.trim_start_matches("smart_").trim_end_matches("_temperature")
+        ),
         name if name.contains("k10temp") => "CPU Temperature".to_string(),
         name if name.contains("nouveau") => "GPU Temperature".to_string(),
         name if name.contains("nvme") => format!(
             "NVMe {} Temperature",
-            name.split('_').last().unwrap_or("Unknown")
+            name.split('_').next_back().unwrap_or("Unknown")
         ),
         name if name.contains("coretemp") => format!(
             "Core {} Temperature",
-            name.split('_').last().unwrap_or("Unknown")
+            name.split('_').next_back().unwrap_or("Unknown")
         ),
         name if name.contains("acpi") => "System Temperature".to_string(),
         name if name.contains("amdgpu") => "AMD GPU Temperature".to_string(),
@@ -67,13 +100,34 @@ fn generate_friendly_name_for_fan(sensor: &SystemSensor) -> String {
         None => format!("Fan {}", sensor.name),
     }
 }
+
+// "net_eth0_rx_kbps" -> "eth0"
+fn network_interface_name(sensor_name: &str) -> &str {
+    let name = sensor_name.strip_prefix("net_").unwrap_or(sensor_name);
+    name.rsplitn(3, '_').last().unwrap_or(name)
+}
 // Generate friendly names for system sensors
 fn generate_system_friendly_name(sensor: &SystemSensor) -> String {
+    // Labels from hwmon or the config take priority over generated names
+    if let Some(label) = &sensor.label {
+        return label.to_string();
+    }
+
     match &sensor.sensor_type {
         SystemSensorType::CpuUsage => "CPU Usage".to_string(),
+        SystemSensorType::CpuCoreUsage => {
+            let core = sensor.name.replace("cpu_core_", "").replace("_usage", "");
+            format!("CPU Core {} Usage", core)
+        }
+        SystemSensorType::LoadAverage => {
+            format!("Load Average ({})", sensor.name.replace("load_avg_", ""))
+        }
         SystemSensorType::MemoryUsage => "Memory Usage".to_string(),
         SystemSensorType::MemoryUsed => "Memory Used".to_string(),
         SystemSensorType::MemoryTotal => "Memory Total".to_string(),
+        SystemSensorType::SwapUsage => "Swap Usage".to_string(),
+        SystemSensorType::SwapUsed => "Swap Used".to_string(),
+        SystemSensorType::SwapTotal => "Swap Total".to_string(),
         SystemSensorType::DiskUsage => {
             if sensor.name.contains("root") {
                 "Disk Usage (Root)".to_string()
@@ -82,6 +136,14 @@ fn generate_system_friendly_name(sensor: &SystemSensor) -> String {
                 format!("Disk Usage ({})", mount_name.to_uppercase())
             }
         }
+        SystemSensorType::DiskInodes => {
+            if sensor.name == "disk_inodes_pct_root" {
+                "Inode Usage (Root)".to_string()
+            } else {
+                let mount_name = sensor.name.replace("disk_inodes_pct_", "").replace("_", " ");
+                format!("Inode Usage ({})", mount_name.to_uppercase())
+            }
+        }
         SystemSensorType::DiskUsed => {
             if sensor.name.contains("root") {
                 "Disk Used (Root)".to_string()
@@ -99,40 +161,395 @@ fn generate_system_friendly_name(sensor: &SystemSensor) -> String {
             }
         }
         SystemSensorType::Fan => generate_friendly_name_for_fan(sensor),
-        SystemSensorType::Temperature => generate_friendly_name(&sensor.name),
+        SystemSensorType::FanPercent => format!("{} Speed", generate_friendly_name_for_fan(sensor)),
+        SystemSensorType::FanPwm => {
+            // nct6797_2_pwm -> "nct6797 PWM 2"
+            let name = sensor.name.trim_end_matches("_pwm");
+            match name.rsplit_once('_') {
+                Some((device, channel)) => format!("{} PWM {}", device.replace("_", " "), channel),
+                None => format!("{} PWM", name),
+            }
+        }
+        SystemSensorType::FileDescriptors => match sensor.name.as_str() {
+            "fd_open_count" => "Open File Descriptors".to_string(),
+            "fd_max_count" => "Max File Descriptors".to_string(),
+            _ => "File Descriptor Usage".to_string(),
+        },
+        SystemSensorType::Entropy => match sensor.name.as_str() {
+            "kernel_entropy_bits" => "Kernel Entropy".to_string(),
+            _ => "Entropy Pool Usage".to_string(),
+        },
+        SystemSensorType::EntropyAlert => "Low Entropy Alert".to_string(),
+        SystemSensorType::Temperature => generate_friendly_name(&sensor.name, sensor.label.as_deref()),
+        SystemSensorType::TemperatureLimit => match sensor.name.rsplit_once('_') {
+            Some((base, "crit")) => format!("{} Critical", generate_friendly_name(base, None)),
+            Some((base, _)) => format!("{} Max", generate_friendly_name(base, None)),
+            None => generate_friendly_name(&sensor.name, None),
+        },
+        SystemSensorType::Uptime => "System Uptime".to_string(),
+        SystemSensorType::Heartbeat => "Heartbeat".to_string(),
+        SystemSensorType::Generic => sensor.name.replace("_", " "),
+        SystemSensorType::DaemonMetric => match sensor.name.as_str() {
+            "daemon_publish_count" => "MQTT Messages Published".to_string(),
+            "daemon_publish_errors" => "MQTT Publish Errors".to_string(),
+            _ => generate_friendly_name(&sensor.name, None),
+        },
+        SystemSensorType::PingLatency => {
+            let target = sensor.name.trim_start_matches("ping_").trim_end_matches("_latency_ms");
+            format!("Ping {}", target.replace("_", " "))
+        }
+        SystemSensorType::Voltage => format!("{} Voltage", sensor.name.replace("_voltage", "").replace("_", " ")),
+        SystemSensorType::Power => format!("{} Power", sensor.name.replace("_power", "").replace("_", " ")),
+        SystemSensorType::NetworkRx => {
+            format!("{} Receive Rate", network_interface_name(&sensor.name))
+        }
+        SystemSensorType::NetworkTx => {
+            format!("{} Transmit Rate", network_interface_name(&sensor.name))
+        }
+        SystemSensorType::NetworkLinkStatus => {
+            format!("{} Link", network_interface_name(&sensor.name))
+        }
+        SystemSensorType::NetworkRxTotal => {
+            format!("{} Total Received", network_interface_name(&sensor.name))
+        }
+        SystemSensorType::NetworkTxTotal => {
+            format!("{} Total Transmitted", network_interface_name(&sensor.name))
+        }
+        SystemSensorType::DiskRead => {
+            let device = sensor.name.replace("disk_", "").replace("_read_kbps", "");
+            format!("Disk Read Rate ({})", device)
+        }
+        SystemSensorType::DiskWrite => {
+            let device = sensor.name.replace("disk_", "").replace("_write_kbps", "");
+            format!("Disk Write Rate ({})", device)
+        }
+        SystemSensorType::DiskReadIops => {
+            let device = sensor.name.replace("disk_", "").replace("_read_iops", "");
+            format!("Disk Read IOPS ({})", device)
+        }
+        SystemSensorType::DiskWriteIops => {
+            let device = sensor.name.replace("disk_", "").replace("_write_iops", "");
+            format!("Disk Write IOPS ({})", device)
+        }
+        SystemSensorType::DiskIoWait => {
+            let device = sensor.name.replace("disk_", "").replace("_io_wait_ms", "");
+            format!("Disk I/O Wait ({})", device)
+        }
+        SystemSensorType::CpuFrequency => {
+            let core = sensor.name.replace("cpu_core_", "").replace("_freq_mhz", "");
+            format!("CPU Core {} Frequency", core)
+        }
+        SystemSensorType::BatteryPercent => {
+            let battery = sensor.name.replace("battery_", "").replace("_percent", "");
+            format!("Battery {} Level", battery.to_uppercase())
+        }
+        SystemSensorType::BatteryState => {
+            let battery = sensor.name.replace("battery_", "").replace("_state", "");
+            format!("Battery {} State", battery.to_uppercase())
+        }
+        SystemSensorType::BatteryEnergy => {
+            let name = sensor.name.replace("battery_", "").replace("_wh", "");
+            match name.split_once("_energy_") {
+                Some((battery, "full")) => format!("Battery {} Energy Full", battery.to_uppercase()),
+                Some((battery, _)) => format!("Battery {} Energy", battery.to_uppercase()),
+                None => format!("Battery {} Energy", name.to_uppercase()),
+            }
+        }
+        SystemSensorType::PowerSupplyOnline => {
+            let supply = sensor.name.replace("power_supply_", "").replace("_online", "");
+            format!("{} Power Connected", supply.to_uppercase())
+        }
+        SystemSensorType::ProcessCount => "Processes".to_string(),
+        SystemSensorType::ThreadCount => "Threads".to_string(),
+        SystemSensorType::PressureStall => {
+            // psi_memory_some_avg10 -> "Memory Pressure (some, 10s)"
+            let parts: Vec<&str> = sensor.name.trim_start_matches("psi_").split('_').collect();
+            match parts.as_slice() {
+                [resource, kind, window] => {
+                    let resource = match *resource {
+                        "cpu" => "CPU",
+                        "io" => "I/O",
+                        _ => "Memory",
+                    };
+                    format!("{} Pressure ({}, {}s)", resource, kind, window.trim_start_matches("avg"))
+                }
+                _ => sensor.name.replace("_", " "),
+            }
+        }
+        SystemSensorType::WatchedProcess => {
+            let name = sensor.name.trim_start_matches("proc_");
+            if let Some(process) = name.strip_suffix("_cpu_usage") {
+                format!("{} CPU Usage", process)
+            } else if let Some(process) = name.strip_suffix("_memory_mb") {
+                format!("{} Memory", process)
+            } else if let Some(process) = name.strip_suffix("_thread_count") {
+                format!("{} Threads", process)
+            } else {
+                name.replace("_", " ")
+            }
+        }
+        SystemSensorType::SmartAttribute => {
+            // smart_sda_reallocated_sectors -> "sda Reallocated Sectors"
+            let name = sensor.name.trim_start_matches("smart_");
+            SMART_ATTRIBUTE_NAMES
+                .iter()
+                .find_map(|(suffix, friendly)| {
+                    let device = name.strip_suffix(suffix)?.strip_suffix('_')?;
+                    Some(format!("{} {}", device, friendly))
+                })
+                .unwrap_or_else(|| name.replace("_", " "))
+        }
+        SystemSensorType::WifiSignal => {
+            let name = sensor.name.trim_start_matches("wifi_");
+            if let Some(interface) = name.strip_suffix("_signal_dbm") {
+                format!("{} WiFi Signal", interface)
+            } else if let Some(interface) = name.strip_suffix("_noise_dbm") {
+                format!("{} WiFi Noise", interface)
+            } else if let Some(interface) = name.strip_suffix("_quality_pct") {
+                format!("{} WiFi Link Quality", interface)
+            } else {
+                name.replace("_", " ")
+            }
+        }
+        SystemSensorType::SystemInfo => match sensor.name.as_str() {
+            "system_hostname" => "Hostname".to_string(),
+            "os_release" => "OS Release".to_string(),
+            "kernel_version" => "Kernel Version".to_string(),
+            _ => sensor.name.replace("_", " "),
+        },
+        SystemSensorType::ConfigInfo => match sensor.name.as_str() {
+            "orbiq_config_hash" => "Config Hash".to_string(),
+            _ => "Config File".to_string(),
+        },
+        SystemSensorType::Humidity => {
+            format!("{} Humidity", sensor.name.replace("_humidityrelative", "").replace("_", " "))
+        }
+    }
+}
+
+// Produce a slug-style object id that Home Assistant will not rewrite. Ids longer than
+// the limit are truncated and suffixed with a hash of the full id to keep them unique.
+pub fn generate_object_id(device: &str, sensor_name: &str) -> String {
+    let full = format!("orbiq_{}_{}", device, sensor_name).to_lowercase();
+
+    let mut slug = String::with_capacity(full.len());
+    for c in full.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.ends_with('_') {
+            slug.push('_');
+        }
+    }
+    let slug = slug.trim_matches('_').to_string();
+
+    if slug.len() <= MAX_OBJECT_ID_LEN {
+        return slug;
+    }
+
+    let digest = Sha256::digest(full.as_bytes());
+    let hash: String = digest[..OBJECT_ID_HASH_LEN / 2]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    let prefix = slug[..MAX_OBJECT_ID_LEN - OBJECT_ID_HASH_LEN - 1].trim_end_matches('_');
+    format!("{}_{}", prefix, hash)
+}
+
+// Static facts about the machine belong in the Diagnostic section rather than the dashboard
+fn default_entity_category(sensor_type: &SystemSensorType) -> Option<EntityCategory> {
+    match sensor_type {
+        SystemSensorType::MemoryTotal
+        | SystemSensorType::DiskTotal
+        | SystemSensorType::SystemInfo
+        | SystemSensorType::Uptime
+        | SystemSensorType::DaemonMetric
+        | SystemSensorType::ConfigInfo => Some(EntityCategory::Diagnostic),
+        _ => None,
+    }
+}
+
+// Decimal places the frontend shows; without it HA prints raw floats like 23.100000
+fn suggested_precision(sensor_type: &SystemSensorType) -> Option<u8> {
+    match sensor_type {
+        SystemSensorType::Fan
+        | SystemSensorType::ProcessCount
+        | SystemSensorType::ThreadCount
+        | SystemSensorType::SmartAttribute
+        | SystemSensorType::WifiSignal
+        | SystemSensorType::FileDescriptors
+        | SystemSensorType::Entropy
+        | SystemSensorType::Uptime
+        | SystemSensorType::Heartbeat
+        | SystemSensorType::DaemonMetric
+        | SystemSensorType::CpuFrequency
+        | SystemSensorType::NetworkRxTotal
+        | SystemSensorType::NetworkTxTotal => Some(0),
+        SystemSensorType::CpuUsage
+        | SystemSensorType::CpuCoreUsage
+        | SystemSensorType::MemoryUsage
+        | SystemSensorType::SwapUsage
+        | SystemSensorType::DiskUsage
+        | SystemSensorType::DiskInodes
+        | SystemSensorType::DiskIoWait
+        | SystemSensorType::PingLatency
+        | SystemSensorType::FanPercent
+        | SystemSensorType::FanPwm
+        | SystemSensorType::BatteryPercent
+        | SystemSensorType::Humidity
+        | SystemSensorType::Power
+        | SystemSensorType::NetworkRx
+        | SystemSensorType::NetworkTx
+        | SystemSensorType::DiskRead
+        | SystemSensorType::DiskWrite
+        | SystemSensorType::DiskReadIops
+        | SystemSensorType::DiskWriteIops
+        | SystemSensorType::PressureStall => Some(1),
+        SystemSensorType::Temperature
+        | SystemSensorType::TemperatureLimit
+        | SystemSensorType::LoadAverage
+        | SystemSensorType::MemoryUsed
+        | SystemSensorType::MemoryTotal
+        | SystemSensorType::SwapUsed
+        | SystemSensorType::SwapTotal
+        | SystemSensorType::DiskUsed
+        | SystemSensorType::DiskTotal
+        | SystemSensorType::BatteryEnergy => Some(2),
+        SystemSensorType::Voltage => Some(3),
+        _ => None,
+    }
+}
+
+// Monotonic counters are graphed differently from point-in-time measurements; static
+// limits aren't measurements at all and get no statistics
+fn state_class(sensor_type: &SystemSensorType) -> Option<&'static str> {
+    match sensor_type {
+        SystemSensorType::Uptime
+        | SystemSensorType::Heartbeat
+        | SystemSensorType::DaemonMetric
+        | SystemSensorType::NetworkRxTotal
+        | SystemSensorType::NetworkTxTotal => Some("total_increasing"),
+        SystemSensorType::TemperatureLimit => None,
+        _ => Some("measurement"),
+    }
+}
+
+// Integration a sensor is discovered as; the domain only appears in the config topic,
+// state and availability topics stay under sensor/ for every sensor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiscoveryDomain {
+    Sensor,
+    BinarySensor, // Two-state sensors publishing 1.0 / 0.0
+}
+
+// Per-sensor choices that don't follow from the sensor itself
+#[derive(Debug, Clone, Copy)]
+pub struct DiscoveryOptions {
+    pub domain: DiscoveryDomain,
+    pub entity_category: Option<EntityCategory>, // Overrides default_entity_category
+    pub payload_format: PayloadFormat, // Selects the templates that decode the state payload
+    pub availability_mode: AvailabilityMode,
+}
+
+impl DiscoveryDomain {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiscoveryDomain::Sensor => "sensor",
+            DiscoveryDomain::BinarySensor => "binary_sensor",
+        }
     }
 }
 
 fn topic(data: Topic) -> String {
     format!(
-        "homeassistant/sensor/orbiq_{}/{}/{}",
-        data.device_name, data.sensor_name, data.sub_topic
+        "{}/sensor/orbiq_{}/{}/{}",
+        data.discovery_prefix, data.device_name, data.sensor_name, data.sub_topic
     )
 }
 
-pub fn system_state(sensor: &SystemSensor, device_name: &str) -> MqttPayload {
+fn sensor_topic(discovery_prefix: &str, device_name: &str, sensor_name: &str, sub_topic: &str) -> String {
+    topic(Topic {
+        discovery_prefix: discovery_prefix.to_string(),
+        device_name: device_name.to_string(),
+        sensor_name: sensor_name.to_string(),
+        sub_topic: sub_topic.to_string(),
+    })
+}
+
+pub fn system_state(
+    sensor: &SystemSensor,
+    history: Option<HistoryStats>,
+    format: PayloadFormat,
+    discovery_prefix: &str,
+    device_name: &str,
+    qos: QoS,
+) -> MqttPayload {
     let topic_data = Topic {
+        discovery_prefix: discovery_prefix.to_string(),
         device_name: device_name.parse().unwrap(),
         sensor_name: sensor.name.clone(),
         sub_topic: "state".to_string(),
     };
 
-    let payload = json!({
-        "value": sensor.value
-    });
+    let mut payload = match &sensor.extra {
+        Some(text) => json!({ "value": text }),
+        None => json!({ "value": sensor.value }),
+    };
+    if let Some(history) = history {
+        history.add_to(&mut payload);
+    }
+    if !sensor.attributes.is_empty() {
+        payload["attributes"] = json!(sensor.attributes);
+    }
+    let payload_bytes = match format {
+        PayloadFormat::Json => None,
+        PayloadFormat::Msgpack => match rmp_serde::to_vec_named(&payload) {
+            Ok(bytes) => Some(bytes),
+            Err(e) => {
+                tracing::warn!(sensor = %sensor.name, error = %e, "Failed to encode MessagePack state, sending JSON");
+                None
+            }
+        },
+    };
     MqttPayload {
         topic: topic(topic_data),
         payload: payload.to_string(),
+        // Static sensors are only published once, so late subscribers need the retained copy
+        retain: sensor.sensor_type.is_static(),
+        qos,
+        payload_bytes,
+    }
+}
+
+// All values of a cycle as one flat object, e.g. {"cpu_usage": 23.1, "kernel_version": "6.8.0"}
+pub fn build_aggregate_payload(sensors: &[SystemSensor], topic: &str, qos: QoS) -> MqttPayload {
+    let values: serde_json::Map<String, serde_json::Value> = sensors
+        .iter()
+        .map(|sensor| {
+            let value = match &sensor.extra {
+                Some(text) => json!(text),
+                None => json!(sensor.value),
+            };
+            (sensor.name.clone(), value)
+        })
+        .collect();
+    MqttPayload {
+        topic: topic.to_string(),
+        payload: serde_json::Value::Object(values).to_string(),
         retain: false,
+        qos,
+        payload_bytes: None,
     }
 }
 
 pub fn system_sensor_availability(
     sensor: &SystemSensor,
+    discovery_prefix: &str,
     device_name: &str,
     available: bool,
+    qos: QoS,
 ) -> MqttPayload {
     let topic_data = Topic {
+        discovery_prefix: discovery_prefix.to_string(),
         device_name: device_name.parse().unwrap(),
         sensor_name: sensor.name.clone(),
         sub_topic: "availability".to_string(),
@@ -142,38 +559,135 @@ pub fn system_sensor_availability(
         topic: topic(topic_data),
         payload: payload.parse().unwrap(),
         retain: true,
+        qos,
+        payload_bytes: None,
+    }
+}
+
+// Device-level availability, set to "offline" by the broker through the last will
+pub fn device_availability_topic(discovery_prefix: &str, device_name: &str) -> String {
+    format!("{}/sensor/orbiq_{}/availability", discovery_prefix, device_name)
+}
+
+// Device triggers of a sensor publish their action here, e.g. {"action": "temperature_high", ...}
+pub fn device_trigger_topic(discovery_prefix: &str, device_name: &str, sensor_name: &str) -> String {
+    format!("{}/device_automation/orbiq_{}/{}/action", discovery_prefix, device_name, sensor_name)
+}
+
+// Registers a trigger under the device's automations that fires when `action` is published
+// for the sensor; the action doubles as the trigger type and the sensor name as its subtype
+pub fn device_trigger_discovery_config(
+    sensor_name: &str,
+    action: &str,
+    discovery_prefix: &str,
+    device_info: &DeviceInfo,
+    qos: QoS,
+) -> MqttPayload {
+    let config = json!({
+        "automation_type": "trigger",
+        "topic": device_trigger_topic(discovery_prefix, &device_info.name, sensor_name),
+        "type": action,
+        "subtype": sensor_name,
+        "payload": action,
+        "value_template": "{{ value_json.action }}",
+        "device": device_info,
+    });
+    MqttPayload {
+        topic: format!(
+            "{}/device_automation/orbiq_{}/{}_{}/config",
+            discovery_prefix, device_info.name, sensor_name, action
+        ),
+        payload: config.to_string(),
+        retain: true,
+        qos,
+        payload_bytes: None,
+    }
+}
+
+pub fn device_availability(discovery_prefix: &str, device_name: &str, available: bool, qos: QoS) -> MqttPayload {
+    let payload = if available { "online" } else { "offline" };
+    MqttPayload {
+        topic: device_availability_topic(discovery_prefix, device_name),
+        payload: payload.to_string(),
+        retain: true,
+        qos,
+        payload_bytes: None,
     }
 }
 
 pub fn system_discovery_config(
     sensor: &SystemSensor,
+    options: DiscoveryOptions,
+    discovery_prefix: &str,
     device_name: &str,
     device_info: &DeviceInfo,
+    use_device_availability: bool,
+    qos: QoS,
 ) -> MqttPayload {
+    let domain = options.domain;
     let unique_id = format!("orbiq_{}_{}", device_name, sensor.name);
-    let object_id = format!("orbiq_{}_{}", device_name, sensor.name);
-    let config_topic = format!(
-        "homeassistant/sensor/orbiq_{}/{}/config",
-        device_name, sensor.name
-    );
-    let state_topic = format!(
-        "homeassistant/sensor/orbiq_{}/{}/state",
-        device_name, sensor.name
-    );
-    let availability_topic = format!(
-        "homeassistant/sensor/orbiq_{}/{}/availability",
-        device_name, sensor.name
-    );
+    let object_id = generate_object_id(device_name, &sensor.name);
+    let config_topic = match domain {
+        DiscoveryDomain::Sensor => sensor_topic(discovery_prefix, device_name, &sensor.name, "config"),
+        DiscoveryDomain::BinarySensor => format!(
+            "{}/{}/orbiq_{}/{}/config",
+            discovery_prefix,
+            domain.as_str(),
+            device_name,
+            sensor.name
+        ),
+    };
+    let state_topic = sensor_topic(discovery_prefix, device_name, &sensor.name, "state");
+    let availability_topic = sensor_topic(discovery_prefix, device_name, &sensor.name, "availability");
     let device_class = match &sensor.sensor_type {
         SystemSensorType::CpuUsage
+        | SystemSensorType::CpuCoreUsage
+        | SystemSensorType::LoadAverage
         | SystemSensorType::MemoryUsage
+        | SystemSensorType::SwapUsage
         | SystemSensorType::DiskUsage => None,
         SystemSensorType::MemoryUsed
         | SystemSensorType::MemoryTotal
+        | SystemSensorType::SwapUsed
+        | SystemSensorType::SwapTotal
         | SystemSensorType::DiskUsed
         | SystemSensorType::DiskTotal => Some("data_size"),
-        SystemSensorType::Temperature => Some("temperature"),
-        SystemSensorType::Fan => None,
+        SystemSensorType::Temperature | SystemSensorType::TemperatureLimit => Some("temperature"),
+        SystemSensorType::Fan | SystemSensorType::FanPercent | SystemSensorType::FanPwm => None,
+        SystemSensorType::FileDescriptors => None,
+        SystemSensorType::Entropy => None,
+        SystemSensorType::EntropyAlert => Some("problem"),
+        SystemSensorType::ProcessCount | SystemSensorType::ThreadCount => None,
+        SystemSensorType::PressureStall => None,
+        SystemSensorType::WatchedProcess => None,
+        SystemSensorType::SmartAttribute => None,
+        // signal_strength would fit the dBm sensors but not the quality percentage
+        SystemSensorType::WifiSignal => None,
+        SystemSensorType::DiskInodes => None,
+        SystemSensorType::Heartbeat => None,
+        SystemSensorType::PingLatency => Some("duration"),
+        SystemSensorType::DaemonMetric => None,
+        SystemSensorType::Generic => None,
+        SystemSensorType::SystemInfo => None,
+        SystemSensorType::Humidity => Some("humidity"),
+        SystemSensorType::ConfigInfo => None,
+        SystemSensorType::Uptime => Some("duration"),
+        SystemSensorType::Voltage => Some("voltage"),
+        SystemSensorType::Power => Some("power"),
+        // "KB/s" is not one of the units HA accepts for data_rate
+        SystemSensorType::NetworkRx
+        | SystemSensorType::NetworkTx
+        | SystemSensorType::DiskRead
+        | SystemSensorType::DiskWrite
+        | SystemSensorType::DiskReadIops
+        | SystemSensorType::DiskWriteIops
+        | SystemSensorType::DiskIoWait => None,
+        SystemSensorType::CpuFrequency => None,
+        SystemSensorType::BatteryPercent => Some("battery"),
+        SystemSensorType::BatteryState | SystemSensorType::PowerSupplyOnline => None,
+        SystemSensorType::BatteryEnergy => Some("energy_storage"),
+        SystemSensorType::NetworkRxTotal | SystemSensorType::NetworkTxTotal => Some("data_size"),
+        SystemSensorType::NetworkLinkStatus => Some("connectivity"),
     };
 
     let friendly_name = generate_system_friendly_name(sensor);
@@ -183,24 +697,307 @@ pub fn system_discovery_config(
         "unique_id": unique_id,
         "object_id": object_id,
         "state_topic": state_topic,
-        "unit_of_measurement": sensor.unit,
-        "state_class": "measurement",
         "value_template": "{{ value_json.value }}",
-        "availability": {
-            "topic": availability_topic,
-            "payload_available": "online",
-            "payload_not_available": "offline"
-        },
+        "json_attributes_topic": state_topic,
+        "json_attributes_template": "{{ value_json.attributes | default({}) | tojson }}",
         "icon": sensor.sensor_type.icon(),
         "device": device_info
     });
+    // Home Assistant has no built-in msgpack_decode filter, it has to be provided by a custom
+    // integration or template extension
+    if options.payload_format == PayloadFormat::Msgpack {
+        config["value_template"] = json!("{{ (value | msgpack_decode).value }}");
+        config["json_attributes_template"] = json!("{{ (value | msgpack_decode).attributes | default({}) | tojson }}");
+    }
 
+    // Text sensors have neither a unit nor a state class, binary sensors compare payloads instead
+    if domain == DiscoveryDomain::BinarySensor {
+        config["payload_on"] = json!("1.0");
+        config["payload_off"] = json!("0.0");
+    } else if sensor.extra.is_none() {
+        config["unit_of_measurement"] = json!(sensor.unit);
+        if let Some(state_class) = state_class(&sensor.sensor_type) {
+            config["state_class"] = json!(state_class);
+        }
+        if let Some(precision) = suggested_precision(&sensor.sensor_type) {
+            config["suggested_display_precision"] = json!(precision);
+        }
+    }
+    let has_availability = sensor.sensor_type.has_availability();
+    if has_availability && options.availability_mode == AvailabilityMode::Device {
+        // One shared topic, the sensors have none of their own
+        config["availability_topic"] = json!(device_availability_topic(discovery_prefix, device_name));
+        config["payload_available"] = json!("online");
+        config["payload_not_available"] = json!("offline");
+    } else if has_availability && use_device_availability {
+        // Unavailable as soon as either the sensor or the whole daemon goes offline
+        config["availability"] = json!([
+            { "topic": availability_topic },
+            { "topic": device_availability_topic(discovery_prefix, device_name) }
+        ]);
+        config["availability_mode"] = json!("all");
+        config["payload_available"] = json!("online");
+        config["payload_not_available"] = json!("offline");
+    } else if has_availability {
+        config["availability"] = json!({
+            "topic": availability_topic,
+            "payload_available": "online",
+            "payload_not_available": "offline"
+        });
+    }
     if let Some(class) = device_class {
         config["device_class"] = json!(class);
     }
+    if let Some(category) = options.entity_category.or(default_entity_category(&sensor.sensor_type)) {
+        config["entity_category"] = json!(category);
+    }
     MqttPayload {
         topic: config_topic,
         payload: config.to_string(),
         retain: true,
+        qos,
+        payload_bytes: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_generate_object_id_slugifies() {
+        assert_eq!(generate_object_id("server-01", "cpu_usage"), "orbiq_server_01_cpu_usage");
+        assert_eq!(generate_object_id("My Host", "Disk--Usage__root"), "orbiq_my_host_disk_usage_root");
+        assert_eq!(generate_object_id("host", "temp_"), "orbiq_host_temp");
+    }
+
+    #[test]
+    fn test_generate_object_id_limits_length() {
+        let long_a = format!("disk_usage_{}_a", "x".repeat(80));
+        let long_b = format!("disk_usage_{}_b", "x".repeat(80));
+        let id_a = generate_object_id("host", &long_a);
+        let id_b = generate_object_id("host", &long_b);
+
+        assert!(id_a.len() <= MAX_OBJECT_ID_LEN);
+        assert!(id_a.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_'));
+        assert_ne!(id_a, id_b);
+        assert_eq!(id_a, generate_object_id("host", &long_a));
+    }
+
+    #[test]
+    fn test_topic_uses_discovery_prefix() {
+        assert_eq!(
+            sensor_topic("homeassistant", "server", "cpu_usage", "state"),
+            "homeassistant/sensor/orbiq_server/cpu_usage/state"
+        );
+        assert_eq!(
+            sensor_topic("openhab", "server", "cpu_usage", "config"),
+            "openhab/sensor/orbiq_server/cpu_usage/config"
+        );
+        assert_eq!(
+            device_availability_topic("nodered/discovery", "server"),
+            "nodered/discovery/sensor/orbiq_server/availability"
+        );
+    }
+
+    #[test]
+    fn test_network_interface_name() {
+        assert_eq!(network_interface_name("net_eth0_rx_kbps"), "eth0");
+        assert_eq!(network_interface_name("net_br_lan_tx_bytes"), "br_lan");
+        assert_eq!(network_interface_name("net_eth0_link_status"), "eth0");
+    }
+
+    #[test]
+    fn test_binary_sensor_discovery() {
+        let sensor = SystemSensor {
+            name: "net_eth0_link_status".to_string(),
+            label: None,
+            value: 1.0,
+            unit: "".to_string(),
+            sensor_type: SystemSensorType::NetworkLinkStatus,
+            attributes: HashMap::new(),
+            extra: None,
+        };
+        let device_info = DeviceInfo::from_config(&crate::config::DeviceConfig::default());
+        let options = DiscoveryOptions {
+            domain: DiscoveryDomain::BinarySensor,
+            entity_category: None,
+            payload_format: PayloadFormat::Json,
+            availability_mode: AvailabilityMode::PerSensor,
+        };
+        let discovery = system_discovery_config(
+            &sensor,
+            options,
+            "homeassistant",
+            "server",
+            &device_info,
+            false,
+            QoS::AtLeastOnce,
+        );
+
+        assert_eq!(discovery.topic, "homeassistant/binary_sensor/orbiq_server/net_eth0_link_status/config");
+        let config: serde_json::Value = serde_json::from_str(&discovery.payload).unwrap();
+        assert_eq!(config["payload_on"], "1.0");
+        assert_eq!(config["payload_off"], "0.0");
+        assert_eq!(config["device_class"], "connectivity");
+        assert_eq!(config["state_topic"], "homeassistant/sensor/orbiq_server/net_eth0_link_status/state");
+        assert!(config.get("unit_of_measurement").is_none());
+        assert!(config.get("state_class").is_none());
+        assert!(config.get("entity_category").is_none());
+        assert!(config.get("suggested_display_precision").is_none());
+    }
+
+    #[test]
+    fn test_msgpack_state_and_discovery() {
+        let sensor = SystemSensor {
+            name: "cpu_usage".to_string(),
+            label: None,
+            value: 12.5,
+            unit: "%".to_string(),
+            sensor_type: SystemSensorType::CpuUsage,
+            attributes: HashMap::new(),
+            extra: None,
+        };
+        let state = system_state(&sensor, None, PayloadFormat::Msgpack, "homeassistant", "server", QoS::AtLeastOnce);
+        let decoded: serde_json::Value = rmp_serde::from_slice(state.payload_bytes.as_deref().unwrap()).unwrap();
+        assert_eq!(decoded, json!({ "value": 12.5 }));
+        // The JSON copy is still used for change detection
+        assert_eq!(state.payload, r#"{"value":12.5}"#);
+        assert!(system_state(&sensor, None, PayloadFormat::Json, "homeassistant", "server", QoS::AtLeastOnce)
+            .payload_bytes
+            .is_none());
+
+        let device_info = DeviceInfo::from_config(&crate::config::DeviceConfig::default());
+        let options = DiscoveryOptions {
+            domain: DiscoveryDomain::Sensor,
+            entity_category: None,
+            payload_format: PayloadFormat::Msgpack,
+            availability_mode: AvailabilityMode::PerSensor,
+        };
+        let discovery =
+            system_discovery_config(&sensor, options, "homeassistant", "server", &device_info, false, QoS::AtLeastOnce);
+        let config: serde_json::Value = serde_json::from_str(&discovery.payload).unwrap();
+        assert_eq!(config["value_template"], "{{ (value | msgpack_decode).value }}");
+        assert!(discovery.payload_bytes.is_none());
+    }
+
+    #[test]
+    fn test_build_aggregate_payload() {
+        let sensor = |name: &str, value: f64, extra: Option<&str>| SystemSensor {
+            name: name.to_string(),
+            label: None,
+            value,
+            unit: "".to_string(),
+            sensor_type: SystemSensorType::CpuUsage,
+            attributes: HashMap::new(),
+            extra: extra.map(str::to_string),
+        };
+        let sensors = [
+            sensor("cpu_usage", 23.1, None),
+            sensor("memory_usage", 67.4, None),
+            sensor("kernel_version", 0.0, Some("6.8.0")),
+        ];
+
+        let aggregate = build_aggregate_payload(&sensors, "orbiq/server/state", QoS::AtMostOnce);
+        assert_eq!(aggregate.topic, "orbiq/server/state");
+        assert_eq!(aggregate.qos, QoS::AtMostOnce);
+        assert!(!aggregate.retain);
+        let payload: serde_json::Value = serde_json::from_str(&aggregate.payload).unwrap();
+        assert_eq!(payload, json!({ "cpu_usage": 23.1, "memory_usage": 67.4, "kernel_version": "6.8.0" }));
+    }
+
+    #[test]
+    fn test_device_availability_mode() {
+        let sensor = SystemSensor {
+            name: "cpu_usage".to_string(),
+            label: None,
+            value: 12.5,
+            unit: "%".to_string(),
+            sensor_type: SystemSensorType::CpuUsage,
+            attributes: HashMap::new(),
+            extra: None,
+        };
+        let device_info = DeviceInfo::from_config(&crate::config::DeviceConfig::default());
+        let options = DiscoveryOptions {
+            domain: DiscoveryDomain::Sensor,
+            entity_category: None,
+            payload_format: PayloadFormat::Json,
+            availability_mode: AvailabilityMode::Device,
+        };
+        // Device mode wins over use_device_availability
+        let discovery =
+            system_discovery_config(&sensor, options, "homeassistant", "server", &device_info, true, QoS::AtLeastOnce);
+        let config: serde_json::Value = serde_json::from_str(&discovery.payload).unwrap();
+        assert_eq!(config["availability_topic"], "homeassistant/sensor/orbiq_server/availability");
+        assert_eq!(config["payload_available"], "online");
+        assert!(config.get("availability").is_none());
+        assert!(config.get("availability_mode").is_none());
+    }
+
+    #[test]
+    fn test_generate_friendly_name_prefers_label() {
+        assert_eq!(
+            generate_friendly_name("coretemp_5", Some("Intel Core 3 Temperature")),
+            "Intel Core 3 Temperature"
+        );
+        assert_eq!(generate_friendly_name("coretemp_5", None), "Core 5 Temperature");
+        assert_eq!(generate_friendly_name("k10temp_1", None), "CPU Temperature");
+    }
+
+    #[test]
+    fn test_label_overrides_every_sensor_type() {
+        for sensor_type in [SystemSensorType::CpuUsage, SystemSensorType::Fan, SystemSensorType::DiskUsage] {
+            let sensor = SystemSensor {
+                name: "disk_usage_root".to_string(),
+                label: Some("Boot SSD".to_string()),
+                value: 1.0,
+                unit: "%".to_string(),
+                sensor_type,
+                attributes: HashMap::new(),
+                extra: None,
+            };
+            assert_eq!(generate_system_friendly_name(&sensor), "Boot SSD");
+        }
+    }
+
+    #[test]
+    fn test_suggested_precision() {
+        assert_eq!(suggested_precision(&SystemSensorType::Temperature), Some(2));
+        assert_eq!(suggested_precision(&SystemSensorType::CpuUsage), Some(1));
+        assert_eq!(suggested_precision(&SystemSensorType::Fan), Some(0));
+        assert_eq!(suggested_precision(&SystemSensorType::MemoryUsed), Some(2));
+        assert_eq!(suggested_precision(&SystemSensorType::Voltage), Some(3));
+        assert_eq!(suggested_precision(&SystemSensorType::SystemInfo), None);
+    }
+
+    #[test]
+    fn test_entity_category() {
+        let mut sensor = SystemSensor {
+            name: "memory_total".to_string(),
+            label: None,
+            value: 31.2,
+            unit: "GB".to_string(),
+            sensor_type: SystemSensorType::MemoryTotal,
+            attributes: HashMap::new(),
+            extra: None,
+        };
+        let device_info = DeviceInfo::from_config(&crate::config::DeviceConfig::default());
+        let category = |sensor: &SystemSensor, entity_category| {
+            let options = DiscoveryOptions {
+                domain: DiscoveryDomain::Sensor,
+                entity_category,
+                payload_format: PayloadFormat::Json,
+                availability_mode: AvailabilityMode::PerSensor,
+            };
+            let discovery =
+                system_discovery_config(sensor, options, "homeassistant", "server", &device_info, false, QoS::AtLeastOnce);
+            serde_json::from_str::<serde_json::Value>(&discovery.payload).unwrap()["entity_category"].clone()
+        };
+
+        assert_eq!(category(&sensor, None), "diagnostic");
+        sensor.sensor_type = SystemSensorType::MemoryUsage;
+        assert!(category(&sensor, None).is_null());
+        assert_eq!(category(&sensor, Some(EntityCategory::Diagnostic)), "diagnostic");
     }
 }
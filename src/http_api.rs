@@ -0,0 +1,128 @@
+use crate::sensors::{SharedSensors, SystemSensor};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde_json::json;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+use tokio::net::TcpListener;
+
+#[derive(Clone)]
+pub struct ApiState {
+    pub sensors: SharedSensors,
+    pub mqtt_connected: Arc<AtomicBool>, // Set by the MQTT event loop
+    pub started_at: Instant,
+}
+
+fn current_sensors(state: &ApiState) -> Vec<SystemSensor> {
+    match state.sensors.read() {
+        Ok(sensors) => sensors.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+}
+
+async fn list_sensors(State(state): State<ApiState>) -> Json<Vec<SystemSensor>> {
+    Json(current_sensors(&state))
+}
+
+async fn get_sensor(State(state): State<ApiState>, Path(name): Path<String>) -> Response {
+    match current_sensors(&state).into_iter().find(|sensor| sensor.name == name) {
+        Some(sensor) => Json(sensor).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": format!("unknown sensor {}", name) })),
+        )
+            .into_response(),
+    }
+}
+
+async fn health(State(state): State<ApiState>) -> Json<serde_json::Value> {
+    Json(json!({
+        "status": "ok",
+        "uptime_secs": state.started_at.elapsed().as_secs(),
+        "mqtt_connected": state.mqtt_connected.load(Ordering::Relaxed),
+    }))
+}
+
+pub fn router(state: ApiState) -> Router {
+    Router::new()
+        .route("/sensors", get(list_sensors))
+        .route("/sensors/{name}", get(get_sensor))
+        .route("/health", get(health))
+        .with_state(state)
+}
+
+// The listener is bound by the caller so a port conflict is reported at startup
+pub async fn serve_api(listener: TcpListener, state: ApiState) {
+    if let Err(e) = axum::serve(listener, router(state)).await {
+        tracing::error!(error = %e, "HTTP API server stopped");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sensors::SystemSensorType;
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use serde_json::Value;
+    use std::collections::HashMap;
+    use std::sync::RwLock;
+    use tower::ServiceExt;
+
+    fn state() -> ApiState {
+        let sensor = SystemSensor {
+            name: "cpu_usage".to_string(),
+            label: None,
+            value: 23.4,
+            unit: "%".to_string(),
+            sensor_type: SystemSensorType::CpuUsage,
+            attributes: HashMap::new(),
+            extra: None,
+        };
+        ApiState {
+            sensors: Arc::new(RwLock::new(vec![sensor])),
+            mqtt_connected: Arc::new(AtomicBool::new(true)),
+            started_at: Instant::now(),
+        }
+    }
+
+    async fn get_json(uri: &str) -> (StatusCode, Value) {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let response = router(state()).oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_list_sensors() {
+        let (status, body) = get_json("/sensors").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body[0]["name"], "cpu_usage");
+        assert_eq!(body[0]["value"], 23.4);
+        assert_eq!(body[0]["unit"], "%");
+    }
+
+    #[tokio::test]
+    async fn test_get_sensor() {
+        let (status, body) = get_json("/sensors/cpu_usage").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["name"], "cpu_usage");
+
+        let (status, _) = get_json("/sensors/unknown").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_health() {
+        let (status, body) = get_json("/health").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["mqtt_connected"], true);
+        assert!(body["uptime_secs"].is_u64());
+    }
+}
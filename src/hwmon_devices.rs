@@ -1,26 +1,71 @@
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
 const HWMON_BASE_PATH: &str = "/sys/class/hwmon";
+const NVME_BASE_PATH: &str = "/sys/class/nvme";
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct HwmonDevice {
     pub path: PathBuf,
     pub name: String,
 }
-pub fn discover_hwmon_devices() -> Result<Vec<HwmonDevice>, std::io::Error> {
+
+// The device list rarely changes, so it is shared by all hwmon based collectors
+#[derive(Debug, Default)]
+struct HwmonCache {
+    devices: Vec<HwmonDevice>,
+    last_refreshed: Option<Instant>,
+}
+
+static HWMON_CACHE: OnceLock<Mutex<HwmonCache>> = OnceLock::new();
+
+pub fn discover_hwmon_devices(cache_ttl: Duration) -> Result<Vec<HwmonDevice>, std::io::Error> {
+    let cache = HWMON_CACHE.get_or_init(|| Mutex::new(HwmonCache::default()));
+    let mut cache = cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    cached_scan(&mut cache, cache_ttl, Instant::now(), scan_hwmon_devices)
+}
+
+fn cached_scan<F>(
+    cache: &mut HwmonCache,
+    cache_ttl: Duration,
+    now: Instant,
+    scan: F,
+) -> Result<Vec<HwmonDevice>, std::io::Error>
+where
+    F: FnOnce() -> Result<Vec<HwmonDevice>, std::io::Error>,
+{
+    let is_fresh = cache
+        .last_refreshed
+        .is_some_and(|refreshed| now.duration_since(refreshed) < cache_ttl);
+
+    if !is_fresh {
+        cache.devices = scan()?;
+        cache.last_refreshed = Some(now);
+    }
+
+    Ok(cache.devices.clone())
+}
+
+fn scan_hwmon_devices() -> Result<Vec<HwmonDevice>, std::io::Error> {
+    let devices = scan_hwmon_devices_in(Path::new(HWMON_BASE_PATH))?;
+    Ok(merge_nvme_devices(devices, scan_nvme_hwmon_devices(Path::new(NVME_BASE_PATH))))
+}
+
+fn scan_hwmon_devices_in(hwmon_base: &Path) -> Result<Vec<HwmonDevice>, std::io::Error> {
     let mut devices = Vec::new();
 
-    for entry in fs::read_dir(HWMON_BASE_PATH)? {
+    for entry in fs::read_dir(hwmon_base)? {
         let entry = entry?;
         let hwmon_path = entry.path();
 
         if let Some(hwmon_name) = hwmon_path.file_name() {
             let hwmon_name = hwmon_name.to_string_lossy().to_string();
-            let device_name = read_device_name(&hwmon_path).unwrap_or_else(|| hwmon_name);
+            let device_name = read_device_name(&hwmon_path).unwrap_or(hwmon_name);
 
             devices.push(HwmonDevice {
-                path: hwmon_path,
+                path: resolve_path(hwmon_path),
                 name: device_name,
             });
         }
@@ -29,9 +74,184 @@ pub fn discover_hwmon_devices() -> Result<Vec<HwmonDevice>, std::io::Error> {
     Ok(devices)
 }
 
+// Every NVMe hwmon device is called "nvme", so controllers are scanned separately to
+// tell the drives apart; missing /sys/class/nvme just means there are no NVMe drives
+fn scan_nvme_hwmon_devices(nvme_base: &Path) -> Vec<HwmonDevice> {
+    let mut devices = Vec::new();
+    let Ok(controllers) = fs::read_dir(nvme_base) else {
+        return devices;
+    };
+
+    for controller in controllers.flatten() {
+        let controller_path = controller.path();
+        let controller_name = controller.file_name().to_string_lossy().to_string();
+        for hwmon_path in nvme_hwmon_dirs(&controller_path) {
+            devices.push(HwmonDevice {
+                path: resolve_path(hwmon_path),
+                name: nvme_device_name(&controller_name, read_model(&controller_path).as_deref()),
+            });
+        }
+    }
+
+    devices
+}
+
+// Newer kernels register the hwmon device below the controller (nvme0/hwmon2), older ones
+// below the PCI device (nvme0/device/hwmon/hwmon2)
+fn nvme_hwmon_dirs(controller_path: &Path) -> Vec<PathBuf> {
+    [controller_path.to_path_buf(), controller_path.join("device").join("hwmon")]
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter(|entry| is_hwmon_dir_name(&entry.file_name().to_string_lossy()))
+        .map(|entry| entry.path())
+        .collect()
+}
+
+// "hwmon2", but not the "hwmon" class directory itself
+fn is_hwmon_dir_name(name: &str) -> bool {
+    name.strip_prefix("hwmon")
+        .is_some_and(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+}
+
+// The same hwmon directory is reachable from /sys/class/hwmon and from the NVMe controller;
+// the controller based name replaces the generic one instead of reporting the drive twice
+fn merge_nvme_devices(mut devices: Vec<HwmonDevice>, nvme_devices: Vec<HwmonDevice>) -> Vec<HwmonDevice> {
+    for nvme_device in nvme_devices {
+        match devices.iter_mut().find(|device| device.path == nvme_device.path) {
+            Some(device) => device.name = nvme_device.name,
+            None => devices.push(nvme_device),
+        }
+    }
+    devices
+}
+
+fn resolve_path(path: PathBuf) -> PathBuf {
+    fs::canonicalize(&path).unwrap_or(path)
+}
+
+fn read_model(controller_path: &Path) -> Option<String> {
+    fs::read_to_string(controller_path.join("model")).ok()
+}
+
+// ("nvme0", "Samsung SSD 980 PRO 1TB   ") -> "nvme0_samsung_ssd_980_pro_1tb"
+fn nvme_device_name(controller: &str, model: Option<&str>) -> String {
+    let model = model
+        .unwrap_or_default()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("_");
+    if model.is_empty() {
+        controller.to_string()
+    } else {
+        format!("{}_{}", controller, model)
+    }
+}
+
 fn read_device_name(hwmon_path: &Path) -> Option<String> {
     let name_file = hwmon_path.join("name");
     fs::read_to_string(&name_file)
         .ok()
         .map(|content| content.trim().to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_cache_is_used_within_ttl() {
+        let mut cache = HwmonCache::default();
+        let ttl = Duration::from_secs(60);
+        let start = Instant::now();
+        let scans = Cell::new(0);
+        let scan = || {
+            scans.set(scans.get() + 1);
+            Ok(vec![HwmonDevice {
+                path: PathBuf::from("/sys/class/hwmon/hwmon0"),
+                name: "k10temp".to_string(),
+            }])
+        };
+
+        let first = cached_scan(&mut cache, ttl, start, scan).unwrap();
+        let second = cached_scan(&mut cache, ttl, start + Duration::from_secs(30), scan).unwrap();
+        assert_eq!(scans.get(), 1);
+        assert_eq!(first.len(), 1);
+        assert_eq!(second[0].name, "k10temp");
+
+        cached_scan(&mut cache, ttl, start + Duration::from_secs(61), scan).unwrap();
+        assert_eq!(scans.get(), 2);
+    }
+
+    #[test]
+    fn test_nvme_device_name() {
+        assert_eq!(nvme_device_name("nvme0", Some("Samsung SSD 980 PRO 1TB      \n")), "nvme0_samsung_ssd_980_pro_1tb");
+        assert_eq!(nvme_device_name("nvme1", Some("  ")), "nvme1");
+        assert_eq!(nvme_device_name("nvme1", None), "nvme1");
+    }
+
+    #[test]
+    fn test_merge_nvme_devices_renames_overlap() {
+        let device = |path: &str, name: &str| HwmonDevice {
+            path: PathBuf::from(path),
+            name: name.to_string(),
+        };
+        let devices = vec![
+            device("/sys/devices/platform/coretemp.0/hwmon/hwmon1", "coretemp"),
+            device("/sys/devices/pci0000:00/nvme/nvme0/hwmon2", "nvme"),
+        ];
+        let nvme = vec![
+            device("/sys/devices/pci0000:00/nvme/nvme0/hwmon2", "nvme0_samsung"),
+            device("/sys/devices/pci0000:00/nvme/nvme1/hwmon3", "nvme1_wd"),
+        ];
+
+        let merged = merge_nvme_devices(devices, nvme);
+        let names: Vec<&str> = merged.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["coretemp", "nvme0_samsung", "nvme1_wd"]);
+    }
+
+    // Mirrors sysfs: class directories hold symlinks into /sys/devices, nvme0 has its hwmon
+    // device below the PCI device (older kernels), nvme1 below the controller (newer kernels)
+    #[test]
+    fn test_scan_nvme_hwmon_devices_sysfs_layout() {
+        use std::os::unix::fs::symlink;
+
+        let root = std::env::temp_dir().join(format!("orbiq_hwmon_{}", std::process::id()));
+        let pci0 = root.join("devices/pci0000:00/0000:01:00.0");
+        let pci1 = root.join("devices/pci0000:00/0000:02:00.0");
+        let hwmon_class = root.join("class/hwmon");
+        let nvme_class = root.join("class/nvme");
+        for dir in [
+            pci0.join("hwmon/hwmon2"),
+            pci0.join("nvme/nvme0"),
+            pci1.join("nvme/nvme1/hwmon3"),
+            root.join("devices/platform/coretemp.0/hwmon/hwmon1"),
+            hwmon_class.clone(),
+            nvme_class.clone(),
+        ] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(pci0.join("hwmon/hwmon2/name"), "nvme\n").unwrap();
+        fs::write(pci1.join("nvme/nvme1/hwmon3/name"), "nvme\n").unwrap();
+        fs::write(root.join("devices/platform/coretemp.0/hwmon/hwmon1/name"), "coretemp\n").unwrap();
+        fs::write(pci0.join("nvme/nvme0/model"), "Samsung SSD 980 PRO 1TB\n").unwrap();
+        fs::write(pci1.join("nvme/nvme1/model"), "WD Blue SN570\n").unwrap();
+        symlink("../../../0000:01:00.0", pci0.join("nvme/nvme0/device")).unwrap();
+        symlink("../../../0000:02:00.0", pci1.join("nvme/nvme1/device")).unwrap();
+        symlink(root.join("devices/platform/coretemp.0/hwmon/hwmon1"), hwmon_class.join("hwmon1")).unwrap();
+        symlink(pci0.join("hwmon/hwmon2"), hwmon_class.join("hwmon2")).unwrap();
+        symlink(pci1.join("nvme/nvme1/hwmon3"), hwmon_class.join("hwmon3")).unwrap();
+        symlink(pci0.join("nvme/nvme0"), nvme_class.join("nvme0")).unwrap();
+        symlink(pci1.join("nvme/nvme1"), nvme_class.join("nvme1")).unwrap();
+
+        let devices = scan_hwmon_devices_in(&hwmon_class).unwrap();
+        let mut merged = merge_nvme_devices(devices, scan_nvme_hwmon_devices(&nvme_class));
+        merged.sort_by(|a, b| a.name.cmp(&b.name));
+        let names: Vec<&str> = merged.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["coretemp", "nvme0_samsung_ssd_980_pro_1tb", "nvme1_wd_blue_sn570"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
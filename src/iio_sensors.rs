@@ -0,0 +1,166 @@
+use crate::sensors::{CollectFuture, SensorCollector, SystemSensor};
+use crate::sensors::SystemSensorType::{Humidity, Temperature};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::sysfs::read_attribute;
+
+const IIO_BASE_PATH: &str = "/sys/bus/iio/devices";
+const IIO_DEVICE_PREFIX: &str = "iio:device";
+const TEMP_FILE_PREFIX: &str = "in_temp";
+const HUMIDITY_FILE: &str = "in_humidityrelative_input";
+const INPUT_SUFFIX: &str = "_input";
+const MILLI_TO_UNIT: f64 = 1000.0;
+
+#[derive(Debug)]
+struct IioDevice {
+    path: PathBuf,
+    name: String,
+}
+
+// Temperature and humidity of IIO devices, e.g. a BME280 on I2C
+pub struct IioCollector {
+    pub read_timeout: Duration,
+}
+
+impl SensorCollector for IioCollector {
+    fn collect(&self) -> CollectFuture<'_> {
+        Box::pin(collect_all_iio_sensors(self.read_timeout))
+    }
+
+    fn name(&self) -> &str {
+        "iio"
+    }
+}
+
+async fn collect_all_iio_sensors(read_timeout: Duration) -> Vec<SystemSensor> {
+    let mut sensors = Vec::new();
+
+    match discover_iio_devices() {
+        Ok(devices) => {
+            for device in devices {
+                sensors.extend(scan_device_sensors(&device, read_timeout).await);
+            }
+        }
+        Err(e) => {
+            // Most x86 machines have no IIO bus at all
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!(error = %e, "Failed to discover IIO devices");
+            }
+        }
+    }
+
+    sensors
+}
+
+fn discover_iio_devices() -> Result<Vec<IioDevice>, std::io::Error> {
+    let mut devices = Vec::new();
+
+    for entry in fs::read_dir(IIO_BASE_PATH)? {
+        let path = entry?.path();
+        let Some(dir_name) = path.file_name().map(|n| n.to_string_lossy().to_string()) else {
+            continue;
+        };
+        if !dir_name.starts_with(IIO_DEVICE_PREFIX) {
+            continue;
+        }
+
+        let name = fs::read_to_string(path.join("name"))
+            .map(|content| content.trim().to_string())
+            .unwrap_or_else(|_| dir_name.replace(':', "_"));
+
+        devices.push(IioDevice { path, name });
+    }
+
+    Ok(devices)
+}
+
+async fn scan_device_sensors(device: &IioDevice, read_timeout: Duration) -> Vec<SystemSensor> {
+    let mut sensors = Vec::new();
+
+    match fs::read_dir(&device.path) {
+        Ok(entries) => {
+            for entry in entries.flatten() {
+                if let Some(sensor) = process_iio_file(&entry.path(), device, read_timeout).await {
+                    sensors.push(sensor);
+                }
+            }
+        }
+        Err(e) => {
+            tracing::warn!(device = %device.path.display(), error = %e, "Failed to read IIO device directory");
+        }
+    }
+
+    sensors
+}
+
+async fn process_iio_file(file_path: &Path, device: &IioDevice, read_timeout: Duration) -> Option<SystemSensor> {
+    let filename = file_path.file_name()?.to_string_lossy();
+
+    let (sensor_type, unit) = if is_temperature_file(&filename) {
+        (Temperature, "°C")
+    } else if filename == HUMIDITY_FILE {
+        (Humidity, "%")
+    } else {
+        return None;
+    };
+
+    let value = read_milli_value(file_path, read_timeout).await?;
+    let channel = extract_channel_name(&filename)?;
+
+    Some(SystemSensor {
+        name: format!("iio_{}_{}", device.name, channel),
+        label: None,
+        value,
+        unit: unit.to_string(),
+        sensor_type,
+        attributes: HashMap::new(),
+        extra: None,
+    })
+}
+
+fn is_temperature_file(filename: &str) -> bool {
+    filename.starts_with(TEMP_FILE_PREFIX) && filename.ends_with(INPUT_SUFFIX)
+}
+
+// Temperatures are reported in millidegrees Celsius and humidity in milli-percent
+async fn read_milli_value(file_path: &Path, read_timeout: Duration) -> Option<f64> {
+    let raw = read_attribute(file_path, read_timeout).await?;
+    let milli = raw.trim().parse::<f64>().ok()?;
+    Some(milli / MILLI_TO_UNIT)
+}
+
+// "in_temp_ambient_input" -> "temp_ambient", "in_temp0_input" -> "temp0"
+fn extract_channel_name(filename: &str) -> Option<String> {
+    let channel = filename.strip_prefix("in_")?.strip_suffix(INPUT_SUFFIX)?;
+    if channel.is_empty() {
+        None
+    } else {
+        Some(channel.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_temperature_file() {
+        assert!(is_temperature_file("in_temp_input"));
+        assert!(is_temperature_file("in_temp0_input"));
+        assert!(is_temperature_file("in_temp_ambient_input"));
+        assert!(!is_temperature_file("in_temp_raw"));
+        assert!(!is_temperature_file("in_humidityrelative_input"));
+    }
+
+    #[test]
+    fn test_extract_channel_name() {
+        assert_eq!(extract_channel_name("in_temp_input"), Some("temp".to_string()));
+        assert_eq!(extract_channel_name("in_temp_ambient_input"), Some("temp_ambient".to_string()));
+        assert_eq!(
+            extract_channel_name("in_humidityrelative_input"),
+            Some("humidityrelative".to_string())
+        );
+    }
+}
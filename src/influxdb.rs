@@ -0,0 +1,132 @@
+use crate::config::{ConfigError, InfluxDbConfig};
+use crate::sensors::SystemSensor;
+use tokio::net::UdpSocket;
+
+// Commas, spaces and equals signs separate tags in the line protocol
+fn escape_tag(value: &str) -> String {
+    value.replace(',', "\\,").replace(' ', "\\ ").replace('=', "\\=")
+}
+
+// `orbiq_metrics,device=myhost,sensor=cpu_usage value=23.4`, text sensors are skipped
+pub fn line_protocol(sensor: &SystemSensor, device_name: &str) -> Option<String> {
+    if sensor.extra.is_some() {
+        return None;
+    }
+    Some(format!(
+        "orbiq_metrics,device={},sensor={} value={}",
+        escape_tag(device_name),
+        escape_tag(&sensor.name),
+        sensor.value
+    ))
+}
+
+pub enum InfluxDbWriter {
+    // One POST per cycle to the v2 write API
+    Http {
+        client: reqwest::Client,
+        endpoint: String,
+        token: Option<String>,
+    },
+    // One datagram per sensor
+    Udp { socket: UdpSocket, target: String },
+}
+
+impl InfluxDbWriter {
+    pub async fn from_config(config: &InfluxDbConfig) -> Result<Option<Self>, ConfigError> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        if let Some(url) = &config.url {
+            let (Some(org), Some(bucket)) = (&config.org, &config.bucket) else {
+                return Err(ConfigError::Invalid(
+                    "influxdb: org and bucket are required with url".to_string(),
+                ));
+            };
+            let endpoint = reqwest::Url::parse_with_params(
+                &format!("{}/api/v2/write", url.trim_end_matches('/')),
+                &[("org", org.as_str()), ("bucket", bucket.as_str())],
+            )
+            .map_err(|e| ConfigError::Invalid(format!("influxdb: invalid url {}: {}", url, e)))?;
+            return Ok(Some(InfluxDbWriter::Http {
+                client: reqwest::Client::new(),
+                endpoint: endpoint.to_string(),
+                token: config.token.clone(),
+            }));
+        }
+        if let Some(host) = &config.udp_host {
+            let socket = UdpSocket::bind(("0.0.0.0", 0))
+                .await
+                .map_err(|e| ConfigError::Invalid(format!("influxdb: failed to open UDP socket: {}", e)))?;
+            return Ok(Some(InfluxDbWriter::Udp {
+                socket,
+                target: format!("{}:{}", host, config.udp_port),
+            }));
+        }
+        Err(ConfigError::Invalid(
+            "influxdb: enabled without url or udp_host".to_string(),
+        ))
+    }
+
+    pub async fn write(&self, sensors: &[SystemSensor], device_name: &str) {
+        let lines = sensors.iter().filter_map(|sensor| line_protocol(sensor, device_name));
+        match self {
+            InfluxDbWriter::Http { client, endpoint, token } => {
+                let body = lines.collect::<Vec<_>>().join("\n");
+                if body.is_empty() {
+                    return;
+                }
+                let mut request = client.post(endpoint).body(body);
+                if let Some(token) = token {
+                    request = request.header("Authorization", format!("Token {}", token));
+                }
+                match request.send().await {
+                    Ok(response) if !response.status().is_success() => {
+                        tracing::error!(status = %response.status(), "InfluxDB write failed");
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::error!(error = %e, "InfluxDB write failed"),
+                }
+            }
+            InfluxDbWriter::Udp { socket, target } => {
+                for line in lines {
+                    if let Err(e) = socket.send_to(line.as_bytes(), target.as_str()).await {
+                        tracing::error!(target = %target, error = %e, "InfluxDB UDP send failed");
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sensors::SystemSensorType;
+    use std::collections::HashMap;
+
+    fn sensor(name: &str, value: f64, extra: Option<&str>) -> SystemSensor {
+        SystemSensor {
+            name: name.to_string(),
+            label: None,
+            value,
+            unit: "%".to_string(),
+            sensor_type: SystemSensorType::CpuUsage,
+            attributes: HashMap::new(),
+            extra: extra.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_line_protocol() {
+        assert_eq!(
+            line_protocol(&sensor("cpu_usage", 23.4, None), "myhost").as_deref(),
+            Some("orbiq_metrics,device=myhost,sensor=cpu_usage value=23.4")
+        );
+        assert_eq!(
+            line_protocol(&sensor("cpu_usage", 5.0, None), "my host,a=b").as_deref(),
+            Some("orbiq_metrics,device=my\\ host\\,a\\=b,sensor=cpu_usage value=5")
+        );
+        assert_eq!(line_protocol(&sensor("battery_bat0_state", 0.0, Some("Full")), "myhost"), None);
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+use serde_json::Value;
+use crate::config::JsonFileSensorConfig;
+use crate::naming::sanitize_sensor_name;
+use crate::sensors::{CollectFuture, SensorCollector, SystemSensor, SystemSensorType};
+
+// Entries currently failing, so a missing file is logged once rather than every cycle
+static FAILING_FILES: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+// Values from JSON files written by other tools, e.g. a cron job dumping `sensors -j`
+pub struct JsonFileCollector {
+    pub files: Vec<JsonFileSensorConfig>,
+}
+
+impl SensorCollector for JsonFileCollector {
+    fn collect(&self) -> CollectFuture<'_> {
+        Box::pin(async move {
+            let mut sensors = Vec::new();
+            for file in &self.files {
+                sensors.extend(read_json_sensor(file).await);
+            }
+            sensors
+        })
+    }
+
+    fn name(&self) -> &str {
+        "json_file"
+    }
+}
+
+async fn read_json_sensor(config: &JsonFileSensorConfig) -> Option<SystemSensor> {
+    let value = match read_value(config).await {
+        Ok(value) => value,
+        Err(e) => {
+            warn_file(&config.name, &e);
+            return None;
+        }
+    };
+    mark_recovered(&config.name);
+    Some(SystemSensor {
+        name: sanitize_sensor_name(&config.name),
+        label: config.label.clone(),
+        value,
+        unit: config.unit.clone(),
+        sensor_type: SystemSensorType::Generic,
+        attributes: HashMap::new(),
+        extra: None,
+    })
+}
+
+async fn read_value(config: &JsonFileSensorConfig) -> Result<f64, String> {
+    let content = tokio::fs::read_to_string(&config.path)
+        .await
+        .map_err(|e| format!("failed to read {}: {}", config.path.display(), e))?;
+    let json: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("invalid JSON in {}: {}", config.path.display(), e))?;
+    let value = lookup(&json, &config.json_path).ok_or_else(|| format!("{} not found", config.json_path))?;
+    number(value).ok_or_else(|| format!("{} is not a number: {}", config.json_path, value))
+}
+
+// "temperatures.cpu.value" -> json["temperatures"]["cpu"]["value"]; numeric segments
+// index into arrays, e.g. "fans.0.rpm"
+fn lookup<'a>(json: &'a Value, json_path: &str) -> Option<&'a Value> {
+    json_path.split('.').try_fold(json, |value, segment| match value {
+        Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+        _ => value.get(segment),
+    })
+}
+
+// Some tools write numbers as strings, e.g. {"temp": "45.5"}
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::String(text) => text.trim().parse().ok(),
+        _ => value.as_f64(),
+    }
+}
+
+fn warn_file(name: &str, error: &str) {
+    let failing = FAILING_FILES.get_or_init(|| Mutex::new(HashSet::new()));
+    let mut failing = failing.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if failing.insert(name.to_string()) {
+        tracing::warn!(sensor = %name, error = %error, "Failed to read JSON file sensor");
+    }
+}
+
+fn mark_recovered(name: &str) {
+    let failing = FAILING_FILES.get_or_init(|| Mutex::new(HashSet::new()));
+    let mut failing = failing.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if failing.remove(name) {
+        tracing::info!(sensor = %name, "JSON file sensor is readable again");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_lookup() {
+        let json = json!({
+            "temperatures": { "cpu": { "value": 45.5 } },
+            "fans": [{ "rpm": 1200 }, { "rpm": "950" }]
+        });
+        assert_eq!(lookup(&json, "temperatures.cpu.value").and_then(number), Some(45.5));
+        assert_eq!(lookup(&json, "fans.0.rpm").and_then(number), Some(1200.0));
+        assert_eq!(lookup(&json, "fans.1.rpm").and_then(number), Some(950.0));
+        assert!(lookup(&json, "fans.2.rpm").is_none());
+        assert!(lookup(&json, "temperatures.gpu.value").is_none());
+        assert!(lookup(&json, "temperatures.cpu").and_then(number).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_read_json_sensor() {
+        let path = std::env::temp_dir().join(format!("orbiq_json_sensor_{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"ups": {"battery": {"charge": "87"}}}"#).unwrap();
+        let config = |json_path: &str, path: PathBuf| JsonFileSensorConfig {
+            name: "ups_battery".to_string(),
+            path,
+            json_path: json_path.to_string(),
+            unit: "%".to_string(),
+            label: Some("UPS Battery".to_string()),
+        };
+
+        let sensor = read_json_sensor(&config("ups.battery.charge", path.clone())).await.unwrap();
+        assert_eq!(sensor.name, "ups_battery");
+        assert_eq!(sensor.value, 87.0);
+        assert_eq!(sensor.unit, "%");
+        assert_eq!(sensor.label.as_deref(), Some("UPS Battery"));
+
+        assert!(read_json_sensor(&config("ups.battery.voltage", path.clone())).await.is_none());
+        std::fs::remove_file(&path).unwrap();
+        assert!(read_json_sensor(&config("ups.battery.charge", path)).await.is_none());
+    }
+}
@@ -0,0 +1,56 @@
+use crate::config::LabelOverride;
+use crate::sensors::SystemSensor;
+use std::collections::HashMap;
+
+// Replaces the hwmon label or generated friendly name of a sensor. sensors.labels and
+// [[sensor_labels]] do the same; when both name a sensor [[sensor_labels]] wins, and the
+// last of its entries for a name wins
+pub fn apply_labels(sensor: &mut SystemSensor, table: &HashMap<String, String>, labels: &[LabelOverride]) {
+    let label = labels
+        .iter()
+        .rev()
+        .find(|o| o.name == sensor.name)
+        .map(|o| &o.label)
+        .or_else(|| table.get(&sensor.name));
+    if let Some(label) = label {
+        sensor.label = Some(label.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sensors::SystemSensorType;
+
+    #[test]
+    fn test_apply_labels() {
+        let mut sensor = SystemSensor {
+            name: "nct6797d_1".to_string(),
+            label: Some("SYSTIN".to_string()),
+            value: 41.0,
+            unit: "°C".to_string(),
+            sensor_type: SystemSensorType::Temperature,
+            attributes: HashMap::new(),
+            extra: None,
+        };
+        let label = |name: &str, label: &str| LabelOverride {
+            name: name.to_string(),
+            label: label.to_string(),
+        };
+
+        let table = HashMap::from([("nct6797d_1".to_string(), "VRM (table)".to_string())]);
+
+        apply_labels(&mut sensor, &HashMap::new(), &[label("nct6797d_2", "Chipset")]);
+        assert_eq!(sensor.label.as_deref(), Some("SYSTIN"));
+
+        apply_labels(&mut sensor, &table, &[label("nct6797d_2", "Chipset")]);
+        assert_eq!(sensor.label.as_deref(), Some("VRM (table)"));
+
+        apply_labels(
+            &mut sensor,
+            &table,
+            &[label("nct6797d_1", "VRM"), label("nct6797d_1", "Motherboard VRM Temperature")],
+        );
+        assert_eq!(sensor.label.as_deref(), Some("Motherboard VRM Temperature"));
+    }
+}
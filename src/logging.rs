@@ -0,0 +1,53 @@
+use crate::config::LogLevel;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::EnvFilter;
+
+pub fn init_logging(level: LogLevel) {
+    #[cfg(feature = "journald")]
+    if std::env::var_os("JOURNAL_STREAM").is_some() {
+        match journald_layer() {
+            Ok(layer) => {
+                use tracing_subscriber::prelude::*;
+                tracing_subscriber::registry()
+                    .with(layer.with_filter(env_filter(level)))
+                    .init();
+                return;
+            }
+            Err(e) => eprintln!("Failed to connect to the systemd journal, logging to stderr: {}", e),
+        }
+    }
+
+    // At debug and trace every closed span logs its busy/idle time, e.g. how long
+    // collect_temperatures took; at info that would double the lines per cycle
+    let filter = env_filter(level);
+    let span_events = match filter.max_level_hint() {
+        Some(max_level) if max_level >= LevelFilter::DEBUG => FmtSpan::CLOSE,
+        _ => FmtSpan::NONE,
+    };
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(span_events)
+        .init();
+}
+
+// RUST_LOG, e.g. RUST_LOG=orbiq=debug, takes precedence over logging.level
+fn env_filter(level: LogLevel) -> EnvFilter {
+    EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::default().add_directive(LevelFilter::from_level(level.into()).into()))
+}
+
+// Syslog priorities: error 3, warn 4, info 6, debug 7. tracing-journald maps info to
+// notice (5) by default, which would make every info line stand out in journalctl.
+#[cfg(feature = "journald")]
+fn journald_layer() -> std::io::Result<tracing_journald::Layer> {
+    use tracing_journald::{Priority, PriorityMappings};
+
+    Ok(tracing_journald::layer()?.with_priority_mappings(PriorityMappings {
+        error: Priority::Error,
+        warn: Priority::Warning,
+        info: Priority::Informational,
+        debug: Priority::Debug,
+        trace: Priority::Debug,
+    }))
+}
@@ -1,118 +1,585 @@
+mod alerts;
+mod amdgpu_sensor;
+mod calibration;
+mod cli;
 mod config;
+mod config_template;
+mod command_sensor;
+mod cpufreq_sensor;
+mod disk_io_sensor;
+mod dry_run;
+mod error;
+mod history;
 mod homeassistant;
+mod http_api;
 mod mqtt_client;
+mod mqtt_failover;
+mod mqtt_protocol;
+mod naming;
+mod network_sensor;
+mod onewire_sensor;
+mod ping_sensor;
 mod sensors;
+mod smoothing;
+mod startup;
+mod state_store;
+mod statsd;
+mod sysfs;
 mod system_sensor;
 mod temperature_sensor;
+mod thermal_zone_sensor;
+mod tls;
 mod fan_sensors;
+mod filters;
 mod hwmon_devices;
+mod iio_sensors;
+mod json_file_sensor;
+mod labeling;
+mod logging;
+mod metrics;
+mod influxdb;
+mod utils;
+mod power_sensor;
+mod power_supply_sensor;
+mod process_sensor;
+mod prometheus;
+mod psi_sensor;
+mod reload;
+mod remote_sensor;
+mod rapl_sensor;
+mod reconnect;
+mod smart_sensor;
+mod voltage_sensor;
+mod wifi_sensor;
 
-use crate::homeassistant::{system_sensor_availability};
-use crate::mqtt_client::{get_mqtt_client, publish, publish_handler, MqttSensorTopics};
-use crate::sensors::{generate_payloads, get_all_sensors, SystemSensor};
+use crate::alerts::AlertTracker;
+use crate::homeassistant::{build_aggregate_payload, device_availability, system_sensor_availability};
+use crate::mqtt_client::{
+    get_mqtt_client, publish, publish_handler, MqttPayload, MqttSensorTopics, PublishBatcher, PublishSchedule, StateTracker,
+};
+use std::time::Instant;
+use crate::sensors::{apply_mqtt_attributes, build_collectors, generate_payloads, get_all_sensors, SharedSensors, SystemSensor};
+use crate::startup::report_startup_checks;
+use crate::cli::{CliArgs, Command};
+use crate::config::{write_to_file_atomic, AvailabilityMode, ConfigError};
+use crate::config_template::generate_config_template;
+use crate::dry_run::dry_run_report;
+use crate::influxdb::InfluxDbWriter;
+use crate::logging::init_logging;
+use crate::mqtt_failover::MqttFailover;
+use crate::mqtt_protocol::{MqttClient, MqttEvent, MqttEventLoop};
+use crate::http_api::{serve_api, ApiState};
+use crate::prometheus::serve_metrics;
+use crate::reconnect::ReconnectState;
+use crate::ping_sensor::spawn_ping_task;
+use crate::reload::{apply_reload, reload_config};
+use crate::state_store::StateStore;
+use crate::statsd::StatsdWriter;
+use crate::error::AppError;
+use clap::Parser;
 use config::DaemonConfig;
 use homeassistant::DeviceInfo;
-use rumqttc::{AsyncClient, Event, EventLoop, Packet};
-use std::collections::HashSet;
+use rumqttc::QoS;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
-use tokio::signal::unix::{signal, SignalKind};
+use tokio::signal::unix::{signal, Signal, SignalKind};
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, mpsc, watch};
 use tokio::{signal, task, time};
 use tokio::task::JoinHandle;
+use tracing::Instrument;
 
 #[tokio::main]
-async fn main() {
-    let config: DaemonConfig = DaemonConfig::load_with_fallback();
+async fn main() -> Result<(), AppError> {
+    let args = CliArgs::parse();
+    if let Some(Command::GenerateConfig { output }) = &args.command {
+        return generate_config(output.as_deref());
+    }
+
+    // An explicit --config skips the default search locations
+    let mut config: DaemonConfig = match &args.config {
+        Some(path) => DaemonConfig::load_from_file(path)?,
+        None => DaemonConfig::load_with_fallback()?,
+    };
+    config.apply_cli_overrides(&args)?;
+    if let Some(Command::SaveConfig { output }) = &args.command {
+        config.save_to_file(output)?;
+        eprintln!("Wrote effective configuration to {}", output.display());
+        return Ok(());
+    }
+
+    init_logging(config.logging.level);
+    match &config.source_path {
+        Some(path) => tracing::info!(path = %path.display(), "Loaded configuration"),
+        None => tracing::info!("No configuration file found, using defaults"),
+    }
+    config.log_warnings();
+    tracing::info!(device = %config.device.name, "Starting system monitor");
+
+    let mut collectors = build_collectors(&config);
+    let startup_sensors = get_all_sensors(&config, &collectors).await;
+    let startup_warnings = config.run_startup_checks(&startup_sensors);
+    if !report_startup_checks(&config, &startup_warnings) {
+        return Err(AppError::StartupChecks);
+    }
+
+    if args.dry_run {
+        let device_info = DeviceInfo::from_config(&config.device);
+        let payloads: Vec<MqttSensorTopics> = generate_payloads(&startup_sensors, &config, &device_info).collect();
+        match serde_json::to_string_pretty(&dry_run_report(&payloads)) {
+            Ok(report) => println!("{}", report),
+            Err(e) => tracing::error!(error = %e, "Failed to format dry run output"),
+        }
+        return Ok(());
+    }
+
+    // Aborted and respawned by the publish task when a reload changes the ping settings
+    let mut ping_task: Option<JoinHandle<()>> =
+        (!config.ping.targets.is_empty()).then(|| spawn_ping_task(config.ping.clone()));
+
+    let failover = MqttFailover::from_config(&config)?;
+    let (publish_client, mut eventloop): (MqttClient, MqttEventLoop) = get_mqtt_client(&config, &failover);
+    let subscribe_client = publish_client.clone();
 
-    println!(
-        "Starting temperature daemon with device: {}",
-        config.device.name
+    // Values received on external topics, shared between the event loop and the publish task
+    let attribute_values: Arc<Mutex<HashMap<String, String>>> = Arc::new(Mutex::new(HashMap::new()));
+    let publish_attribute_values = Arc::clone(&attribute_values);
+    let device_online = device_availability(
+        &config.mqtt.discovery_prefix,
+        &config.device.name,
+        true,
+        config.availability_qos().into(),
     );
+    // Credentials changed by a SIGHUP reload are handed to the event loop for the next reconnect
+    let (credentials_tx, mut credentials_rx) =
+        watch::channel((config.mqtt.username.clone(), config.mqtt.password.clone()));
+    let attribute_topics: Vec<String> = config
+        .sensors
+        .mqtt_attributes
+        .iter()
+        .map(|attribute| attribute.mqtt_topic.clone())
+        .collect();
 
-    let (publish_client, mut eventloop): (AsyncClient, EventLoop) = get_mqtt_client(&config);
+    // Sensors of the latest cycle, served by the Prometheus endpoint and the HTTP API
+    let shared_sensors: SharedSensors = Arc::new(RwLock::new(Vec::new()));
+    let mqtt_connected = Arc::new(AtomicBool::new(false));
+    if config.prometheus.enabled {
+        let listener = TcpListener::bind(("0.0.0.0", config.prometheus.port))
+            .await
+            .map_err(AppError::MetricsBind)?;
+        tracing::info!(port = config.prometheus.port, "Serving Prometheus metrics");
+        task::spawn(serve_metrics(listener, Arc::clone(&shared_sensors), config.device.name.clone()));
+    }
+    if config.http_api.enabled {
+        let listener = TcpListener::bind(("0.0.0.0", config.http_api.port))
+            .await
+            .map_err(AppError::HttpApiBind)?;
+        tracing::info!(port = config.http_api.port, "Serving HTTP API");
+        let state = ApiState {
+            sensors: Arc::clone(&shared_sensors),
+            mqtt_connected: Arc::clone(&mqtt_connected),
+            started_at: Instant::now(),
+        };
+        task::spawn(serve_api(listener, state));
+    }
+    let mut influxdb = InfluxDbWriter::from_config(&config.influxdb).await?;
+    let mut statsd = StatsdWriter::from_config(&config.statsd).await?;
+    let mqtt_enabled = config.mqtt.enabled;
+    // ConnAcks from the event loop, carrying session_present
+    let (connack_tx, mut connack_rx) = mpsc::channel::<bool>(8);
+    let mut reconnect = ReconnectState::new(&config.mqtt, failover, Arc::clone(&mqtt_connected), connack_tx);
+
+    // Signals the publish task to mark everything offline and stop
+    let (shutdown_tx, mut shutdown_rx) = broadcast::channel::<()>(1);
+    let shutdown_timeout = Duration::from_secs(config.daemon.shutdown_timeout_secs);
 
     // Spawn a task to publish temperatures and system stats
-    let publish_task: JoinHandle<()> = task::spawn(async move {
+    let publish_span = tracing::info_span!("publish_loop", device = %config.device.name);
+    let mut publish_task: JoinHandle<()> = task::spawn(async move {
+        // Installed before the first sleep, SIGHUP would terminate the process until then
+        let mut hangup = match signal(SignalKind::hangup()) {
+            Ok(hangup) => Some(hangup),
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to bind SIGHUP handler, config reload is disabled");
+                None
+            }
+        };
+
         // Wait a bit for the connection to establish
         time::sleep(Duration::from_secs(5)).await;
 
-        let mut published_sensors: HashSet<String> = HashSet::new();
-        let device_info: DeviceInfo = DeviceInfo::from_config(&config.device);
-        let mut cycle_counter = 0u32;
+        // When each sensor's discovery was last sent, re-sent after mqtt.discovery_ttl_secs
+        let mut published_sensors: HashMap<String, Instant> = HashMap::new();
+        let mut state_tracker = StateTracker::new(
+            Duration::from_secs(config.max_value_age_secs()),
+            config.publish.delta_threshold,
+        );
+        let mut device_info: DeviceInfo = DeviceInfo::from_config(&config.device);
+        let mut schedule = PublishSchedule::new(config.update_interval_secs, &config.sensor_overrides);
+        let mut alert_tracker = AlertTracker::with_device_triggers(
+            &config.mqtt.discovery_prefix,
+            &device_info,
+            config.discovery_qos().into(),
+        );
+        let mut state_store = config.state_file.as_deref().map(StateStore::open);
+        let mut session_resumed = false;
+        let mut cycle: u64 = 0;
 
         loop {
-            let all_sensors: Vec<SystemSensor> = get_all_sensors();
-            if all_sensors.is_empty() {
-                eprintln!("No sensors found");
-            }
+            cycle += 1;
+            let cycle_start = Instant::now();
+            let all_sensors = async {
+                // Without a surviving session the broker may have lost the retained discovery
+                // messages, so everything is re-sent after a reconnect
+                while let Ok(session_present) = connack_rx.try_recv() {
+                    session_resumed = session_present;
+                    if !session_present && let Some(store) = &mut state_store {
+                        store.discard_restored();
+                    }
+                    if !session_present && !published_sensors.is_empty() {
+                        tracing::info!("Reconnected without a persistent session, re-sending discovery");
+                        published_sensors.clear();
+                        state_tracker = StateTracker::new(
+                            Duration::from_secs(config.max_value_age_secs()),
+                            config.publish.delta_threshold,
+                        );
+                    }
+                }
 
-            let all_payloads: Vec<MqttSensorTopics> =
-                generate_payloads(&all_sensors, &config, &device_info).collect();
-
-            for payload in &all_payloads {
-                publish_handler(
-                    &publish_client,
-                    &payload,
-                    &mut published_sensors,
-                    config.discovery_delay_ms,
-                    &mut cycle_counter,
-                )
-                .await;
-            }
+                let mut all_sensors: Vec<SystemSensor> = get_all_sensors(&config, &collectors).await;
+                if all_sensors.is_empty() {
+                    tracing::warn!("No sensors found");
+                }
+                tracing::debug!(count = all_sensors.len(), "Collected sensors");
 
-            cycle_counter = cycle_counter.wrapping_add(1);
+                if let Ok(received_values) = publish_attribute_values.lock() {
+                    apply_mqtt_attributes(&mut all_sensors, &config.sensors.mqtt_attributes, &received_values);
+                }
+
+                if config.prometheus.enabled || config.http_api.enabled {
+                    match shared_sensors.write() {
+                        Ok(mut shared) => *shared = all_sensors.clone(),
+                        Err(poisoned) => *poisoned.into_inner() = all_sensors.clone(),
+                    }
+                }
+
+                if let Some(influxdb) = &influxdb {
+                    influxdb.write(&all_sensors, &config.device.name).await;
+                }
+                if let Some(statsd) = &mut statsd {
+                    statsd.write(&all_sensors, &config.device.name).await;
+                }
+
+                if config.mqtt.enabled {
+                    // aggregate_only publishes neither per-sensor states nor their discovery
+                    let all_payloads: Vec<MqttSensorTopics> = if config.publish.aggregate_only {
+                        Vec::new()
+                    } else {
+                        generate_payloads(&all_sensors, &config, &device_info).collect()
+                    };
+
+                    let now = Instant::now();
+                    let mut batcher = PublishBatcher::new(config.mqtt.publish_batch_size, config.mqtt.batch_delay_ms);
+                    for payload in &all_payloads {
+                        if !schedule.is_due(&payload.name, now) {
+                            continue;
+                        }
+                        schedule.mark_published(&payload.name, now);
+                        // Discovery from the previous run is still on the broker when it resumed our session
+                        if session_resumed
+                            && let Some(store) = &mut state_store
+                            && let Some(previous) = store.take_restored(payload)
+                        {
+                            published_sensors.insert(payload.name.clone(), now);
+                            if previous.last_state.as_deref() == Some(payload.state.payload.as_str()) {
+                                state_tracker.mark_published(&payload.name, &payload.state.payload, payload.value, now);
+                            }
+                        }
+                        publish_handler(
+                            &publish_client,
+                            payload,
+                            &mut published_sensors,
+                            &mut state_tracker,
+                            config.discovery_delay_ms,
+                            Duration::from_secs(config.mqtt.discovery_ttl_secs),
+                        )
+                        .await;
+                        if let Some(store) = &mut state_store
+                            && published_sensors.contains_key(&payload.name)
+                        {
+                            store.record(payload);
+                        }
+                        batcher.pace().await;
+                    }
+                    if let Some(store) = &mut state_store {
+                        store.save_if_changed();
+                    }
+                    if let Some(aggregate_topic) = &config.publish.aggregate_topic {
+                        let aggregate = build_aggregate_payload(&all_sensors, aggregate_topic, config.state_qos().into());
+                        if let Err(e) = publish(&publish_client, aggregate).await {
+                            tracing::error!(topic = %aggregate_topic, error = %e, "Aggregate publish failed");
+                        }
+                    }
+
+                    let alerts = alert_tracker.check(&all_sensors, &config.thresholds, config.state_qos().into());
+                    for alert in alerts {
+                        if let Err(e) = publish(&publish_client, alert).await {
+                            tracing::error!(error = %e, "Alert publish failed");
+                        }
+                    }
+                }
+
+                tracing::info!(
+                    sensor_count = all_sensors.len(),
+                    cycle_duration_ms = cycle_start.elapsed().as_millis() as u64,
+                    "Publish cycle finished"
+                );
+                all_sensors
+            }
+            .instrument(tracing::info_span!("publish_cycle", cycle))
+            .await;
 
             // Check if we should exit
             tokio::select! {
-                _ = time::sleep(Duration::from_secs(config.update_interval_secs)) => {},
-                _ = wait_for_sigterm() => {
-                    println!("Received shutdown signal, marking sensors as offline...");
-                    for sensor in &all_sensors {
-                        let payload = system_sensor_availability(sensor, &config.device.name, false);
-                        if let Err(e) = publish(&publish_client, payload).await {
-                            eprintln!("Failed to mark sensor {} as offline: {}", sensor.name, e);
+                _ = time::sleep(schedule.tick_interval()) => {},
+                _ = wait_for_sighup(&mut hangup) => {
+                    let reloaded = match reload_config(&config, &args) {
+                        Ok(reloaded) => reloaded,
+                        Err(e) => {
+                            tracing::error!(error = %e, "Config reload failed, keeping the current config");
+                            continue;
+                        }
+                    };
+                    reloaded.log_warnings();
+                    let summary = apply_reload(&mut config, reloaded);
+                    for change in &summary.restart_required {
+                        tracing::warn!(%change, "Config change requires a restart and was not applied");
+                    }
+                    if summary.changed.is_empty() {
+                        tracing::info!("Config reloaded, nothing changed");
+                    } else {
+                        tracing::info!(changed = %summary.changed.join(", "), "Config reloaded");
+                    }
+                    if summary.credentials_changed {
+                        credentials_tx.send_replace((config.mqtt.username.clone(), config.mqtt.password.clone()));
+                    }
+                    if summary.ping_changed {
+                        if let Some(task) = ping_task.take() {
+                            task.abort();
+                        }
+                        ping_task = (!config.ping.targets.is_empty()).then(|| spawn_ping_task(config.ping.clone()));
+                    }
+
+                    // Re-send discovery so Home Assistant picks up updated device info
+                    device_info = DeviceInfo::from_config(&config.device);
+                    published_sensors.clear();
+                    state_tracker = StateTracker::new(
+                        Duration::from_secs(config.max_value_age_secs()),
+                        config.publish.delta_threshold,
+                    );
+                    schedule = PublishSchedule::new(config.update_interval_secs, &config.sensor_overrides);
+                    collectors = build_collectors(&config);
+                    alert_tracker = AlertTracker::with_device_triggers(
+                        &config.mqtt.discovery_prefix,
+                        &device_info,
+                        config.discovery_qos().into(),
+                    );
+                    match InfluxDbWriter::from_config(&config.influxdb).await {
+                        Ok(writer) => influxdb = writer,
+                        Err(e) => tracing::error!(error = %e, "Keeping the previous InfluxDB output"),
+                    }
+                    match StatsdWriter::from_config(&config.statsd).await {
+                        Ok(writer) => statsd = writer,
+                        Err(e) => tracing::error!(error = %e, "Keeping the previous StatsD output"),
+                    }
+                }
+                _ = shutdown_rx.recv() => {
+                    if let Some(store) = &mut state_store {
+                        store.save();
+                    }
+                    if config.mqtt.enabled {
+                        tracing::info!("Received shutdown signal, marking sensors as offline");
+                        let availability_qos = config.availability_qos().into();
+                        let device_offline = device_availability(&config.mqtt.discovery_prefix, &config.device.name, false, availability_qos);
+                        if let Err(e) = publish(&publish_client, device_offline).await {
+                            tracing::error!(error = %e, "Failed to mark device as offline");
+                        }
+                        // Without per-sensor topics there is no sensor availability to withdraw
+                        let per_sensor = config.mqtt.availability_mode == AvailabilityMode::PerSensor
+                            && !config.publish.aggregate_only;
+                        for sensor in all_sensors.iter().filter(|s| per_sensor && s.sensor_type.has_availability()) {
+                            let payload = system_sensor_availability(
+                                sensor,
+                                &config.mqtt.discovery_prefix,
+                                &config.device.name,
+                                false,
+                                availability_qos,
+                            );
+                            if let Err(e) = publish(&publish_client, payload).await {
+                                tracing::error!(sensor = %sensor.name, error = %e, "Failed to mark sensor as offline");
+                            }
+                        }
+                        // The event loop stops once everything queued before this has been sent
+                        if let Err(e) = publish_client.disconnect().await {
+                            tracing::error!(error = %e, "Failed to disconnect from MQTT broker");
                         }
                     }
                     break;
                 }
             }
         }
-    });
+    }.instrument(publish_span));
 
     // Handle events and connection status with auto-reconnect
-    tokio::select! {
-        _ = async {
-            loop {
-                match eventloop.poll().await {
-                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
-                        println!("Connected to MQTT broker");
-                    }
-                    Ok(Event::Incoming(_packet)) => {
-                        // Optionally log incoming packets
-                    }
-                    Ok(Event::Outgoing(_packet)) => {
-                        // Optionally log outgoing packets
-                    }
-                    Err(e) => {
-                        eprintln!("MQTT Error: {}", e);
-                        println!("Attempting to reconnect in 5 seconds...");
-                        time::sleep(Duration::from_secs(5)).await;
-                        // The eventloop will automatically try to reconnect
+    let event_loop = async {
+        if mqtt_enabled {
+            run_event_loop(
+                &mut eventloop,
+                &subscribe_client,
+                &device_online,
+                &mut credentials_rx,
+                &attribute_topics,
+                &attribute_values,
+                &mut reconnect,
+            )
+            .await
+        } else {
+            std::future::pending().await
+        }
+    };
+    tokio::pin!(event_loop);
+    let reason = tokio::select! {
+        result = &mut event_loop => return result,
+        reason = wait_for_shutdown(&mut publish_task) => reason?,
+    };
+    tracing::info!(?reason, "Shutting down");
+    if reason == ShutdownReason::PublishTaskDone {
+        return Ok(());
+    }
+
+    // The event loop keeps running until the publish task's offline messages have been sent
+    let _ = shutdown_tx.send(());
+    let drain = async {
+        let _ = (&mut publish_task).await;
+        if mqtt_enabled {
+            event_loop.await
+        } else {
+            Ok(())
+        }
+    };
+    match time::timeout(shutdown_timeout, drain).await {
+        Ok(result) => result,
+        Err(_) => {
+            tracing::warn!(
+                timeout_secs = shutdown_timeout.as_secs(),
+                "Shutdown timed out, exiting without waiting for pending MQTT messages"
+            );
+            Ok(())
+        }
+    }
+}
+
+fn generate_config(output: Option<&Path>) -> Result<(), AppError> {
+    let template = generate_config_template()
+        .map_err(|e| ConfigError::Invalid(format!("failed to render the config template: {}", e)))?;
+
+    match output {
+        Some(path) => {
+            write_to_file_atomic(path, &template)?;
+            eprintln!("Wrote default configuration to {}", path.display());
+        }
+        None => print!("{}", template),
+    }
+    Ok(())
+}
+
+async fn run_event_loop(
+    eventloop: &mut MqttEventLoop,
+    subscribe_client: &MqttClient,
+    device_online: &MqttPayload,
+    credentials: &mut watch::Receiver<(Option<String>, Option<String>)>,
+    attribute_topics: &[String],
+    attribute_values: &Mutex<HashMap<String, String>>,
+    reconnect: &mut ReconnectState,
+) -> Result<(), AppError> {
+    loop {
+        if credentials.has_changed().unwrap_or(false)
+            && let (Some(username), Some(password)) = credentials.borrow_and_update().clone()
+        {
+            if reconnect.set_credentials(username.clone(), password.clone()) {
+                eventloop.set_credentials(username, password);
+            }
+            tracing::info!("MQTT credentials updated, they are used from the next reconnect");
+        }
+
+        match eventloop.poll().await {
+            Ok(MqttEvent::Connected { session_present }) => {
+                tracing::info!("Connected to MQTT broker");
+                reconnect.on_connected(session_present);
+                // Replaces the retained last will message from a previous connection
+                if let Err(e) = publish(subscribe_client, device_online.clone()).await {
+                    tracing::error!(error = %e, "Failed to mark device as online");
+                }
+                for topic in attribute_topics {
+                    if let Err(e) = subscribe_client.subscribe(topic, QoS::AtLeastOnce).await {
+                        tracing::error!(%topic, error = %e, "Failed to subscribe");
                     }
                 }
             }
-        } => {},
-        _ = publish_task => {},
-        _ = signal::ctrl_c() => {
-            println!("Shutting down...");
-        }
-        _ = wait_for_sigterm() => {
-            println!("Signal received, shutting down...");
+            Ok(MqttEvent::Message { topic, payload }) => {
+                if let Ok(mut values) = attribute_values.lock() {
+                    values.insert(topic, payload);
+                }
+            }
+            Ok(MqttEvent::Disconnected) => return Ok(()),
+            Ok(MqttEvent::Other) => {}
+            // Retrying with rejected credentials will never succeed
+            Err(e) if e.is_not_authorized() => return Err(e.into()),
+            Err(e) => {
+                tracing::warn!(error = %e, "MQTT connection error");
+                if let Some(options) = reconnect.next_broker() {
+                    let (broker, port) = options.broker_address();
+                    tracing::warn!(%broker, port, "MQTT broker unreachable for too long, failing over");
+                    eventloop.set_options(options);
+                }
+                reconnect.wait_before_reconnect().await;
+            }
         }
+    }
+}
 
+// Never resolves when the SIGHUP handler could not be installed
+async fn wait_for_sighup(hangup: &mut Option<Signal>) {
+    match hangup {
+        Some(hangup) => {
+            hangup.recv().await;
+        }
+        None => std::future::pending().await,
     }
 }
 
-async fn wait_for_sigterm() {
-    let mut sigterm = signal(SignalKind::terminate()).expect("Failed to bind SIGTERM handler");
-    sigterm.recv().await;
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ShutdownReason {
+    Sigterm,
+    Sigint,
+    PublishTaskDone, // The publish task only returns early by panicking, reported as AppError::PublishTask
+}
+
+async fn wait_for_shutdown(publish_task: &mut JoinHandle<()>) -> Result<ShutdownReason, AppError> {
+    let mut sigterm = signal(SignalKind::terminate()).map_err(AppError::SignalBind)?;
+    tokio::select! {
+        _ = sigterm.recv() => Ok(ShutdownReason::Sigterm),
+        result = signal::ctrl_c() => {
+            result.map_err(AppError::SignalBind)?;
+            Ok(ShutdownReason::Sigint)
+        }
+        result = publish_task => {
+            // Returned from main so the process exits non-zero and the service manager restarts it
+            result.map_err(AppError::PublishTask)?;
+            Ok(ShutdownReason::PublishTaskDone)
+        }
+    }
 }
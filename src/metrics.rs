@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::sensors::SystemSensor;
+use crate::sensors::SystemSensorType::DaemonMetric;
+
+// Every MQTT publish goes through mqtt_client::publish, which counts it here
+static PUBLISH_COUNT: AtomicU64 = AtomicU64::new(0);
+static PUBLISH_ERRORS: AtomicU64 = AtomicU64::new(0);
+
+pub fn record_publish(succeeded: bool) {
+    let counter = if succeeded { &PUBLISH_COUNT } else { &PUBLISH_ERRORS };
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+// Health of the daemon itself, enabled with daemon.self_monitoring
+pub fn collect_daemon_metrics() -> Vec<SystemSensor> {
+    let sensor = |name: &str, value: u64| SystemSensor {
+        name: name.to_string(),
+        label: None,
+        value: value as f64,
+        unit: "".to_string(),
+        sensor_type: DaemonMetric,
+        attributes: HashMap::new(),
+        extra: None,
+    };
+    vec![
+        sensor("daemon_publish_count", PUBLISH_COUNT.load(Ordering::Relaxed)),
+        sensor("daemon_publish_errors", PUBLISH_ERRORS.load(Ordering::Relaxed)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_publish() {
+        let value = |name: &str| {
+            collect_daemon_metrics()
+                .into_iter()
+                .find(|s| s.name == name)
+                .map(|s| s.value)
+                .unwrap()
+        };
+        let (count, errors) = (value("daemon_publish_count"), value("daemon_publish_errors"));
+
+        record_publish(true);
+        record_publish(true);
+        record_publish(false);
+
+        // Other tests may publish concurrently, so only a lower bound holds
+        assert!(value("daemon_publish_count") >= count + 2.0);
+        assert!(value("daemon_publish_errors") >= errors + 1.0);
+    }
+}
@@ -1,77 +1,392 @@
-use crate::config::DaemonConfig;
-use rumqttc::{AsyncClient, EventLoop, MqttOptions, QoS};
-use std::collections::HashSet;
-use std::time::Duration;
+use crate::config::{ConfigError, DaemonConfig, MqttBrokerConfig, QosLevel, SensorOverrideConfig};
+use crate::metrics::record_publish;
+use crate::mqtt_failover::MqttFailover;
+use crate::homeassistant::device_availability_topic;
+use crate::tls::transport_for;
+use crate::utils::matches_pattern;
+use crate::mqtt_protocol::{MqttClient, MqttClientError, MqttEventLoop, connect};
+use rumqttc::{LastWill, MqttOptions, QoS};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 use tokio::time;
 
 #[derive(Debug, Clone)]
 pub struct MqttPayload {
     pub(crate) topic: String,
     pub(crate) payload: String,
+    // Sent instead of payload when set; payload keeps the JSON for change detection and logs
+    pub(crate) payload_bytes: Option<Vec<u8>>,
     pub(crate) retain: bool,
+    pub(crate) qos: QoS,
 }
 
 #[derive(Debug, Clone)]
 pub struct MqttSensorTopics {
     pub(crate) name: String,
+    pub(crate) value: Option<f64>, // None for text sensors
+    pub(crate) publish_mode: PublishMode,
     pub(crate) state: MqttPayload,
     pub(crate) discovery: MqttPayload,
-    pub(crate) availability: MqttPayload,
+    pub(crate) availability: Option<MqttPayload>,
 }
 
-pub fn get_mqtt_client(config: &DaemonConfig) -> (AsyncClient, EventLoop) {
-    let mut mqttoptions = MqttOptions::new(
-        &config.mqtt.client_id,
-        &config.mqtt.broker,
-        config.mqtt.port,
-    );
+// When a sensor's state is sent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PublishMode {
+    OnChange,   // When changed beyond the delta threshold or older than max_value_age
+    Once,       // Static sensors, once per connection
+    EveryCycle, // Regardless of delta settings, e.g. the heartbeat
+}
+
+// Pauses briefly after every `batch_size` sensors so a cycle's publishes don't pile up in
+// the client's request channel (capacity 100) and fail with RequestsFull
+#[derive(Debug)]
+pub struct PublishBatcher {
+    batch_size: usize,
+    delay: Duration,
+    in_batch: usize,
+}
+
+impl PublishBatcher {
+    pub fn new(batch_size: usize, delay_ms: u64) -> Self {
+        Self {
+            batch_size: batch_size.max(1),
+            delay: Duration::from_millis(delay_ms),
+            in_batch: 0,
+        }
+    }
+
+    // Counts a published sensor, true when it completed a batch
+    fn count(&mut self) -> bool {
+        self.in_batch += 1;
+        if self.in_batch < self.batch_size {
+            return false;
+        }
+        self.in_batch = 0;
+        true
+    }
+
+    pub async fn pace(&mut self) {
+        if self.count() && !self.delay.is_zero() {
+            time::sleep(self.delay).await;
+        }
+    }
+}
+
+// Remembers the last published state of every sensor so unchanged values are only
+// republished once they are older than `max_value_age`. Numeric values must move by
+// more than `delta_threshold` to count as changed.
+#[derive(Debug)]
+pub struct StateTracker {
+    last_published: HashMap<String, (String, Instant)>,
+    last_values: HashMap<String, f64>,
+    max_value_age: Duration,
+    delta_threshold: f64,
+}
+
+impl StateTracker {
+    pub fn new(max_value_age: Duration, delta_threshold: f64) -> Self {
+        Self {
+            last_published: HashMap::new(),
+            last_values: HashMap::new(),
+            max_value_age,
+            delta_threshold,
+        }
+    }
+
+    pub fn should_publish(&self, name: &str, state: &str, value: Option<f64>, now: Instant) -> bool {
+        let Some((last_state, published_at)) = self.last_published.get(name) else {
+            return true;
+        };
+        if now.duration_since(*published_at) > self.max_value_age {
+            return true;
+        }
+        match (value, self.last_values.get(name)) {
+            // Attributes and history stats are published whenever they change, only the
+            // value itself is held back by the threshold
+            (Some(value), Some(last_value)) => {
+                (value - last_value).abs() > self.delta_threshold || without_value(last_state) != without_value(state)
+            }
+            _ => last_state != state,
+        }
+    }
+
+    pub fn was_published(&self, name: &str) -> bool {
+        self.last_published.contains_key(name)
+    }
+
+    pub fn mark_published(&mut self, name: &str, state: &str, value: Option<f64>, now: Instant) {
+        self.last_published
+            .insert(name.to_string(), (state.to_string(), now));
+        match value {
+            Some(value) => self.last_values.insert(name.to_string(), value),
+            None => self.last_values.remove(name),
+        };
+    }
+}
+
+// The state payload minus its "value", e.g. {"attributes": {...}, "min_60": 41.0}
+fn without_value(state: &str) -> Option<serde_json::Value> {
+    let mut payload: serde_json::Value = serde_json::from_str(state).ok()?;
+    payload.as_object_mut()?.remove("value");
+    Some(payload)
+}
+
+impl From<QosLevel> for QoS {
+    fn from(qos: QosLevel) -> Self {
+        match qos {
+            QosLevel::AtMostOnce => QoS::AtMostOnce,
+            QosLevel::AtLeastOnce => QoS::AtLeastOnce,
+            QosLevel::ExactlyOnce => QoS::ExactlyOnce,
+        }
+    }
+}
+
+// Per-sensor publish intervals from [[sensor_overrides]]; the publish loop ticks at the
+// shortest interval and only publishes sensors whose own interval has elapsed
+#[derive(Debug)]
+pub struct PublishSchedule {
+    default_interval: Duration,
+    overrides: Vec<(String, Duration)>,
+    last_published: HashMap<String, Instant>,
+}
+
+impl PublishSchedule {
+    pub fn new(default_interval_secs: u64, overrides: &[SensorOverrideConfig]) -> Self {
+        Self {
+            default_interval: Duration::from_secs(default_interval_secs),
+            overrides: overrides
+                .iter()
+                .filter_map(|o| Some((o.name_pattern.clone(), Duration::from_secs(o.update_interval_secs?))))
+                .collect(),
+            last_published: HashMap::new(),
+        }
+    }
+
+    pub fn tick_interval(&self) -> Duration {
+        self.overrides
+            .iter()
+            .map(|(_, interval)| *interval)
+            .fold(self.default_interval, Duration::min)
+    }
+
+    // The first matching override wins
+    fn interval_for(&self, name: &str) -> Duration {
+        self.overrides
+            .iter()
+            .find(|(pattern, _)| matches_pattern(pattern, name))
+            .map_or(self.default_interval, |(_, interval)| *interval)
+    }
+
+    pub fn is_due(&self, name: &str, now: Instant) -> bool {
+        // Half a tick of slack so loop jitter doesn't push a sensor to the next tick
+        let slack = self.tick_interval() / 2;
+        match self.last_published.get(name) {
+            Some(last) => now.duration_since(*last) + slack >= self.interval_for(name),
+            None => true,
+        }
+    }
+
+    pub fn mark_published(&mut self, name: &str, now: Instant) {
+        self.last_published.insert(name.to_string(), now);
+    }
+}
+
+// Connects to the failover's current broker; switching brokers later only swaps the
+// event loop's options, so the client handed to the publish task stays valid
+pub fn get_mqtt_client(config: &DaemonConfig, failover: &MqttFailover) -> (MqttClient, MqttEventLoop) {
+    if config.mqtt.tls.is_enabled() && config.mqtt.tls.insecure {
+        tracing::warn!("MQTT TLS certificate verification is disabled (mqtt.tls.insecure = true)");
+    }
+
+    let mqttoptions = failover.current_options();
+    let (broker, port) = mqttoptions.broker_address();
+    tracing::info!(%broker, port, protocol = ?config.mqtt.protocol_version, "MQTT broker configured");
+    connect(&config.mqtt, mqttoptions)
+}
+
+pub fn mqtt_options(config: &DaemonConfig, endpoint: &MqttBrokerConfig) -> Result<MqttOptions, ConfigError> {
+    let mut mqttoptions = MqttOptions::new(&config.mqtt.client_id, &endpoint.broker, endpoint.port);
     mqttoptions.set_keep_alive(Duration::from_secs(config.mqtt.keep_alive_secs));
 
+    // The broker marks the device offline if the connection drops without a clean shutdown
+    mqttoptions.set_last_will(LastWill::new(
+        device_availability_topic(&config.mqtt.discovery_prefix, &config.device.name),
+        "offline",
+        config.last_will_qos().into(),
+        config.mqtt.last_will_retain,
+    ));
+
     // Increase channel capacity and add auto-reconnect settings
     mqttoptions.set_max_packet_size(10240, 10240);
     mqttoptions.set_clean_session(false);
 
-    if let (Some(username), Some(password)) = (&config.mqtt.username, &config.mqtt.password) {
+    if let (Some(username), Some(password)) = (&endpoint.username, &endpoint.password) {
         mqttoptions.set_credentials(username, password);
     }
-    println!("MQTT broker: {}:{}", config.mqtt.broker, config.mqtt.port);
-    AsyncClient::new(mqttoptions, 100)
+    if let Some(transport) = transport_for(&config.mqtt.tls)? {
+        mqttoptions.set_transport(transport);
+    }
+    Ok(mqttoptions)
+}
+pub async fn publish(client: &MqttClient, data: MqttPayload) -> Result<(), MqttClientError> {
+    let result = client
+        .publish(data.topic, data.qos, data.retain, data.payload_bytes.unwrap_or_else(|| data.payload.into_bytes()))
+        .await;
+    record_publish(result.is_ok());
+    result
 }
-pub async fn publish(client: &AsyncClient, data: MqttPayload) -> Result<(), rumqttc::ClientError> {
-    client
-        .publish(data.topic, QoS::AtLeastOnce, data.retain, data.payload)
-        .await
+
+// Discovery (and with it availability) is re-sent once it is older than the TTL, so a broker
+// that lost its retained messages is repopulated without a daemon restart. A TTL of zero
+// never expires
+pub fn discovery_due(published_sensors: &HashMap<String, Instant>, name: &str, ttl: Duration, now: Instant) -> bool {
+    match published_sensors.get(name) {
+        None => true,
+        Some(discovered) => !ttl.is_zero() && now.duration_since(*discovered) >= ttl,
+    }
 }
 
 pub async fn publish_handler(
-    client: &AsyncClient,
+    client: &MqttClient,
     payload: &MqttSensorTopics,
-    published_sensors: &mut HashSet<String>,
+    published_sensors: &mut HashMap<String, Instant>,
+    state_tracker: &mut StateTracker,
     discovery_delay_ms: u64,
-    cycle_counter: &mut u32,
+    discovery_ttl: Duration,
 ) {
-    if !published_sensors.contains(&payload.name) {
+    if discovery_due(published_sensors, &payload.name, discovery_ttl, Instant::now()) {
+        published_sensors.remove(&payload.name);
         //publish Discovery
-        if let Err(e) = publish(&client, payload.discovery.clone()).await {
-            eprintln!("Discovery config error: {}", e);
+        if let Err(e) = publish(client, payload.discovery.clone()).await {
+            tracing::error!(sensor = %payload.name, error = %e, "Discovery publish failed");
         } else {
             //publish availability
-            published_sensors.insert(payload.name.parse().unwrap() /* std::string::String */);
+            published_sensors.insert(payload.name.clone(), Instant::now());
             // Mark as available immediately after discovery
-            if let Err(e) = publish(&client, payload.availability.clone()).await {
-                eprintln!("Availability publish error: {}", e);
+            if let Some(availability) = &payload.availability
+                && let Err(e) = publish(client, availability.clone()).await
+            {
+                tracing::error!(sensor = %payload.name, error = %e, "Availability publish failed");
             }
         }
         time::sleep(Duration::from_millis(discovery_delay_ms)).await;
     }
     //publish state
-    if let Err(e) = publish(&client, payload.state.clone()).await {
-        eprintln!("State publish error: {}", e);
-    }
-    if *cycle_counter % 20 == 0 {
-        println!("Refreshing sensor availability status: {:?}", payload.availability);
-        if let Err(e) = publish(&client, payload.availability.clone()).await {
-            eprintln!("Availability refresh error: {}", e);
+    let now = Instant::now();
+    let due = match payload.publish_mode {
+        PublishMode::OnChange => state_tracker.should_publish(&payload.name, &payload.state.payload, payload.value, now),
+        PublishMode::Once => !state_tracker.was_published(&payload.name),
+        PublishMode::EveryCycle => true,
+    };
+    if due {
+        if let Err(e) = publish(client, payload.state.clone()).await {
+            tracing::error!(sensor = %payload.name, error = %e, "State publish failed");
+        } else {
+            state_tracker.mark_published(&payload.name, &payload.state.payload, payload.value, now);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discovery_due() {
+        let now = Instant::now();
+        let ttl = Duration::from_secs(3600);
+        let published = HashMap::from([("cpu_usage".to_string(), now)]);
+
+        assert!(discovery_due(&published, "memory_usage", ttl, now));
+        assert!(!discovery_due(&published, "cpu_usage", ttl, now + Duration::from_secs(3599)));
+        assert!(discovery_due(&published, "cpu_usage", ttl, now + ttl));
+        assert!(!discovery_due(&published, "cpu_usage", Duration::ZERO, now + ttl));
+    }
+
+    #[test]
+    fn test_publish_batcher() {
+        let mut batcher = PublishBatcher::new(3, 5);
+        let completed: Vec<bool> = (0..7).map(|_| batcher.count()).collect();
+        assert_eq!(completed, [false, false, true, false, false, true, false]);
+
+        let mut unbatched = PublishBatcher::new(0, 5);
+        assert!(unbatched.count());
+    }
+
+    #[test]
+    fn test_publish_schedule() {
+        let overrides = [
+            SensorOverrideConfig {
+                name_pattern: "cpu_*".to_string(),
+                update_interval_secs: Some(5),
+                ..SensorOverrideConfig::default()
+            },
+            SensorOverrideConfig {
+                name_pattern: "disk_total_*".to_string(),
+                update_interval_secs: Some(3600),
+                ..SensorOverrideConfig::default()
+            },
+        ];
+        let mut schedule = PublishSchedule::new(30, &overrides);
+        let start = Instant::now();
+
+        assert_eq!(schedule.tick_interval(), Duration::from_secs(5));
+        assert!(schedule.is_due("cpu_usage", start));
+        schedule.mark_published("cpu_usage", start);
+        schedule.mark_published("memory_usage", start);
+        schedule.mark_published("disk_total_root", start);
+
+        let later = start + Duration::from_secs(5);
+        assert!(schedule.is_due("cpu_usage", later));
+        assert!(!schedule.is_due("memory_usage", later));
+        assert!(schedule.is_due("memory_usage", start + Duration::from_secs(28)));
+        assert!(!schedule.is_due("disk_total_root", start + Duration::from_secs(1800)));
+    }
+
+    #[test]
+    fn test_state_tracker_republishes_stale_values() {
+        let mut tracker = StateTracker::new(Duration::from_secs(150), 0.0);
+        let start = Instant::now();
+
+        let state = r#"{"value":12.5}"#;
+        let changed = r#"{"value":13.0}"#;
+
+        assert!(tracker.should_publish("cpu_usage", state, Some(12.5), start));
+        tracker.mark_published("cpu_usage", state, Some(12.5), start);
+
+        let later = start + Duration::from_secs(30);
+        assert!(!tracker.should_publish("cpu_usage", state, Some(12.5), later));
+        assert!(tracker.should_publish("cpu_usage", changed, Some(13.0), later));
+        assert!(tracker.should_publish("cpu_usage", state, Some(12.5), start + Duration::from_secs(151)));
+
+        tracker.mark_published("cpu_usage", state, Some(12.5), start + Duration::from_secs(151));
+        assert!(!tracker.should_publish("cpu_usage", state, Some(12.5), start + Duration::from_secs(181)));
+
+        assert!(tracker.was_published("cpu_usage"));
+        assert!(!tracker.was_published("os_release"));
+    }
+
+    #[test]
+    fn test_state_tracker_delta_threshold() {
+        let mut tracker = StateTracker::new(Duration::from_secs(300), 0.5);
+        let start = Instant::now();
+        let later = start + Duration::from_secs(30);
+
+        tracker.mark_published("k10temp_1", r#"{"value":45.0}"#, Some(45.0), start);
+        assert!(!tracker.should_publish("k10temp_1", r#"{"value":45.4}"#, Some(45.4), later));
+        assert!(tracker.should_publish("k10temp_1", r#"{"value":45.6}"#, Some(45.6), later));
+        assert!(tracker.should_publish("k10temp_1", r#"{"value":45.4}"#, Some(45.4), start + Duration::from_secs(301)));
+
+        // A changed attribute is published even though the value stayed within the threshold
+        tracker.mark_published("k10temp_1", r#"{"value":45.0,"attributes":{"source":"a"}}"#, Some(45.0), start);
+        assert!(!tracker.should_publish("k10temp_1", r#"{"value":45.2,"attributes":{"source":"a"}}"#, Some(45.2), later));
+        assert!(tracker.should_publish("k10temp_1", r#"{"value":45.0,"attributes":{"source":"b"}}"#, Some(45.0), later));
+        assert!(tracker.should_publish("k10temp_1", r#"{"value":45.0,"attributes":{"source":"a"},"max_60":47.0}"#, Some(45.0), later));
+
+        // Text sensors fall back to comparing the payload
+        tracker.mark_published("battery_bat0_state", r#"{"value":"Charging"}"#, None, start);
+        assert!(!tracker.should_publish("battery_bat0_state", r#"{"value":"Charging"}"#, None, later));
+        assert!(tracker.should_publish("battery_bat0_state", r#"{"value":"Full"}"#, None, later));
+    }
+}
@@ -0,0 +1,155 @@
+use crate::config::{ConfigError, DaemonConfig, MqttBrokerConfig};
+use crate::mqtt_client::mqtt_options;
+use rumqttc::MqttOptions;
+use std::time::{Duration, Instant};
+
+struct BrokerEndpoint {
+    options: MqttOptions,
+    own_credentials: bool, // Set in [[mqtt.brokers]] rather than taken from [mqtt]
+}
+
+// Brokers from [[mqtt.brokers]] in order of preference. When the current broker stays
+// unreachable for longer than mqtt.reconnect_max_secs the next one is tried, wrapping
+// around at the end of the list.
+pub struct MqttFailover {
+    endpoints: Vec<BrokerEndpoint>,
+    index: usize,
+    failover_after: Duration,
+    failing_since: Option<Instant>,
+    credentials: Option<(String, String)>, // Latest [mqtt] credentials, updated on reload
+}
+
+impl MqttFailover {
+    pub fn from_config(config: &DaemonConfig) -> Result<Self, ConfigError> {
+        let mqtt = &config.mqtt;
+        let brokers = if mqtt.brokers.is_empty() {
+            vec![MqttBrokerConfig {
+                broker: mqtt.broker.clone(),
+                port: mqtt.port,
+                username: None,
+                password: None,
+            }]
+        } else {
+            mqtt.brokers.clone()
+        };
+        let credentials = mqtt.username.clone().zip(mqtt.password.clone());
+
+        let mut endpoints = Vec::with_capacity(brokers.len());
+        for mut broker in brokers {
+            let own_credentials = broker.username.is_some() && broker.password.is_some();
+            if !own_credentials {
+                (broker.username, broker.password) = credentials.clone().unzip();
+            }
+            endpoints.push(BrokerEndpoint {
+                options: mqtt_options(config, &broker)?,
+                own_credentials,
+            });
+        }
+
+        Ok(Self {
+            endpoints,
+            index: 0,
+            failover_after: Duration::from_secs(mqtt.reconnect_max_secs),
+            failing_since: None,
+            credentials,
+        })
+    }
+
+    pub fn current_options(&self) -> MqttOptions {
+        let endpoint = &self.endpoints[self.index];
+        let mut options = endpoint.options.clone();
+        if !endpoint.own_credentials
+            && let Some((username, password)) = &self.credentials
+        {
+            options.set_credentials(username, password);
+        }
+        options
+    }
+
+    pub fn on_connected(&mut self) {
+        self.failing_since = None;
+    }
+
+    // Returns the options of the next broker once the current one has been failing for too long
+    pub fn on_connection_error(&mut self, now: Instant) -> Option<MqttOptions> {
+        let failing_since = *self.failing_since.get_or_insert(now);
+        if self.endpoints.len() < 2 || now.duration_since(failing_since) < self.failover_after {
+            return None;
+        }
+
+        self.index = (self.index + 1) % self.endpoints.len();
+        self.failing_since = None;
+        Some(self.current_options())
+    }
+
+    // Returns whether the current broker uses the shared credentials and needs them applied
+    pub fn set_credentials(&mut self, username: String, password: String) -> bool {
+        self.credentials = Some((username, password));
+        !self.endpoints[self.index].own_credentials
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn broker(host: &str, username: Option<&str>) -> MqttBrokerConfig {
+        MqttBrokerConfig {
+            broker: host.to_string(),
+            port: 1883,
+            username: username.map(str::to_string),
+            password: username.map(|_| "secret".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_failover_rotates_after_reconnect_max_secs() {
+        let mut config = DaemonConfig::default();
+        config.mqtt.brokers = vec![broker("10.0.0.1", None), broker("10.0.0.2", None)];
+        let mut failover = MqttFailover::from_config(&config).unwrap();
+        let start = Instant::now();
+
+        assert_eq!(failover.current_options().broker_address().0, "10.0.0.1");
+        assert!(failover.on_connection_error(start).is_none());
+        assert!(failover.on_connection_error(start + Duration::from_secs(30)).is_none());
+
+        let next = failover.on_connection_error(start + Duration::from_secs(60)).unwrap();
+        assert_eq!(next.broker_address().0, "10.0.0.2");
+
+        // Connecting again restarts the failure window
+        failover.on_connected();
+        assert!(failover.on_connection_error(start + Duration::from_secs(200)).is_none());
+        let next = failover.on_connection_error(start + Duration::from_secs(260)).unwrap();
+        assert_eq!(next.broker_address().0, "10.0.0.1");
+    }
+
+    #[test]
+    fn test_single_broker_never_rotates() {
+        let mut failover = MqttFailover::from_config(&DaemonConfig::default()).unwrap();
+        let start = Instant::now();
+        failover.on_connection_error(start);
+        assert!(failover.on_connection_error(start + Duration::from_secs(3600)).is_none());
+        assert_eq!(failover.current_options().broker_address().0, "localhost");
+    }
+
+    #[test]
+    fn test_shared_credentials() {
+        let mut config = DaemonConfig::default();
+        config.mqtt.username = Some("orbiq".to_string());
+        config.mqtt.password = Some("old".to_string());
+        config.mqtt.brokers = vec![broker("10.0.0.1", None), broker("10.0.0.2", Some("backup"))];
+        let mut failover = MqttFailover::from_config(&config).unwrap();
+
+        assert!(failover.set_credentials("orbiq".to_string(), "new".to_string()));
+        assert_eq!(
+            failover.current_options().credentials(),
+            Some(("orbiq".to_string(), "new".to_string()))
+        );
+
+        let start = Instant::now();
+        failover.on_connection_error(start);
+        let next = failover.on_connection_error(start + Duration::from_secs(60)).unwrap();
+        assert_eq!(next.credentials(), Some(("backup".to_string(), "secret".to_string())));
+        assert!(!failover.set_credentials("orbiq".to_string(), "newer".to_string()));
+    }
+}
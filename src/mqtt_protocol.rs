@@ -0,0 +1,262 @@
+use crate::config::MqttConfig;
+use crate::config::ProtocolVersion;
+use rumqttc::v5::mqttbytes::v5::{LastWill as LastWillV5, Packet as PacketV5, PublishProperties};
+use rumqttc::v5::mqttbytes::QoS as QoSV5;
+use rumqttc::{AsyncClient, ConnectReturnCode, ConnectionError, Event, EventLoop, MqttOptions, Outgoing, Packet, QoS, v5};
+use std::fmt;
+
+// Capacity of the request queue between the client handles and the event loop
+const REQUEST_CAPACITY: usize = 100;
+
+// Client handle of either protocol version; rumqttc has separate types for MQTT 3.1.1
+// and MQTT 5, so everything else publishes through this
+#[derive(Clone)]
+pub enum MqttClient {
+    V311(AsyncClient),
+    V5 {
+        client: v5::AsyncClient,
+        properties: PublishProperties, // mqtt.user_properties, sent with every publish
+    },
+}
+
+// Boxed, the two event loops differ a lot in size
+pub enum MqttEventLoop {
+    V311(Box<EventLoop>),
+    V5(Box<v5::EventLoop>),
+}
+
+// The events run_event_loop reacts to, the same for both protocol versions
+pub enum MqttEvent {
+    Connected { session_present: bool },
+    Message { topic: String, payload: String },
+    Disconnected, // Everything queued before disconnect() has been sent
+    Other,
+}
+
+#[derive(Debug)]
+pub enum MqttClientError {
+    V311(rumqttc::ClientError),
+    V5(v5::ClientError),
+}
+
+#[derive(Debug)]
+pub enum MqttConnectionError {
+    V311(ConnectionError),
+    V5(v5::ConnectionError),
+}
+
+// The failover keeps MQTT 3.1.1 options for every broker, MQTT 5 options are derived from them
+pub fn connect(config: &MqttConfig, options: MqttOptions) -> (MqttClient, MqttEventLoop) {
+    match config.protocol_version {
+        ProtocolVersion::V311 => {
+            if !config.user_properties.is_empty() {
+                tracing::warn!("mqtt.user_properties need protocol_version = \"v5\", ignoring them");
+            }
+            let (client, eventloop) = AsyncClient::new(options, REQUEST_CAPACITY);
+            (MqttClient::V311(client), MqttEventLoop::V311(Box::new(eventloop)))
+        }
+        ProtocolVersion::V5 => {
+            let (client, eventloop) = v5::AsyncClient::new(v5_options(&options), REQUEST_CAPACITY);
+            let properties = PublishProperties {
+                user_properties: config
+                    .user_properties
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect(),
+                ..Default::default()
+            };
+            (MqttClient::V5 { client, properties }, MqttEventLoop::V5(Box::new(eventloop)))
+        }
+    }
+}
+
+impl MqttClient {
+    pub async fn publish(&self, topic: String, qos: QoS, retain: bool, payload: Vec<u8>) -> Result<(), MqttClientError> {
+        match self {
+            MqttClient::V311(client) => client.publish(topic, qos, retain, payload).await.map_err(MqttClientError::V311),
+            MqttClient::V5 { client, properties } => client
+                .publish_with_properties(topic, v5_qos(qos), retain, payload, properties.clone())
+                .await
+                .map_err(MqttClientError::V5),
+        }
+    }
+
+    pub async fn subscribe(&self, topic: &str, qos: QoS) -> Result<(), MqttClientError> {
+        match self {
+            MqttClient::V311(client) => client.subscribe(topic, qos).await.map_err(MqttClientError::V311),
+            MqttClient::V5 { client, .. } => client.subscribe(topic, v5_qos(qos)).await.map_err(MqttClientError::V5),
+        }
+    }
+
+    // Queued behind any pending publishes, so the event loop sends those first
+    pub async fn disconnect(&self) -> Result<(), MqttClientError> {
+        match self {
+            MqttClient::V311(client) => client.disconnect().await.map_err(MqttClientError::V311),
+            MqttClient::V5 { client, .. } => client.disconnect().await.map_err(MqttClientError::V5),
+        }
+    }
+}
+
+impl MqttEventLoop {
+    pub async fn poll(&mut self) -> Result<MqttEvent, MqttConnectionError> {
+        match self {
+            MqttEventLoop::V311(eventloop) => match eventloop.poll().await.map_err(MqttConnectionError::V311)? {
+                Event::Incoming(Packet::ConnAck(connack)) => Ok(MqttEvent::Connected {
+                    session_present: connack.session_present,
+                }),
+                Event::Incoming(Packet::Publish(message)) => Ok(MqttEvent::Message {
+                    topic: message.topic,
+                    payload: String::from_utf8_lossy(&message.payload).trim().to_string(),
+                }),
+                Event::Outgoing(Outgoing::Disconnect) => Ok(MqttEvent::Disconnected),
+                _ => Ok(MqttEvent::Other),
+            },
+            MqttEventLoop::V5(eventloop) => match eventloop.poll().await.map_err(MqttConnectionError::V5)? {
+                v5::Event::Incoming(PacketV5::ConnAck(connack)) => Ok(MqttEvent::Connected {
+                    session_present: connack.session_present,
+                }),
+                v5::Event::Incoming(PacketV5::Publish(message)) => Ok(MqttEvent::Message {
+                    topic: String::from_utf8_lossy(&message.topic).to_string(),
+                    payload: String::from_utf8_lossy(&message.payload).trim().to_string(),
+                }),
+                v5::Event::Outgoing(Outgoing::Disconnect) => Ok(MqttEvent::Disconnected),
+                _ => Ok(MqttEvent::Other),
+            },
+        }
+    }
+
+    // Used from the next reconnect
+    pub fn set_credentials(&mut self, username: String, password: String) {
+        match self {
+            MqttEventLoop::V311(eventloop) => {
+                eventloop.mqtt_options.set_credentials(username, password);
+            }
+            MqttEventLoop::V5(eventloop) => {
+                eventloop.options.set_credentials(username, password);
+            }
+        }
+    }
+
+    // Switches to another broker from the next reconnect
+    pub fn set_options(&mut self, options: MqttOptions) {
+        match self {
+            MqttEventLoop::V311(eventloop) => eventloop.mqtt_options = options,
+            MqttEventLoop::V5(eventloop) => eventloop.options = v5_options(&options),
+        }
+    }
+}
+
+impl MqttConnectionError {
+    // Retrying with rejected credentials will never succeed
+    pub fn is_not_authorized(&self) -> bool {
+        match self {
+            MqttConnectionError::V311(e) => matches!(
+                e,
+                ConnectionError::ConnectionRefused(
+                    ConnectReturnCode::BadUserNamePassword | ConnectReturnCode::NotAuthorized
+                )
+            ),
+            MqttConnectionError::V5(e) => matches!(
+                e,
+                v5::ConnectionError::ConnectionRefused(
+                    v5::mqttbytes::v5::ConnectReturnCode::BadUserNamePassword
+                        | v5::mqttbytes::v5::ConnectReturnCode::NotAuthorized
+                )
+            ),
+        }
+    }
+}
+
+impl fmt::Display for MqttClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MqttClientError::V311(e) => write!(f, "{}", e),
+            MqttClientError::V5(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl fmt::Display for MqttConnectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MqttConnectionError::V311(e) => write!(f, "{}", e),
+            MqttConnectionError::V5(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+fn v5_options(options: &MqttOptions) -> v5::MqttOptions {
+    let (broker, port) = options.broker_address();
+    let mut v5_options = v5::MqttOptions::new(options.client_id(), broker, port);
+    v5_options.set_keep_alive(options.keep_alive());
+    v5_options.set_clean_start(options.clean_session());
+    v5_options.set_max_packet_size(Some(options.max_packet_size() as u32));
+    v5_options.set_transport(options.transport());
+    if let Some(will) = options.last_will() {
+        v5_options.set_last_will(LastWillV5::new(will.topic, will.message.to_vec(), v5_qos(will.qos), will.retain, None));
+    }
+    if let Some((username, password)) = options.credentials() {
+        v5_options.set_credentials(username, password);
+    }
+    v5_options
+}
+
+fn v5_qos(qos: QoS) -> QoSV5 {
+    match qos {
+        QoS::AtMostOnce => QoSV5::AtMostOnce,
+        QoS::AtLeastOnce => QoSV5::AtLeastOnce,
+        QoS::ExactlyOnce => QoSV5::ExactlyOnce,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rumqttc::LastWill;
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+
+    #[test]
+    fn test_v5_options() {
+        let mut options = MqttOptions::new("orbiq-test", "mqtt.local", 1884);
+        options.set_keep_alive(Duration::from_secs(30));
+        options.set_clean_session(false);
+        options.set_credentials("orbiq", "secret");
+        options.set_last_will(LastWill::new("orbiq/availability", "offline", QoS::AtLeastOnce, true));
+
+        let v5_options = v5_options(&options);
+        assert_eq!(v5_options.broker_address(), ("mqtt.local".to_string(), 1884));
+        assert_eq!(v5_options.client_id(), "orbiq-test");
+        assert_eq!(v5_options.keep_alive(), Duration::from_secs(30));
+        assert!(!v5_options.clean_start());
+        assert_eq!(v5_options.credentials(), Some(("orbiq".to_string(), "secret".to_string())));
+        let will = v5_options.last_will().unwrap();
+        assert_eq!(&will.topic[..], b"orbiq/availability");
+        assert_eq!(&will.message[..], b"offline");
+        assert_eq!(will.qos, QoSV5::AtLeastOnce);
+        assert!(will.retain);
+    }
+
+    #[tokio::test]
+    async fn test_connect_v5_user_properties() {
+        let config = MqttConfig {
+            protocol_version: ProtocolVersion::V5,
+            user_properties: BTreeMap::from([
+                ("site".to_string(), "basement".to_string()),
+                ("rack".to_string(), "r2".to_string()),
+            ]),
+            ..MqttConfig::default()
+        };
+        let (client, _eventloop) = connect(&config, MqttOptions::new("orbiq-test", "localhost", 1883));
+        let MqttClient::V5 { properties, .. } = client else {
+            panic!("expected an MQTT 5 client");
+        };
+        assert_eq!(
+            properties.user_properties,
+            [
+                ("rack".to_string(), "r2".to_string()),
+                ("site".to_string(), "basement".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,44 @@
+// Sensor names end up in MQTT topics and Home Assistant entity ids, which only take
+// letters, digits and underscores. Names built from interface, mount point or hwmon
+// device names are passed through here: "br-3f2a.100" -> "br_3f2a_100"
+pub fn sanitize_sensor_name(name: &str) -> String {
+    let mut sanitized = String::with_capacity(name.len());
+    for c in name.chars() {
+        let c = if matches!(c, '-' | '.' | ':' | ' ') { '_' } else { c };
+        if c == '_' && (sanitized.is_empty() || sanitized.ends_with('_')) {
+            continue;
+        }
+        sanitized.push(c);
+    }
+    sanitized.trim_end_matches('_').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_sensor_name() {
+        assert_eq!(sanitize_sensor_name("k10temp_1"), "k10temp_1");
+        assert_eq!(sanitize_sensor_name("net_br-abcdef123456_rx_kbps"), "net_br_abcdef123456_rx_kbps");
+        assert_eq!(sanitize_sensor_name("net_eth0.100_link_status"), "net_eth0_100_link_status");
+        assert_eq!(sanitize_sensor_name("net_veth-a1:b2_tx_bytes"), "net_veth_a1_b2_tx_bytes");
+        assert_eq!(sanitize_sensor_name("disk_usage_mnt_my disk"), "disk_usage_mnt_my_disk");
+    }
+
+    #[test]
+    fn test_sanitize_sensor_name_collapses_and_trims() {
+        assert_eq!(sanitize_sensor_name("cros-ec__1_fan"), "cros_ec_1_fan");
+        assert_eq!(sanitize_sensor_name("disk_usage_-_-_backup"), "disk_usage_backup");
+        assert_eq!(sanitize_sensor_name("-.:lead"), "lead");
+        assert_eq!(sanitize_sensor_name("trail. -"), "trail");
+        assert_eq!(sanitize_sensor_name("--"), "");
+        assert_eq!(sanitize_sensor_name(""), "");
+    }
+
+    #[test]
+    fn test_sanitize_sensor_name_keeps_other_characters() {
+        assert_eq!(sanitize_sensor_name("Wlan0_Signal"), "Wlan0_Signal");
+        assert_eq!(sanitize_sensor_name("temp°"), "temp°");
+    }
+}
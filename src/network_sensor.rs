@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use sysinfo::Networks;
+use crate::config::SensorsConfig;
+use crate::naming::sanitize_sensor_name;
+use crate::sensors::{CollectFuture, SensorCollector, SystemSensor, SystemSensorType};
+use crate::sysfs::read_attribute;
+
+const LOOPBACK_INTERFACE: &str = "lo";
+const NET_CLASS_PATH: &str = "/sys/class/net";
+const BYTES_PER_KB: f64 = 1024.0;
+
+// Byte counters from the previous cycle, keyed by interface name
+#[derive(Debug, Clone, Copy)]
+struct InterfaceReading {
+    received: u64,
+    transmitted: u64,
+    at: Instant,
+}
+
+static PREVIOUS_READINGS: OnceLock<Mutex<HashMap<String, InterfaceReading>>> = OnceLock::new();
+
+// Per-interface throughput of the interfaces selected in sensors.network_interfaces
+pub struct NetworkCollector {
+    pub sensors_config: SensorsConfig,
+    pub read_timeout: Duration,
+}
+
+impl SensorCollector for NetworkCollector {
+    fn collect(&self) -> CollectFuture<'_> {
+        Box::pin(collect_network_stats(&self.sensors_config, self.read_timeout))
+    }
+
+    fn name(&self) -> &str {
+        "network"
+    }
+}
+
+async fn collect_network_stats(sensors_config: &SensorsConfig, read_timeout: Duration) -> Vec<SystemSensor> {
+    let networks = Networks::new_with_refreshed_list();
+
+    let mut interfaces: Vec<_> = networks
+        .iter()
+        .filter(|(name, _)| should_monitor(name, sensors_config))
+        .collect();
+    interfaces.sort_by(|a, b| a.0.cmp(b.0));
+
+    // Read before locking, the readings lock can't be held across an await
+    let mut link_states = HashMap::new();
+    for (name, _) in &interfaces {
+        let operstate = read_attribute(&Path::new(NET_CLASS_PATH).join(name).join("operstate"), read_timeout).await;
+        if let Some(link_up) = operstate.as_deref().and_then(parse_operstate) {
+            link_states.insert(name.to_string(), link_up);
+        }
+    }
+
+    let readings = PREVIOUS_READINGS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut readings = readings.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let now = Instant::now();
+
+    let mut sensors = Vec::new();
+    for (name, data) in interfaces {
+        let reading = InterfaceReading {
+            received: data.total_received(),
+            transmitted: data.total_transmitted(),
+            at: now,
+        };
+        let previous = readings.insert(name.to_string(), reading);
+        let (rx_rate, tx_rate) = interface_rates(previous, reading);
+
+        sensors.push(rate_sensor(name, "rx", rx_rate, SystemSensorType::NetworkRx));
+        sensors.push(rate_sensor(name, "tx", tx_rate, SystemSensorType::NetworkTx));
+        sensors.push(total_sensor(name, "rx", reading.received, SystemSensorType::NetworkRxTotal));
+        sensors.push(total_sensor(name, "tx", reading.transmitted, SystemSensorType::NetworkTxTotal));
+        if let Some(link_up) = link_states.get(name.as_str()) {
+            sensors.push(link_status_sensor(name, *link_up));
+        }
+    }
+
+    sensors
+}
+
+// An explicit interface list wins; otherwise everything except loopback unless opted in
+pub fn should_monitor(interface: &str, sensors_config: &SensorsConfig) -> bool {
+    if !sensors_config.network_interfaces.is_empty() {
+        return sensors_config.network_interfaces.iter().any(|name| name == interface);
+    }
+
+    interface != LOOPBACK_INTERFACE || sensors_config.include_loopback
+}
+
+// Rates in KB/s; the first cycle has nothing to compare against and reports 0
+fn interface_rates(previous: Option<InterfaceReading>, current: InterfaceReading) -> (f64, f64) {
+    let Some(previous) = previous else {
+        return (0.0, 0.0);
+    };
+
+    let elapsed = current.at.duration_since(previous.at).as_secs_f64();
+    if elapsed <= 0.0 {
+        return (0.0, 0.0);
+    }
+
+    // Counters go backwards when an interface is recreated, treat that as a fresh start
+    let rate = |now: u64, before: u64| {
+        let kbps = now.saturating_sub(before) as f64 / BYTES_PER_KB / elapsed;
+        (kbps * 10.0).round() / 10.0
+    };
+
+    (
+        rate(current.received, previous.received),
+        rate(current.transmitted, previous.transmitted),
+    )
+}
+
+fn rate_sensor(interface: &str, direction: &str, value: f64, sensor_type: SystemSensorType) -> SystemSensor {
+    SystemSensor {
+        name: sanitize_sensor_name(&format!("net_{}_{}_kbps", interface, direction)),
+        label: None,
+        value,
+        unit: "KB/s".to_string(),
+        sensor_type,
+        attributes: HashMap::new(),
+        extra: None,
+    }
+}
+
+// "unknown" is what loopback and many tunnel drivers report, which says nothing about the link
+fn parse_operstate(operstate: &str) -> Option<bool> {
+    match operstate.trim() {
+        "up" => Some(true),
+        "unknown" => None,
+        _ => Some(false), // down, dormant, lowerlayerdown, notpresent, testing
+    }
+}
+
+// Published as a binary_sensor: 1.0 when the link is up, 0.0 otherwise
+fn link_status_sensor(interface: &str, link_up: bool) -> SystemSensor {
+    SystemSensor {
+        name: sanitize_sensor_name(&format!("net_{}_link_status", interface)),
+        label: None,
+        value: if link_up { 1.0 } else { 0.0 },
+        unit: "".to_string(),
+        sensor_type: SystemSensorType::NetworkLinkStatus,
+        attributes: HashMap::new(),
+        extra: None,
+    }
+}
+
+// Bytes transferred since boot, for bandwidth accounting
+fn total_sensor(interface: &str, direction: &str, bytes: u64, sensor_type: SystemSensorType) -> SystemSensor {
+    SystemSensor {
+        name: sanitize_sensor_name(&format!("net_{}_{}_bytes", interface, direction)),
+        label: None,
+        value: bytes as f64,
+        unit: "B".to_string(),
+        sensor_type,
+        attributes: HashMap::new(),
+        extra: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_should_monitor() {
+        let config = SensorsConfig::default();
+        assert!(should_monitor("eth0", &config));
+        assert!(!should_monitor("lo", &config));
+
+        let config = SensorsConfig {
+            include_loopback: true,
+            ..SensorsConfig::default()
+        };
+        assert!(should_monitor("lo", &config));
+
+        let config = SensorsConfig {
+            network_interfaces: vec!["wlan0".to_string()],
+            ..SensorsConfig::default()
+        };
+        assert!(should_monitor("wlan0", &config));
+        assert!(!should_monitor("eth0", &config));
+    }
+
+    #[test]
+    fn test_parse_operstate() {
+        assert_eq!(parse_operstate("up\n"), Some(true));
+        assert_eq!(parse_operstate("down"), Some(false));
+        assert_eq!(parse_operstate("lowerlayerdown"), Some(false));
+        assert_eq!(parse_operstate("unknown"), None);
+    }
+
+    #[test]
+    fn test_interface_rates() {
+        let start = Instant::now();
+        let before = InterfaceReading { received: 0, transmitted: 4096, at: start };
+        let after = InterfaceReading {
+            received: 20480,
+            transmitted: 2048,
+            at: start + Duration::from_secs(2),
+        };
+
+        assert_eq!(interface_rates(None, after), (0.0, 0.0));
+        assert_eq!(interface_rates(Some(before), after), (10.0, 0.0));
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use crate::config::OneWireSensorConfig;
+use crate::sensors::{CollectFuture, SensorCollector, SystemSensor};
+use crate::sensors::SystemSensorType::Temperature;
+use crate::sysfs::read_attribute;
+
+const W1_DEVICES_PATH: &str = "/sys/bus/w1/devices";
+// Family code of the DS18B20 temperature probe
+const DS18B20_PREFIX: &str = "28-";
+const MILLIDEGREE_TO_CELSIUS: f64 = 1000.0;
+
+// DS18B20 probes on the w1 bus
+pub struct OneWireCollector {
+    pub sensor_configs: Vec<OneWireSensorConfig>,
+    pub read_timeout: Duration,
+}
+
+impl SensorCollector for OneWireCollector {
+    fn collect(&self) -> CollectFuture<'_> {
+        Box::pin(collect_onewire(&self.sensor_configs, self.read_timeout))
+    }
+
+    fn name(&self) -> &str {
+        "onewire"
+    }
+}
+
+// Systems without the w1 bus (or without probes) yield no sensors
+async fn collect_onewire(sensor_configs: &[OneWireSensorConfig], read_timeout: Duration) -> Vec<SystemSensor> {
+    let Ok(entries) = fs::read_dir(W1_DEVICES_PATH) else {
+        return Vec::new();
+    };
+    let mut ids: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|id| id.starts_with(DS18B20_PREFIX))
+        .collect();
+    ids.sort();
+
+    let mut sensors = Vec::new();
+    for id in ids {
+        let path = Path::new(W1_DEVICES_PATH).join(&id).join("w1_slave");
+        let Some(content) = read_attribute(&path, read_timeout).await else {
+            continue;
+        };
+        let Some(temperature) = parse_w1_slave(&id, &content) else {
+            continue;
+        };
+        let label = sensor_configs
+            .iter()
+            .find(|config| config.id == id)
+            .and_then(|config| config.label.clone());
+        sensors.push(SystemSensor {
+            name: format!("onewire_{}_temperature", id.replace('-', "")),
+            label,
+            value: temperature,
+            unit: "°C".to_string(),
+            sensor_type: Temperature,
+            attributes: HashMap::new(),
+            extra: None,
+        });
+    }
+    sensors
+}
+
+// "72 01 4b 46 7f ff 0e 10 57 : crc=57 YES"
+// "72 01 4b 46 7f ff 0e 10 57 t=23125"
+// A failed CRC is a transient bus error and the reading is simply skipped this cycle
+fn parse_w1_slave(id: &str, content: &str) -> Option<f64> {
+    let mut lines = content.lines();
+    if !lines.next()?.trim_end().ends_with("YES") {
+        tracing::debug!(id = %id, "1-Wire CRC check failed");
+        return None;
+    }
+    let millidegrees = lines
+        .next()
+        .and_then(|line| line.split_once("t="))
+        .and_then(|(_, value)| value.trim().parse::<i64>().ok());
+    match millidegrees {
+        Some(millidegrees) => Some(millidegrees as f64 / MILLIDEGREE_TO_CELSIUS),
+        None => {
+            tracing::warn!(id = %id, "Failed to parse 1-Wire temperature");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_w1_slave() {
+        let valid = "72 01 4b 46 7f ff 0e 10 57 : crc=57 YES\n72 01 4b 46 7f ff 0e 10 57 t=23125\n";
+        assert_eq!(parse_w1_slave("28-0316a2795cff", valid), Some(23.125));
+
+        let negative = "5e ff 4b 46 7f ff 0c 10 1c : crc=1c YES\n5e ff 4b 46 7f ff 0c 10 1c t=-10125\n";
+        assert_eq!(parse_w1_slave("28-0316a2795cff", negative), Some(-10.125));
+
+        let bad_crc = "72 01 4b 46 7f ff 0e 10 57 : crc=00 NO\n72 01 4b 46 7f ff 0e 10 57 t=23125\n";
+        assert_eq!(parse_w1_slave("28-0316a2795cff", bad_crc), None);
+
+        let missing_value = "72 01 4b 46 7f ff 0e 10 57 : crc=57 YES\n72 01 4b 46 7f ff 0e 10 57\n";
+        assert_eq!(parse_w1_slave("28-0316a2795cff", missing_value), None);
+    }
+}
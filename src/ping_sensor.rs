@@ -0,0 +1,168 @@
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use surge_ping::{Client, Config, ICMP, PingIdentifier, PingSequence};
+use tokio::task::{JoinHandle, JoinSet};
+use crate::config::PingConfig;
+use crate::naming::sanitize_sensor_name;
+use crate::sensors::SystemSensor;
+use crate::sensors::SystemSensorType::PingLatency;
+
+// Published instead of a latency when the target didn't answer, so a graph shows the
+// outage as a spike rather than a gap
+pub const PING_FAILED_MS: f64 = 9999.0;
+const PING_PAYLOAD: [u8; 56] = [0; 56];
+
+// Latest round trip per target, written by the ping task and read by every collect cycle
+static PING_RESULTS: OnceLock<Mutex<HashMap<String, f64>>> = OnceLock::new();
+// Targets currently failing, so an outage is logged once rather than every interval
+static FAILING_TARGETS: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+// Pings run on their own interval, a slow or unreachable target never delays the publish
+// loop. Unprivileged ICMP sockets need net.ipv4.ping_group_range to cover the daemon's
+// group, otherwise CAP_NET_RAW
+pub fn spawn_ping_task(config: PingConfig) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let clients = match (Client::new(&Config::default()), Client::new(&Config::builder().kind(ICMP::V6).build())) {
+            (Ok(v4), Ok(v6)) => Arc::new((v4, v6)),
+            (Err(e), _) | (_, Err(e)) => {
+                tracing::error!(error = %e, "Failed to open an ICMP socket, ping sensors are disabled");
+                return;
+            }
+        };
+        let timeout = Duration::from_millis(config.timeout_ms);
+        let mut interval = tokio::time::interval(Duration::from_secs(config.interval_secs));
+        let mut sequence: u16 = 0;
+        loop {
+            interval.tick().await;
+            let mut pings = JoinSet::new();
+            for (index, target) in config.targets.iter().cloned().enumerate() {
+                let clients = Arc::clone(&clients);
+                // Identifiers only have to be unique among this daemon's pingers
+                let ident = PingIdentifier((std::process::id() as u16).wrapping_add(index as u16));
+                pings.spawn(async move {
+                    let latency = ping_target(&clients, &target, ident, PingSequence(sequence), timeout).await;
+                    (target, latency)
+                });
+            }
+            while let Some(Ok((target, latency))) = pings.join_next().await {
+                store_result(&target, latency);
+            }
+            sequence = sequence.wrapping_add(1);
+        }
+    })
+}
+
+async fn ping_target(
+    (v4, v6): &(Client, Client),
+    target: &str,
+    ident: PingIdentifier,
+    sequence: PingSequence,
+    timeout: Duration,
+) -> Result<f64, String> {
+    let address = resolve_target(target).await?;
+    let client = if address.is_ipv4() { v4 } else { v6 };
+    let mut pinger = client.pinger(address, ident).await;
+    pinger.timeout(timeout);
+    let (_, rtt) = pinger.ping(sequence, &PING_PAYLOAD).await.map_err(|e| e.to_string())?;
+    Ok(rtt.as_secs_f64() * 1000.0)
+}
+
+// Resolved on every ping so a changed DNS record is picked up
+async fn resolve_target(target: &str) -> Result<IpAddr, String> {
+    if let Ok(address) = target.parse() {
+        return Ok(address);
+    }
+    tokio::net::lookup_host((target, 0))
+        .await
+        .map_err(|e| format!("failed to resolve: {}", e))?
+        .next()
+        .map(|address| address.ip())
+        .ok_or_else(|| "no address found".to_string())
+}
+
+fn store_result(target: &str, latency: Result<f64, String>) {
+    let failing = FAILING_TARGETS.get_or_init(|| Mutex::new(HashSet::new()));
+    let mut failing = failing.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let value = match latency {
+        Ok(latency) => {
+            if failing.remove(target) {
+                tracing::info!(target = %target, "Ping target is answering again");
+            }
+            latency
+        }
+        Err(e) => {
+            if failing.insert(target.to_string()) {
+                tracing::warn!(target = %target, error = %e, "Ping failed");
+            }
+            PING_FAILED_MS
+        }
+    };
+    drop(failing);
+
+    let results = PING_RESULTS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut results = results.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    results.insert(target.to_string(), value);
+}
+
+// Targets that haven't completed their first ping yet are left out
+pub fn collect_ping(config: &PingConfig) -> Vec<SystemSensor> {
+    let Some(results) = PING_RESULTS.get() else {
+        return Vec::new();
+    };
+    let results = results.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    config
+        .targets
+        .iter()
+        .filter_map(|target| Some(ping_sensor(target, *results.get(target)?)))
+        .collect()
+}
+
+fn ping_sensor(target: &str, latency_ms: f64) -> SystemSensor {
+    SystemSensor {
+        name: ping_sensor_name(target),
+        label: Some(format!("Ping {}", target)),
+        value: latency_ms,
+        unit: "ms".to_string(),
+        sensor_type: PingLatency,
+        attributes: HashMap::from([("target".to_string(), target.to_string())]),
+        extra: None,
+    }
+}
+
+// "8.8.8.8" -> "ping_8_8_8_8_latency_ms", "Router.lan" -> "ping_router_lan_latency_ms"
+fn ping_sensor_name(target: &str) -> String {
+    sanitize_sensor_name(&format!("ping_{}_latency_ms", target.to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ping_sensor_name() {
+        assert_eq!(ping_sensor_name("8.8.8.8"), "ping_8_8_8_8_latency_ms");
+        assert_eq!(ping_sensor_name("Router.lan"), "ping_router_lan_latency_ms");
+        assert_eq!(ping_sensor_name("fe80::1"), "ping_fe80_1_latency_ms");
+    }
+
+    #[test]
+    fn test_collect_ping() {
+        let config = PingConfig {
+            targets: vec!["192.0.2.1".to_string(), "192.0.2.2".to_string(), "192.0.2.3".to_string()],
+            ..PingConfig::default()
+        };
+        store_result("192.0.2.1", Ok(12.5));
+        store_result("192.0.2.2", Err("timeout".to_string()));
+
+        let sensors = collect_ping(&config);
+        let values: Vec<(&str, f64)> = sensors.iter().map(|s| (s.name.as_str(), s.value)).collect();
+        assert_eq!(
+            values,
+            [("ping_192_0_2_1_latency_ms", 12.5), ("ping_192_0_2_2_latency_ms", PING_FAILED_MS)]
+        );
+        assert_eq!(sensors[0].unit, "ms");
+        assert_eq!(sensors[0].label.as_deref(), Some("Ping 192.0.2.1"));
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use crate::hwmon_devices::{discover_hwmon_devices, HwmonDevice};
+use crate::sensors::{CollectFuture, SensorCollector, SystemSensor};
+use crate::sysfs::read_attribute;
+use crate::sensors::SystemSensorType::Power;
+
+const POWER_FILE_PREFIX: &str = "power";
+const POWER_FILE_SUFFIX: &str = "_input";
+const MICROWATT_TO_WATT: f64 = 1_000_000.0;
+
+// hwmon power*_input readings, e.g. GPU board power
+pub struct PowerCollector {
+    pub hwmon_cache_ttl: Duration,
+    pub read_timeout: Duration,
+}
+
+impl SensorCollector for PowerCollector {
+    fn collect(&self) -> CollectFuture<'_> {
+        Box::pin(collect_all_power(self.hwmon_cache_ttl, self.read_timeout))
+    }
+
+    fn name(&self) -> &str {
+        "power"
+    }
+}
+
+async fn collect_all_power(hwmon_cache_ttl: Duration, read_timeout: Duration) -> Vec<SystemSensor> {
+    let mut sensors = Vec::new();
+
+    match discover_hwmon_devices(hwmon_cache_ttl) {
+        Ok(devices) => {
+            for device in devices {
+                let device_sensors = scan_device_power(&device, read_timeout).await;
+                sensors.extend(device_sensors);
+            }
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to discover hwmon devices");
+        }
+    }
+
+    sensors
+}
+
+async fn scan_device_power(device: &HwmonDevice, read_timeout: Duration) -> Vec<SystemSensor> {
+    let mut sensors = Vec::new();
+
+    match fs::read_dir(&device.path) {
+        Ok(entries) => {
+            for entry in entries.flatten() {
+                if let Some(sensor) = process_power_file(&entry.path(), device, read_timeout).await {
+                    sensors.push(sensor);
+                }
+            }
+        }
+        Err(e) => {
+            tracing::warn!(device = %device.path.display(), error = %e, "Failed to read device directory");
+        }
+    }
+
+    sensors
+}
+
+async fn process_power_file(file_path: &Path, device: &HwmonDevice, read_timeout: Duration) -> Option<SystemSensor> {
+    let filename = file_path.file_name()?.to_string_lossy();
+    let power_number = extract_power_number(&filename)?;
+
+    let power = read_power_value(file_path, read_timeout).await?;
+    let sensor_name = format!("{}_{}_power", device.name, power_number);
+    let label = get_power_label(file_path, read_timeout).await;
+
+    Some(SystemSensor {
+        name: sensor_name,
+        label,
+        value: power,
+        unit: "W".to_string(),
+        sensor_type: Power,
+        attributes: HashMap::new(),
+        extra: None,
+    })
+}
+
+async fn read_power_value(file_path: &Path, read_timeout: Duration) -> Option<f64> {
+    let power_raw = read_attribute(file_path, read_timeout).await?;
+    let power_microwatts = power_raw.trim().parse::<f64>().ok()?;
+    Some(power_microwatts / MICROWATT_TO_WATT)
+}
+
+// "power1_input" -> "1"; power1_average, power1_cap etc. are skipped
+fn extract_power_number(filename: &str) -> Option<String> {
+    let number = filename
+        .strip_prefix(POWER_FILE_PREFIX)?
+        .strip_suffix(POWER_FILE_SUFFIX)?;
+
+    if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) {
+        Some(number.to_string())
+    } else {
+        None
+    }
+}
+
+async fn get_power_label(file_path: &Path, read_timeout: Duration) -> Option<String> {
+    let filename = file_path.file_name()?.to_string_lossy();
+    let label_filename = filename.replace(POWER_FILE_SUFFIX, "_label");
+    let label_path = file_path.with_file_name(label_filename);
+    read_attribute(&label_path, read_timeout).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_power_number() {
+        assert_eq!(extract_power_number("power1_input"), Some("1".to_string()));
+        assert_eq!(extract_power_number("power1_average"), None);
+        assert_eq!(extract_power_number("power_input"), None);
+    }
+
+    #[tokio::test]
+    async fn test_process_power_file() {
+        let dir = std::env::temp_dir().join("orbiq_power_sensor_test");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("power1_input"), "15000000\n").unwrap();
+        fs::write(dir.join("power1_label"), "PPT\n").unwrap();
+
+        let device = HwmonDevice {
+            path: dir.clone(),
+            name: "amdgpu".to_string(),
+        };
+        let sensor = process_power_file(&dir.join("power1_input"), &device, Duration::from_millis(500)).await;
+        fs::remove_dir_all(&dir).unwrap();
+
+        let sensor = sensor.unwrap();
+        assert_eq!(sensor.name, "amdgpu_1_power");
+        assert_eq!(sensor.label.as_deref(), Some("PPT"));
+        assert_eq!(sensor.value, 15.0);
+        assert_eq!(sensor.unit, "W");
+    }
+}
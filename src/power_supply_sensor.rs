@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use crate::sensors::{CollectFuture, SensorCollector, SystemSensor, SystemSensorType};
+use crate::sysfs::{read_attribute, read_number};
+
+const POWER_SUPPLY_BASE_PATH: &str = "/sys/class/power_supply";
+const MICRO_TO_BASE: f64 = 1_000_000.0;
+
+// Batteries and AC adapters under /sys/class/power_supply
+pub struct PowerSupplyCollector {
+    pub read_timeout: Duration,
+}
+
+impl SensorCollector for PowerSupplyCollector {
+    fn collect(&self) -> CollectFuture<'_> {
+        Box::pin(collect_all_power_supplies(self.read_timeout))
+    }
+
+    fn name(&self) -> &str {
+        "power_supply"
+    }
+}
+
+async fn collect_all_power_supplies(read_timeout: Duration) -> Vec<SystemSensor> {
+    // Desktops and servers usually have no power_supply class at all
+    let Ok(entries) = fs::read_dir(POWER_SUPPLY_BASE_PATH) else {
+        return Vec::new();
+    };
+
+    let mut supplies: Vec<_> = entries.flatten().map(|entry| entry.path()).collect();
+    supplies.sort();
+
+    let mut sensors = Vec::new();
+    for path in &supplies {
+        sensors.extend(power_supply_sensors(path, read_timeout).await);
+    }
+    sensors
+}
+
+async fn power_supply_sensors(path: &Path, read_timeout: Duration) -> Vec<SystemSensor> {
+    let Some(supply_name) = path.file_name().map(|n| n.to_string_lossy().to_lowercase()) else {
+        return Vec::new();
+    };
+
+    match read_attribute(&path.join("type"), read_timeout).await.as_deref() {
+        Some("Battery") => battery_sensors(path, &supply_name, read_timeout).await,
+        Some("Mains") => mains_sensors(path, &supply_name, read_timeout).await,
+        _ => Vec::new(),
+    }
+}
+
+async fn battery_sensors(path: &Path, supply_name: &str, read_timeout: Duration) -> Vec<SystemSensor> {
+    let mut sensors = Vec::new();
+
+    if let Some(capacity) = read_number(&path.join("capacity"), read_timeout).await {
+        sensors.push(sensor(
+            format!("battery_{}_percent", supply_name),
+            capacity,
+            "%",
+            SystemSensorType::BatteryPercent,
+        ));
+    }
+
+    // Charging, Discharging, Full, Not charging, Unknown
+    if let Some(status) = read_attribute(&path.join("status"), read_timeout).await {
+        let mut state = sensor(
+            format!("battery_{}_state", supply_name),
+            0.0,
+            "",
+            SystemSensorType::BatteryState,
+        );
+        state.extra = Some(status);
+        sensors.push(state);
+    }
+
+    // energy_* is reported in µWh, voltage_now in µV
+    for file in ["energy_now", "energy_full"] {
+        if let Some(energy) = read_number(&path.join(file), read_timeout).await {
+            sensors.push(sensor(
+                format!("battery_{}_{}_wh", supply_name, file),
+                (energy / MICRO_TO_BASE * 100.0).round() / 100.0,
+                "Wh",
+                SystemSensorType::BatteryEnergy,
+            ));
+        }
+    }
+
+    if let Some(voltage) = read_number(&path.join("voltage_now"), read_timeout).await {
+        sensors.push(sensor(
+            format!("battery_{}_voltage", supply_name),
+            (voltage / MICRO_TO_BASE * 100.0).round() / 100.0,
+            "V",
+            SystemSensorType::Voltage,
+        ));
+    }
+
+    sensors
+}
+
+// AC adapters only report whether grid power is present, published as 1/0
+async fn mains_sensors(path: &Path, supply_name: &str, read_timeout: Duration) -> Vec<SystemSensor> {
+    read_number(&path.join("online"), read_timeout)
+        .await
+        .map(|online| {
+            sensor(
+                format!("power_supply_{}_online", supply_name),
+                if online > 0.0 { 1.0 } else { 0.0 },
+                "",
+                SystemSensorType::PowerSupplyOnline,
+            )
+        })
+        .into_iter()
+        .collect()
+}
+
+fn sensor(name: String, value: f64, unit: &str, sensor_type: SystemSensorType) -> SystemSensor {
+    SystemSensor {
+        name,
+        label: None,
+        value,
+        unit: unit.to_string(),
+        sensor_type,
+        attributes: HashMap::new(),
+        extra: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const READ_TIMEOUT: Duration = Duration::from_millis(500);
+
+    #[tokio::test]
+    async fn test_battery_sensors() {
+        let dir = std::env::temp_dir().join("orbiq_power_supply_test").join("BAT0");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("type"), "Battery\n").unwrap();
+        fs::write(dir.join("capacity"), "87\n").unwrap();
+        fs::write(dir.join("status"), "Discharging\n").unwrap();
+        fs::write(dir.join("energy_now"), "45120000\n").unwrap();
+        fs::write(dir.join("voltage_now"), "12345000\n").unwrap();
+
+        let sensors = power_supply_sensors(&dir, READ_TIMEOUT).await;
+        fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+
+        let names: Vec<&str> = sensors.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "battery_bat0_percent",
+                "battery_bat0_state",
+                "battery_bat0_energy_now_wh",
+                "battery_bat0_voltage"
+            ]
+        );
+        assert_eq!(sensors[0].value, 87.0);
+        assert_eq!(sensors[1].extra.as_deref(), Some("Discharging"));
+        assert_eq!(sensors[2].value, 45.12);
+        assert_eq!(sensors[3].value, 12.35);
+    }
+
+    #[tokio::test]
+    async fn test_mains_sensors() {
+        let dir = std::env::temp_dir().join("orbiq_power_supply_mains_test").join("AC");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("type"), "Mains\n").unwrap();
+        fs::write(dir.join("online"), "1\n").unwrap();
+
+        let sensors = power_supply_sensors(&dir, READ_TIMEOUT).await;
+        fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+
+        assert_eq!(sensors.len(), 1);
+        assert_eq!(sensors[0].name, "power_supply_ac_online");
+        assert_eq!(sensors[0].value, 1.0);
+    }
+}
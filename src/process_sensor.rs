@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::sync::{Mutex, OnceLock};
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
+use crate::config::{ProcessMetric, WatchProcessConfig};
+use crate::sensors::{CollectFuture, SensorCollector, SystemSensor};
+use crate::sensors::SystemSensorType::WatchedProcess;
+
+const BYTES_PER_MB: f64 = 1024.0 * 1024.0;
+
+// Process CPU usage is measured between two refreshes, so the same System is kept across cycles
+static PROCESS_SYSTEM: OnceLock<Mutex<System>> = OnceLock::new();
+
+#[derive(Debug, Default, PartialEq)]
+struct ProcessTotals {
+    cpu_usage: f64,
+    memory_bytes: u64,
+    threads: u64,
+}
+
+// Processes from [[watch_processes]]; sysinfo reads them synchronously
+pub struct ProcessCollector {
+    pub watch_processes: Vec<WatchProcessConfig>,
+}
+
+impl SensorCollector for ProcessCollector {
+    fn collect(&self) -> CollectFuture<'_> {
+        Box::pin(async { collect_watched_processes(&self.watch_processes) })
+    }
+
+    fn name(&self) -> &str {
+        "process"
+    }
+}
+
+fn collect_watched_processes(watch_processes: &[WatchProcessConfig]) -> Vec<SystemSensor> {
+    if watch_processes.is_empty() {
+        return Vec::new();
+    }
+
+    let system = PROCESS_SYSTEM.get_or_init(|| Mutex::new(System::new()));
+    let mut system = system.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing().with_cpu().with_memory().with_tasks(),
+    );
+
+    let mut sensors = Vec::new();
+    for watch in watch_processes {
+        // Threads show up as processes of the same name on Linux, only count thread group leaders
+        let totals = system
+            .processes_by_name(OsStr::new(&watch.name))
+            .filter(|process| process.thread_kind().is_none())
+            .fold(ProcessTotals::default(), |mut totals, process| {
+                totals.cpu_usage += process.cpu_usage() as f64;
+                totals.memory_bytes += process.memory();
+                totals.threads += process.tasks().map_or(0, |tasks| tasks.len() as u64) + 1;
+                totals
+            });
+        sensors.extend(process_metric_sensors(watch, &totals));
+    }
+    sensors
+}
+
+// An absent process publishes zeros so Home Assistant keeps the entity around
+fn process_metric_sensors(watch: &WatchProcessConfig, totals: &ProcessTotals) -> Vec<SystemSensor> {
+    let prefix = format!("proc_{}", sanitize_process_name(&watch.name));
+
+    watch
+        .metrics
+        .iter()
+        .map(|metric| {
+            let (suffix, value, unit) = match metric {
+                ProcessMetric::Cpu => ("cpu_usage", round_to_tenth(totals.cpu_usage), "%"),
+                ProcessMetric::MemoryMb => ("memory_mb", round_to_tenth(totals.memory_bytes as f64 / BYTES_PER_MB), "MB"),
+                ProcessMetric::ThreadCount => ("thread_count", totals.threads as f64, ""),
+            };
+            SystemSensor {
+                name: format!("{}_{}", prefix, suffix),
+                label: None,
+                value,
+                unit: unit.to_string(),
+                sensor_type: WatchedProcess,
+                attributes: HashMap::new(),
+                extra: None,
+            }
+        })
+        .collect()
+}
+
+// "java-server" -> "java_server", keeping sensor names and topics slug-friendly
+fn sanitize_process_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect()
+}
+
+fn round_to_tenth(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_metric_sensors() {
+        let watch = WatchProcessConfig {
+            name: "postgres".to_string(),
+            metrics: vec![ProcessMetric::Cpu, ProcessMetric::MemoryMb, ProcessMetric::ThreadCount],
+        };
+        let totals = ProcessTotals {
+            cpu_usage: 12.34,
+            memory_bytes: 512 * 1024 * 1024,
+            threads: 9,
+        };
+
+        let sensors = process_metric_sensors(&watch, &totals);
+        let values: Vec<(&str, f64)> = sensors.iter().map(|s| (s.name.as_str(), s.value)).collect();
+        assert_eq!(
+            values,
+            [
+                ("proc_postgres_cpu_usage", 12.3),
+                ("proc_postgres_memory_mb", 512.0),
+                ("proc_postgres_thread_count", 9.0),
+            ]
+        );
+
+        let absent = process_metric_sensors(&watch, &ProcessTotals::default());
+        assert_eq!(absent.len(), 3);
+        assert!(absent.iter().all(|s| s.value == 0.0));
+    }
+
+    #[test]
+    fn test_sanitize_process_name() {
+        assert_eq!(sanitize_process_name("java-server"), "java_server");
+        assert_eq!(sanitize_process_name("Xorg"), "xorg");
+    }
+}
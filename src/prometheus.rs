@@ -0,0 +1,100 @@
+use crate::sensors::{SharedSensors, SystemSensor};
+use axum::Router;
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use std::fmt::Write;
+use tokio::net::TcpListener;
+
+#[derive(Clone)]
+struct MetricsState {
+    sensors: SharedSensors,
+    device_name: String,
+}
+
+// Metric names may only contain [a-zA-Z0-9_:]
+fn metric_name(sensor_name: &str) -> String {
+    let name: String = sensor_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == ':' { c } else { '_' })
+        .collect();
+    format!("orbiq_{}", name)
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+// One gauge per numeric sensor in the Prometheus text exposition format
+pub fn render_metrics(sensors: &[SystemSensor], device_name: &str) -> String {
+    let mut output = String::new();
+    for sensor in sensors.iter().filter(|s| s.extra.is_none()) {
+        let name = metric_name(&sensor.name);
+        let _ = writeln!(output, "# TYPE {} gauge", name);
+        let _ = writeln!(
+            output,
+            "{}{{device=\"{}\",unit=\"{}\"}} {}",
+            name,
+            escape_label(device_name),
+            escape_label(&sensor.unit),
+            sensor.value
+        );
+    }
+    output
+}
+
+async fn metrics(State(state): State<MetricsState>) -> impl IntoResponse {
+    let body = match state.sensors.read() {
+        Ok(sensors) => render_metrics(&sensors, &state.device_name),
+        Err(poisoned) => render_metrics(&poisoned.into_inner(), &state.device_name),
+    };
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
+
+// The listener is bound by the caller so a port conflict is reported at startup
+pub async fn serve_metrics(listener: TcpListener, sensors: SharedSensors, device_name: String) {
+    let app = Router::new()
+        .route("/metrics", get(metrics))
+        .with_state(MetricsState { sensors, device_name });
+    if let Err(e) = axum::serve(listener, app).await {
+        tracing::error!(error = %e, "Prometheus metrics server stopped");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sensors::SystemSensorType;
+    use std::collections::HashMap;
+
+    fn sensor(name: &str, value: f64, unit: &str, extra: Option<&str>) -> SystemSensor {
+        SystemSensor {
+            name: name.to_string(),
+            label: None,
+            value,
+            unit: unit.to_string(),
+            sensor_type: SystemSensorType::Temperature,
+            attributes: HashMap::new(),
+            extra: extra.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_render_metrics() {
+        let sensors = [
+            sensor("k10temp_1", 45.5, "°C", None),
+            sensor("net_eth0.10_rx_kbps", 12.0, "KB/s", None),
+            sensor("battery_bat0_state", 0.0, "", Some("Charging")),
+        ];
+
+        let output = render_metrics(&sensors, "my \"pc\"");
+        assert_eq!(
+            output,
+            "# TYPE orbiq_k10temp_1 gauge\n\
+             orbiq_k10temp_1{device=\"my \\\"pc\\\"\",unit=\"°C\"} 45.5\n\
+             # TYPE orbiq_net_eth0_10_rx_kbps gauge\n\
+             orbiq_net_eth0_10_rx_kbps{device=\"my \\\"pc\\\"\",unit=\"KB/s\"} 12\n"
+        );
+    }
+}
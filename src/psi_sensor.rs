@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+use crate::sensors::{CollectFuture, SensorCollector, SystemSensor};
+use crate::sysfs::read_attribute;
+use crate::sensors::SystemSensorType::PressureStall;
+
+const PRESSURE_BASE_PATH: &str = "/proc/pressure";
+const RESOURCES: [&str; 3] = ["cpu", "memory", "io"];
+
+// CPU, memory and IO pressure from /proc/pressure
+pub struct PressureStallCollector {
+    pub read_timeout: Duration,
+}
+
+impl SensorCollector for PressureStallCollector {
+    fn collect(&self) -> CollectFuture<'_> {
+        Box::pin(collect_pressure_stall(self.read_timeout))
+    }
+
+    fn name(&self) -> &str {
+        "psi"
+    }
+}
+
+// Kernels before 4.20 (or built without CONFIG_PSI) have no /proc/pressure, which yields no sensors
+async fn collect_pressure_stall(read_timeout: Duration) -> Vec<SystemSensor> {
+    let base = Path::new(PRESSURE_BASE_PATH);
+    if !base.exists() {
+        return Vec::new();
+    }
+
+    let mut sensors = Vec::new();
+    for resource in RESOURCES {
+        if let Some(content) = read_attribute(&base.join(resource), read_timeout).await {
+            sensors.extend(pressure_sensors(resource, &content));
+        }
+    }
+    sensors
+}
+
+// Each line looks like "some avg10=0.12 avg60=0.05 avg300=0.01 total=123456"; the
+// cumulative total (microseconds) is left out since the averages already carry the rate
+fn pressure_sensors(resource: &str, content: &str) -> Vec<SystemSensor> {
+    let mut sensors = Vec::new();
+    for line in content.lines() {
+        let mut fields = line.split_whitespace();
+        let Some(kind @ ("some" | "full")) = fields.next() else {
+            continue;
+        };
+        for field in fields {
+            let Some((window, value)) = field.split_once('=') else {
+                continue;
+            };
+            if !window.starts_with("avg") {
+                continue;
+            }
+            let Ok(value) = value.parse::<f64>() else {
+                continue;
+            };
+            sensors.push(SystemSensor {
+                name: format!("psi_{}_{}_{}", resource, kind, window),
+                label: None,
+                value,
+                unit: "%".to_string(),
+                sensor_type: PressureStall,
+                attributes: HashMap::new(),
+                extra: None,
+            });
+        }
+    }
+    sensors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pressure_sensors() {
+        let content = "some avg10=1.25 avg60=0.50 avg300=0.10 total=123456\n\
+                       full avg10=0.75 avg60=0.20 avg300=0.05 total=65432\n";
+        let sensors = pressure_sensors("memory", content);
+
+        let names: Vec<&str> = sensors.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "psi_memory_some_avg10",
+                "psi_memory_some_avg60",
+                "psi_memory_some_avg300",
+                "psi_memory_full_avg10",
+                "psi_memory_full_avg60",
+                "psi_memory_full_avg300",
+            ]
+        );
+        assert_eq!(sensors[0].value, 1.25);
+        assert_eq!(sensors[3].value, 0.75);
+        assert!(pressure_sensors("cpu", "garbage\n").is_empty());
+    }
+}
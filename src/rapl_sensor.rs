@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use crate::sensors::{CollectFuture, SensorCollector, SystemSensor};
+use crate::sysfs::read_attribute;
+use crate::sensors::SystemSensorType::Power;
+
+const POWERCAP_BASE_PATH: &str = "/sys/class/powercap";
+const RAPL_DIR_PREFIX: &str = "intel-rapl:";
+const MICROJOULE_TO_JOULE: f64 = 1_000_000.0;
+
+// energy_uj is a cumulative counter, so power is derived from the previous
+// reading of each domain, keyed by its sysfs path
+static PREVIOUS_READINGS: OnceLock<Mutex<HashMap<PathBuf, (u64, Instant)>>> = OnceLock::new();
+
+struct RaplDomain {
+    path: PathBuf,
+    sensor_name: String,
+}
+
+// Intel RAPL package and DRAM power, derived from the energy counters
+pub struct RaplCollector {
+    pub read_timeout: Duration,
+}
+
+impl SensorCollector for RaplCollector {
+    fn collect(&self) -> CollectFuture<'_> {
+        Box::pin(collect_all_rapl(self.read_timeout))
+    }
+
+    fn name(&self) -> &str {
+        "rapl"
+    }
+}
+
+async fn collect_all_rapl(read_timeout: Duration) -> Vec<SystemSensor> {
+    let domains = match discover_rapl_domains() {
+        Ok(domains) => domains,
+        // No powercap support (non-Intel CPU, container, ...) is not worth a log line
+        Err(_) => return Vec::new(),
+    };
+
+    // Read everything first, the readings lock can't be held across an await
+    let mut energies = Vec::new();
+    for domain in domains {
+        let Some(energy) = read_u64(&domain.path.join("energy_uj"), read_timeout).await else {
+            continue;
+        };
+        let max_energy = read_u64(&domain.path.join("max_energy_range_uj"), read_timeout).await;
+        energies.push((domain, energy, max_energy));
+    }
+
+    let readings = PREVIOUS_READINGS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut readings = readings.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let now = Instant::now();
+    let mut sensors = Vec::new();
+
+    for (domain, energy, max_energy) in energies {
+        let previous = readings.insert(domain.path.clone(), (energy, now));
+
+        sensors.push(SystemSensor {
+            name: domain.sensor_name,
+            label: None,
+            value: compute_power(previous, energy, max_energy, now),
+            unit: "W".to_string(),
+            sensor_type: Power,
+            attributes: HashMap::new(),
+            extra: None,
+        });
+    }
+
+    sensors
+}
+
+fn discover_rapl_domains() -> Result<Vec<RaplDomain>, std::io::Error> {
+    let mut domains = Vec::new();
+
+    for entry in fs::read_dir(POWERCAP_BASE_PATH)? {
+        let path = entry?.path();
+        let Some(dir_name) = path.file_name().map(|n| n.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let Some(index) = dir_name.strip_prefix(RAPL_DIR_PREFIX) else {
+            continue;
+        };
+        let Ok(domain_name) = fs::read_to_string(path.join("name")) else {
+            continue;
+        };
+
+        domains.push(RaplDomain {
+            sensor_name: rapl_sensor_name(domain_name.trim(), index),
+            path,
+        });
+    }
+
+    Ok(domains)
+}
+
+// Top level domains are named "package-0", subdomains just "core"/"dram"/...,
+// so subdomains borrow the package index from "intel-rapl:0:1"
+fn rapl_sensor_name(domain_name: &str, index: &str) -> String {
+    let domain_name = domain_name.replace('-', "_");
+    match index.split_once(':') {
+        Some((package, _)) => format!("rapl_{}_{}_power", domain_name, package),
+        None => format!("rapl_{}_power", domain_name),
+    }
+}
+
+fn compute_power(
+    previous: Option<(u64, Instant)>,
+    energy: u64,
+    max_energy: Option<u64>,
+    now: Instant,
+) -> f64 {
+    let Some((previous_energy, previous_time)) = previous else {
+        return 0.0;
+    };
+
+    let elapsed = now.duration_since(previous_time).as_secs_f64();
+    if elapsed <= 0.0 {
+        return 0.0;
+    }
+
+    let delta = if energy >= previous_energy {
+        energy - previous_energy
+    } else {
+        match max_energy {
+            // The kernel resets the counter once it reaches max_energy_range_uj
+            Some(max_energy) if max_energy >= previous_energy => {
+                max_energy - previous_energy + energy
+            }
+            _ => return 0.0,
+        }
+    };
+
+    let watts = delta as f64 / MICROJOULE_TO_JOULE / elapsed;
+    (watts * 100.0).round() / 100.0
+}
+
+async fn read_u64(path: &Path, read_timeout: Duration) -> Option<u64> {
+    read_attribute(path, read_timeout).await?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_rapl_sensor_name() {
+        assert_eq!(rapl_sensor_name("package-0", "0"), "rapl_package_0_power");
+        assert_eq!(rapl_sensor_name("dram", "0:2"), "rapl_dram_0_power");
+    }
+
+    #[test]
+    fn test_compute_power() {
+        let start = Instant::now();
+        let later = start + Duration::from_secs(2);
+
+        assert_eq!(compute_power(None, 5_000_000, None, later), 0.0);
+        assert_eq!(compute_power(Some((10_000_000, start)), 40_000_000, None, later), 15.0);
+    }
+
+    #[test]
+    fn test_compute_power_counter_wrap() {
+        let start = Instant::now();
+        let later = start + Duration::from_secs(1);
+
+        let watts = compute_power(Some((99_000_000, start)), 4_000_000, Some(100_000_000), later);
+        assert_eq!(watts, 5.0);
+        assert_eq!(compute_power(Some((99_000_000, start)), 4_000_000, None, later), 0.0);
+    }
+}
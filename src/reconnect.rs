@@ -0,0 +1,153 @@
+use crate::config::MqttConfig;
+use crate::mqtt_failover::MqttFailover;
+use rumqttc::MqttOptions;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::time;
+
+// Exponential backoff between reconnect attempts, with random jitter so instances
+// restarted together don't hit the broker in lockstep
+#[derive(Debug)]
+pub struct Backoff {
+    min: Duration,
+    max: Duration,
+    jitter_factor: f64,
+    current: Duration,
+}
+
+impl Backoff {
+    pub fn from_config(config: &MqttConfig) -> Self {
+        let min = Duration::from_secs(config.reconnect_min_secs);
+        Self {
+            min,
+            max: Duration::from_secs(config.reconnect_max_secs),
+            jitter_factor: config.reconnect_jitter_factor,
+            current: min,
+        }
+    }
+
+    pub fn next_delay(&mut self) -> Duration {
+        self.next_delay_with(fastrand::f64())
+    }
+
+    // `random` in [0, 1) maps to a delay between -jitter_factor and +jitter_factor
+    fn next_delay_with(&mut self, random: f64) -> Duration {
+        let delay = self.current.mul_f64(1.0 + self.jitter_factor * (2.0 * random - 1.0));
+        self.current = (self.current * 2).min(self.max);
+        delay
+    }
+
+    // Called once the broker accepted the connection
+    pub fn reset(&mut self) {
+        self.current = self.min;
+    }
+}
+
+// Connection status of the MQTT event loop, shared with the HTTP API. Every ConnAck is
+// forwarded to the publish task with its session_present flag.
+pub struct ReconnectState {
+    backoff: Backoff,
+    failover: MqttFailover,
+    connected: Arc<AtomicBool>,
+    connack_tx: mpsc::Sender<bool>,
+}
+
+impl ReconnectState {
+    pub fn new(
+        config: &MqttConfig,
+        failover: MqttFailover,
+        connected: Arc<AtomicBool>,
+        connack_tx: mpsc::Sender<bool>,
+    ) -> Self {
+        Self {
+            backoff: Backoff::from_config(config),
+            failover,
+            connected,
+            connack_tx,
+        }
+    }
+
+    pub fn on_connected(&mut self, session_present: bool) {
+        self.connected.store(true, Ordering::Relaxed);
+        self.backoff.reset();
+        self.failover.on_connected();
+        // A full channel already holds a pending notification for the publish task
+        let _ = self.connack_tx.try_send(session_present);
+    }
+
+    // Options of the next broker when the current one has been unreachable for too long.
+    // The new broker has no session, so the publish task re-sends discovery after the ConnAck.
+    pub fn next_broker(&mut self) -> Option<MqttOptions> {
+        let options = self.failover.on_connection_error(Instant::now())?;
+        self.backoff.reset();
+        Some(options)
+    }
+
+    pub fn set_credentials(&mut self, username: String, password: String) -> bool {
+        self.failover.set_credentials(username, password)
+    }
+
+    // The event loop reconnects on its next poll, this only delays that poll
+    pub async fn wait_before_reconnect(&mut self) {
+        self.connected.store(false, Ordering::Relaxed);
+        let delay = self.backoff.next_delay();
+        tracing::info!(delay_secs = delay.as_secs_f64(), "Attempting to reconnect");
+        time::sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DaemonConfig;
+
+    fn backoff() -> Backoff {
+        Backoff::from_config(&MqttConfig::default())
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let mut backoff = backoff();
+        let delays: Vec<u64> = (0..8).map(|_| backoff.next_delay_with(0.5).as_secs()).collect();
+        assert_eq!(delays, [1, 2, 4, 8, 16, 32, 60, 60]);
+
+        backoff.reset();
+        assert_eq!(backoff.next_delay_with(0.5), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_backoff_jitter_bounds() {
+        // The 5th delay is 16s +-25%
+        let fifth_delay = |random| {
+            let mut backoff = backoff();
+            for _ in 0..4 {
+                backoff.next_delay_with(0.5);
+            }
+            backoff.next_delay_with(random)
+        };
+        assert_eq!(fifth_delay(0.0), Duration::from_secs(12));
+        assert_eq!(fifth_delay(1.0), Duration::from_secs(20));
+    }
+
+    #[test]
+    fn test_on_connected_forwards_session_present() {
+        let (connack_tx, mut connack_rx) = mpsc::channel(1);
+        let connected = Arc::new(AtomicBool::new(false));
+        let mut state = ReconnectState::new(
+            &MqttConfig::default(),
+            MqttFailover::from_config(&DaemonConfig::default()).unwrap(),
+            Arc::clone(&connected),
+            connack_tx,
+        );
+
+        state.on_connected(false);
+        // Doesn't block when the publish task hasn't picked up the previous ConnAck yet
+        state.on_connected(true);
+
+        assert!(connected.load(Ordering::Relaxed));
+        assert_eq!(connack_rx.try_recv(), Ok(false));
+        assert!(connack_rx.try_recv().is_err());
+    }
+}
@@ -0,0 +1,198 @@
+use crate::cli::CliArgs;
+use crate::config::{ConfigError, DaemonConfig};
+
+// What a SIGHUP reload changed, and what it could not apply without a restart
+#[derive(Debug, Default, PartialEq)]
+pub struct ReloadSummary {
+    pub changed: Vec<String>,
+    pub restart_required: Vec<String>,
+    pub credentials_changed: bool,
+    pub ping_changed: bool, // The ping task has to be respawned with the new targets
+}
+
+// Re-read the file the running config came from, with the same CLI overrides on top
+pub fn reload_config(current: &DaemonConfig, args: &CliArgs) -> Result<DaemonConfig, ConfigError> {
+    let Some(path) = &current.source_path else {
+        return Err(ConfigError::Invalid(
+            "running on built-in defaults, there is no config file to reload".to_string(),
+        ));
+    };
+
+    let mut reloaded = DaemonConfig::load_from_file(path)?;
+    reloaded.apply_cli_overrides(args)?;
+    Ok(reloaded)
+}
+
+// The broker connection and device name are baked into the MQTT client and the HTTP
+// servers are bound at startup, so those keep their current values; everything else is
+// taken from the reloaded config
+pub fn apply_reload(current: &mut DaemonConfig, mut reloaded: DaemonConfig) -> ReloadSummary {
+    let mut summary = ReloadSummary::default();
+
+    if reloaded.mqtt.broker != current.mqtt.broker {
+        summary.restart_required.push(format!(
+            "mqtt.broker: {} -> {}",
+            current.mqtt.broker, reloaded.mqtt.broker
+        ));
+    }
+    if reloaded.mqtt.port != current.mqtt.port {
+        summary.restart_required.push(format!(
+            "mqtt.port: {} -> {}",
+            current.mqtt.port, reloaded.mqtt.port
+        ));
+    }
+    if reloaded.mqtt.brokers != current.mqtt.brokers {
+        summary.restart_required.push(format!(
+            "mqtt.brokers: {} -> {} entries",
+            current.mqtt.brokers.len(),
+            reloaded.mqtt.brokers.len()
+        ));
+    }
+    if reloaded.mqtt.protocol_version != current.mqtt.protocol_version {
+        summary.restart_required.push(format!(
+            "mqtt.protocol_version: {:?} -> {:?}",
+            current.mqtt.protocol_version, reloaded.mqtt.protocol_version
+        ));
+    }
+    if reloaded.mqtt.enabled != current.mqtt.enabled {
+        summary.restart_required.push(format!(
+            "mqtt.enabled: {} -> {}",
+            current.mqtt.enabled, reloaded.mqtt.enabled
+        ));
+    }
+    if reloaded.prometheus.enabled != current.prometheus.enabled
+        || reloaded.prometheus.port != current.prometheus.port
+    {
+        summary.restart_required.push(format!(
+            "prometheus: enabled {} port {} -> enabled {} port {}",
+            current.prometheus.enabled, current.prometheus.port, reloaded.prometheus.enabled, reloaded.prometheus.port
+        ));
+    }
+    if reloaded.http_api.enabled != current.http_api.enabled || reloaded.http_api.port != current.http_api.port {
+        summary.restart_required.push(format!(
+            "http_api: enabled {} port {} -> enabled {} port {}",
+            current.http_api.enabled, current.http_api.port, reloaded.http_api.enabled, reloaded.http_api.port
+        ));
+    }
+    if reloaded.logging.level != current.logging.level {
+        summary.restart_required.push(format!(
+            "logging.level: {:?} -> {:?}",
+            current.logging.level, reloaded.logging.level
+        ));
+    }
+    if reloaded.state_file != current.state_file {
+        summary.restart_required.push(format!(
+            "state_file: {:?} -> {:?}",
+            current.state_file, reloaded.state_file
+        ));
+    }
+    if reloaded.device.name != current.device.name {
+        summary.restart_required.push(format!(
+            "device.name: {} -> {}",
+            current.device.name, reloaded.device.name
+        ));
+    }
+
+    if reloaded.update_interval_secs != current.update_interval_secs {
+        summary.changed.push(format!(
+            "update_interval_secs: {} -> {}",
+            current.update_interval_secs, reloaded.update_interval_secs
+        ));
+    }
+    if reloaded.discovery_delay_ms != current.discovery_delay_ms {
+        summary.changed.push(format!(
+            "discovery_delay_ms: {} -> {}",
+            current.discovery_delay_ms, reloaded.discovery_delay_ms
+        ));
+    }
+    if reloaded.ping != current.ping {
+        summary.changed.push(format!(
+            "ping: {} targets every {}s -> {} targets every {}s",
+            current.ping.targets.len(),
+            current.ping.interval_secs,
+            reloaded.ping.targets.len(),
+            reloaded.ping.interval_secs
+        ));
+        summary.ping_changed = true;
+    }
+    if reloaded.mqtt.username != current.mqtt.username || reloaded.mqtt.password != current.mqtt.password {
+        // Never log the credentials themselves
+        summary.changed.push("mqtt credentials".to_string());
+        summary.credentials_changed = true;
+    }
+
+    let username = reloaded.mqtt.username.take();
+    let password = reloaded.mqtt.password.take();
+    reloaded.mqtt = current.mqtt.clone();
+    reloaded.mqtt.username = username;
+    reloaded.mqtt.password = password;
+    reloaded.prometheus = current.prometheus.clone();
+    reloaded.http_api = current.http_api.clone();
+    reloaded.logging = current.logging.clone();
+    reloaded.device.name = current.device.name.clone();
+    reloaded.state_file = current.state_file.clone();
+
+    if summary.changed.is_empty() && reloaded.fingerprint() != current.fingerprint() {
+        summary.changed.push("sensor and filter settings".to_string());
+    }
+
+    *current = reloaded;
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_reload() {
+        let mut current = DaemonConfig::default();
+        let mut reloaded = DaemonConfig {
+            update_interval_secs: 60,
+            ..DaemonConfig::default()
+        };
+        reloaded.mqtt.broker = "10.0.0.2".to_string();
+        reloaded.mqtt.password = Some("new-secret".to_string());
+
+        let summary = apply_reload(&mut current, reloaded);
+
+        assert_eq!(summary.changed, ["update_interval_secs: 30 -> 60", "mqtt credentials"]);
+        assert_eq!(summary.restart_required, ["mqtt.broker: localhost -> 10.0.0.2"]);
+        assert!(summary.credentials_changed);
+        assert_eq!(current.update_interval_secs, 60);
+        assert_eq!(current.mqtt.broker, "localhost");
+        assert_eq!(current.mqtt.password.as_deref(), Some("new-secret"));
+    }
+
+    #[test]
+    fn test_apply_reload_ping() {
+        let mut current = DaemonConfig::default();
+        let mut reloaded = DaemonConfig::default();
+        reloaded.ping.targets = vec!["192.168.1.1".to_string()];
+        reloaded.ping.interval_secs = 10;
+
+        let summary = apply_reload(&mut current, reloaded);
+
+        assert_eq!(summary.changed, ["ping: 0 targets every 30s -> 1 targets every 10s"]);
+        assert!(summary.restart_required.is_empty());
+        assert!(summary.ping_changed);
+        assert_eq!(current.ping.targets, ["192.168.1.1"]);
+        assert_eq!(current.ping.interval_secs, 10);
+    }
+
+    #[test]
+    fn test_apply_reload_without_changes() {
+        let mut current = DaemonConfig::default();
+        let summary = apply_reload(&mut current, DaemonConfig::default());
+        assert_eq!(summary, ReloadSummary::default());
+    }
+
+    #[test]
+    fn test_reload_requires_config_file() {
+        let current = DaemonConfig::default();
+        assert!(matches!(
+            reload_config(&current, &CliArgs::default()),
+            Err(ConfigError::Invalid(_))
+        ));
+    }
+}
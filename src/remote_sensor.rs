@@ -0,0 +1,226 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::task::JoinSet;
+use crate::config::RemoteDeviceConfig;
+use crate::fan_sensors::{extract_fan_id, is_fan_file};
+use crate::homeassistant::generate_friendly_name;
+use crate::naming::sanitize_sensor_name;
+use crate::sensors::{CollectFuture, SensorCollector, SystemSensor};
+use crate::sensors::SystemSensorType::{Fan, Temperature};
+use crate::temperature_sensor::{device_label, extract_temperature_number, is_temperature_file};
+
+const SSH: &str = "ssh";
+const SSH_CONNECT_TIMEOUT_SECS: u64 = 5;
+// Covers connecting plus reading every hwmon file on the remote host
+const SSH_TIMEOUT: Duration = Duration::from_secs(15);
+const MILLIDEGREE_TO_CELSIUS: f64 = 1000.0;
+// Prints "<path>\t<content>" for every readable hwmon name, temperature and fan file, so a
+// single ssh round trip per host and cycle is enough
+const HWMON_DUMP_SCRIPT: &str = r#"for f in /sys/class/hwmon/hwmon*/name /sys/class/hwmon/hwmon*/temp*_input /sys/class/hwmon/hwmon*/temp*_label /sys/class/hwmon/hwmon*/fan*_input /sys/class/hwmon/hwmon*/fan*_label; do [ -r "$f" ] && printf '%s\t%s\n' "$f" "$(cat "$f" 2>/dev/null)"; done; true"#;
+
+// Hosts currently failing, so an outage is logged once rather than every cycle
+static FAILING_HOSTS: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+// Temperatures and fans of the hosts in [[remote_devices]], read over ssh
+pub struct RemoteCollector {
+    pub devices: Vec<RemoteDeviceConfig>,
+}
+
+impl SensorCollector for RemoteCollector {
+    fn collect(&self) -> CollectFuture<'_> {
+        Box::pin(collect_remote(&self.devices))
+    }
+
+    fn name(&self) -> &str {
+        "remote"
+    }
+}
+
+// Hosts are queried concurrently; an unreachable host only costs its own timeout
+async fn collect_remote(devices: &[RemoteDeviceConfig]) -> Vec<SystemSensor> {
+    let mut tasks = JoinSet::new();
+    for (index, device) in devices.iter().cloned().enumerate() {
+        tasks.spawn(async move { (index, run_remote_dump(&device).await, device) });
+    }
+
+    let mut results = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        match result {
+            Ok(result) => results.push(result),
+            Err(e) => tracing::error!(error = %e, "Remote sensor task failed"),
+        }
+    }
+    // Keep the configured host order stable across cycles
+    results.sort_by_key(|(index, _, _)| *index);
+
+    let mut sensors = Vec::new();
+    for (_, output, device) in results {
+        match output {
+            Ok(output) => {
+                mark_host(&device.name, None);
+                sensors.extend(remote_sensors(&device.name, &parse_hwmon_dump(&output)));
+            }
+            Err(e) => mark_host(&device.name, Some(&e)),
+        }
+    }
+    sensors
+}
+
+// BatchMode makes ssh fail instead of prompting for a password or host key confirmation
+async fn run_remote_dump(device: &RemoteDeviceConfig) -> Result<String, String> {
+    let mut command = Command::new(SSH);
+    command
+        .arg("-i")
+        .arg(&device.ssh_key_path)
+        .args(["-p", &device.ssh_port.to_string()])
+        .args(["-o", "BatchMode=yes"])
+        .args(["-o", &format!("ConnectTimeout={}", SSH_CONNECT_TIMEOUT_SECS)])
+        .arg(format!("{}@{}", device.ssh_user, device.ssh_host))
+        .arg(HWMON_DUMP_SCRIPT)
+        .kill_on_drop(true);
+
+    let output = match tokio::time::timeout(SSH_TIMEOUT, command.output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => return Err(format!("failed to run {}: {}", SSH, e)),
+        Err(_) => return Err(format!("{} timed out", SSH)),
+    };
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{} exited with {}: {}", SSH, output.status, stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn mark_host(name: &str, error: Option<&str>) {
+    let failing = FAILING_HOSTS.get_or_init(|| Mutex::new(HashSet::new()));
+    let mut failing = failing.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    match error {
+        Some(error) => {
+            if failing.insert(name.to_string()) {
+                tracing::warn!(remote = %name, error = %error, "Failed to read remote sensors");
+            }
+        }
+        None => {
+            if failing.remove(name) {
+                tracing::info!(remote = %name, "Remote sensors readable again");
+            }
+        }
+    }
+}
+
+// "/sys/class/hwmon/hwmon2/temp1_input\t45000" -> {"/sys/class/hwmon/hwmon2": {"temp1_input": "45000"}}
+fn parse_hwmon_dump(output: &str) -> BTreeMap<String, HashMap<String, String>> {
+    let mut devices: BTreeMap<String, HashMap<String, String>> = BTreeMap::new();
+    for line in output.lines() {
+        let Some((path, content)) = line.split_once('\t') else {
+            continue;
+        };
+        let Some((device, file)) = path.rsplit_once('/') else {
+            continue;
+        };
+        devices
+            .entry(device.to_string())
+            .or_default()
+            .insert(file.to_string(), content.trim().to_string());
+    }
+    devices
+}
+
+// Same names and labels as the local hwmon collectors, prefixed with the remote device name
+fn remote_sensors(remote: &str, devices: &BTreeMap<String, HashMap<String, String>>) -> Vec<SystemSensor> {
+    let mut sensors = Vec::new();
+    for (path, files) in devices {
+        let device_name = files
+            .get("name")
+            .cloned()
+            .unwrap_or_else(|| path.rsplit('/').next().unwrap_or(path).to_string());
+        let mut filenames: Vec<&String> = files.keys().collect();
+        filenames.sort();
+
+        for filename in filenames {
+            let label = |suffix: &str| files.get(&filename.replace("_input", suffix));
+            let sensor = if is_temperature_file(filename) {
+                let Some(number) = extract_temperature_number(filename) else {
+                    continue;
+                };
+                let Ok(millidegrees) = files[filename].parse::<f64>() else {
+                    continue;
+                };
+                let local_name = format!("{}_{}", device_name, number);
+                let hwmon_label = label("_label").map(|label| device_label(&device_name, label).unwrap_or(label.clone()));
+                SystemSensor {
+                    name: sanitize_sensor_name(&format!("{}_{}", remote, local_name)),
+                    label: Some(format!("{} {}", remote, generate_friendly_name(&local_name, hwmon_label.as_deref()))),
+                    value: millidegrees / MILLIDEGREE_TO_CELSIUS,
+                    unit: "°C".to_string(),
+                    sensor_type: Temperature,
+                    attributes: HashMap::new(),
+                    extra: None,
+                }
+            } else if is_fan_file(filename) {
+                let Some(fan_id) = extract_fan_id(filename) else {
+                    continue;
+                };
+                let Ok(rpm) = files[filename].parse::<f64>() else {
+                    continue;
+                };
+                let fan_label = label("_label").cloned().unwrap_or_else(|| format!("{} Fan {}", device_name, fan_id));
+                SystemSensor {
+                    name: sanitize_sensor_name(&format!("{}_{}_{}_fan", remote, device_name, fan_id)),
+                    label: Some(format!("{} {}", remote, fan_label)),
+                    value: rpm,
+                    unit: "RPM".to_string(),
+                    sensor_type: Fan,
+                    attributes: HashMap::new(),
+                    extra: None,
+                }
+            } else {
+                continue;
+            };
+            sensors.push(sensor);
+        }
+    }
+    sensors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DUMP: &str = "/sys/class/hwmon/hwmon0/name\tacpitz
+/sys/class/hwmon/hwmon0/temp1_input\t27800
+/sys/class/hwmon/hwmon3/name\tcoretemp
+/sys/class/hwmon/hwmon3/temp2_input\t45000
+/sys/class/hwmon/hwmon3/temp2_label\tCore 0
+/sys/class/hwmon/hwmon4/name\tnct6775
+/sys/class/hwmon/hwmon4/fan2_input\t1150
+/sys/class/hwmon/hwmon4/temp1_input\t
+";
+
+    #[test]
+    fn test_parse_hwmon_dump() {
+        let devices = parse_hwmon_dump(DUMP);
+        assert_eq!(devices.len(), 3);
+        assert_eq!(devices["/sys/class/hwmon/hwmon3"]["temp2_label"], "Core 0");
+        assert_eq!(devices["/sys/class/hwmon/hwmon4"]["temp1_input"], "");
+    }
+
+    #[test]
+    fn test_remote_sensors() {
+        let sensors = remote_sensors("nas", &parse_hwmon_dump(DUMP));
+        let names: Vec<(&str, f64, Option<&str>)> = sensors
+            .iter()
+            .map(|s| (s.name.as_str(), s.value, s.label.as_deref()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("nas_acpitz_1", 27.8, Some("nas System Temperature")),
+                ("nas_coretemp_2", 45.0, Some("nas Intel Core 0 Temperature")),
+                ("nas_nct6775_2_fan", 1150.0, Some("nas nct6775 Fan 2")),
+            ]
+        );
+    }
+}
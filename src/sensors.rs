@@ -1,11 +1,54 @@
-use crate::config::DaemonConfig;
-use crate::fan_sensors::collect_all_fans;
+use crate::calibration::apply_calibration;
+use crate::command_sensor::CommandCollector;
+use crate::config::{AvailabilityMode, DaemonConfig, DedupStrategy, MqttAttributeConfig};
+use crate::fan_sensors::FanCollector;
+use crate::filters::apply_filters;
 use crate::homeassistant::{
-    system_discovery_config, system_sensor_availability, system_state, DeviceInfo,
+    system_discovery_config, system_sensor_availability, system_state, DeviceInfo, DiscoveryDomain, DiscoveryOptions,
 };
-use crate::mqtt_client::MqttSensorTopics;
-use crate::system_sensor::collect_system_stats;
-use crate::temperature_sensor::collect_all_temperatures;
+use crate::iio_sensors::IioCollector;
+use crate::json_file_sensor::JsonFileCollector;
+use crate::mqtt_client::{MqttSensorTopics, PublishMode};
+use crate::system_sensor::{collect_config_info, collect_system_info, SystemStatsCollector};
+use crate::temperature_sensor::{convert_temperatures, TemperatureCollector};
+use crate::thermal_zone_sensor::ThermalZoneCollector;
+use crate::utils::{auto_scale_stable, matches_pattern};
+use crate::voltage_sensor::VoltageCollector;
+use crate::power_sensor::PowerCollector;
+use crate::rapl_sensor::RaplCollector;
+use crate::network_sensor::NetworkCollector;
+use crate::disk_io_sensor::DiskIoCollector;
+use crate::cpufreq_sensor::CpuFrequencyCollector;
+use crate::power_supply_sensor::PowerSupplyCollector;
+use crate::psi_sensor::PressureStallCollector;
+use crate::amdgpu_sensor::AmdgpuCollector;
+use crate::onewire_sensor::OneWireCollector;
+use crate::metrics::collect_daemon_metrics;
+use crate::ping_sensor::collect_ping;
+use crate::remote_sensor::RemoteCollector;
+use crate::smart_sensor::SmartCollector;
+use crate::wifi_sensor::WifiCollector;
+use crate::process_sensor::ProcessCollector;
+use crate::history::record_history;
+use crate::labeling::apply_labels;
+use crate::smoothing::smooth_sensor;
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::Entry;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::Duration;
+
+// Names already reported as duplicates, so the warning isn't repeated every cycle
+static WARNED_DUPLICATES: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+// SI prefix auto_scale_units picked for each sensor on its first reading
+static SCALE_PREFIXES: OnceLock<Mutex<HashMap<String, &'static str>>> = OnceLock::new();
+
+// Latest collected sensors, written by the publish loop every cycle for the HTTP endpoints
+pub type SharedSensors = Arc<RwLock<Vec<SystemSensor>>>;
 
 #[derive(Debug, Clone)]
 pub struct SystemSensor {
@@ -14,42 +57,377 @@ pub struct SystemSensor {
     pub value: f64,
     pub unit: String,
     pub sensor_type: SystemSensorType,
+    pub attributes: HashMap<String, String>,
+    pub extra: Option<String>, // Published as the state instead of `value` for text sensors
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SystemSensorType {
     CpuUsage,
+    CpuCoreUsage,
+    LoadAverage,
     MemoryUsage,
     DiskUsage,
     MemoryUsed,
     MemoryTotal,
+    SwapUsage,
+    SwapUsed,
+    SwapTotal,
     DiskUsed,
     DiskTotal,
     Temperature,
     Fan,
+    FanPercent,
+    FileDescriptors,
+    Entropy,
+    EntropyAlert, // On while the entropy is below sensors.low_entropy_threshold
+    Humidity,
+    ConfigInfo,
+    Uptime,
+    Voltage,
+    Power,
+    NetworkRx,
+    NetworkTx,
+    NetworkRxTotal,
+    NetworkTxTotal,
+    DiskRead,
+    DiskWrite,
+    DiskReadIops,
+    DiskWriteIops,
+    DiskIoWait,
+    CpuFrequency,
+    BatteryPercent,
+    BatteryState,
+    BatteryEnergy,
+    PowerSupplyOnline,
+    ProcessCount,
+    ThreadCount,
+    PressureStall,
+    WatchedProcess,
+    SystemInfo,
+    FanPwm,
+    TemperatureLimit,
+    NetworkLinkStatus,
+    SmartAttribute,
+    WifiSignal,
+    DiskInodes,
+    Heartbeat,
+    PingLatency,
+    DaemonMetric,
+    Generic, // Command and JSON file sensors that don't fit any of the other types
 }
 
 impl SystemSensorType {
     pub fn icon(&self) -> &str {
         match self {
-            SystemSensorType::CpuUsage => "mdi:cpu-64-bit",
+            SystemSensorType::CpuUsage | SystemSensorType::CpuCoreUsage => "mdi:cpu-64-bit",
+            SystemSensorType::LoadAverage => "mdi:chart-line",
             SystemSensorType::MemoryUsage
             | SystemSensorType::MemoryUsed
             | SystemSensorType::MemoryTotal => "mdi:memory",
+            SystemSensorType::SwapUsage
+            | SystemSensorType::SwapUsed
+            | SystemSensorType::SwapTotal => "mdi:swap-horizontal",
             SystemSensorType::DiskUsage
             | SystemSensorType::DiskUsed
             | SystemSensorType::DiskTotal => "mdi:harddisk",
             SystemSensorType::Temperature => "mdi:thermometer",
-            SystemSensorType::Fan => "mdi:fan",
+            SystemSensorType::TemperatureLimit => "mdi:thermometer-alert",
+            SystemSensorType::NetworkLinkStatus => "mdi:lan-connect",
+            SystemSensorType::Fan | SystemSensorType::FanPercent => "mdi:fan",
+            SystemSensorType::FanPwm => "mdi:fan-speed-1",
+            SystemSensorType::FileDescriptors => "mdi:file-outline",
+            SystemSensorType::Entropy => "mdi:lock",
+            SystemSensorType::EntropyAlert => "mdi:lock-alert",
+            SystemSensorType::Humidity => "mdi:water-percent",
+            SystemSensorType::ConfigInfo => "mdi:file-cog",
+            SystemSensorType::Uptime => "mdi:clock-outline",
+            SystemSensorType::Voltage => "mdi:flash",
+            SystemSensorType::Power => "mdi:lightning-bolt",
+            SystemSensorType::NetworkRx => "mdi:download-network",
+            SystemSensorType::NetworkTx => "mdi:upload-network",
+            SystemSensorType::NetworkRxTotal => "mdi:download-network-outline",
+            SystemSensorType::NetworkTxTotal => "mdi:upload-network-outline",
+            SystemSensorType::DiskRead => "mdi:harddisk",
+            SystemSensorType::DiskWrite => "mdi:harddisk",
+            SystemSensorType::DiskReadIops => "mdi:harddisk",
+            SystemSensorType::DiskWriteIops => "mdi:harddisk",
+            SystemSensorType::DiskIoWait => "mdi:timer-outline",
+            SystemSensorType::CpuFrequency => "mdi:speedometer",
+            SystemSensorType::BatteryPercent => "mdi:battery",
+            SystemSensorType::BatteryState => "mdi:battery-charging",
+            SystemSensorType::BatteryEnergy => "mdi:battery-high",
+            SystemSensorType::PowerSupplyOnline => "mdi:power-plug",
+            SystemSensorType::ProcessCount => "mdi:application-cog",
+            SystemSensorType::ThreadCount => "mdi:cog-transfer",
+            SystemSensorType::PressureStall => "mdi:gauge",
+            SystemSensorType::WatchedProcess => "mdi:application-outline",
+            SystemSensorType::SystemInfo => "mdi:information-outline",
+            SystemSensorType::SmartAttribute => "mdi:harddisk-plus",
+            SystemSensorType::WifiSignal => "mdi:wifi",
+            SystemSensorType::DiskInodes => "mdi:file-tree",
+            SystemSensorType::Heartbeat => "mdi:heart-pulse",
+            SystemSensorType::PingLatency => "mdi:lan-pending",
+            SystemSensorType::DaemonMetric => "mdi:counter",
+            SystemSensorType::Generic => "mdi:console",
+        }
+    }
+
+    // State-only sensors don't publish availability; their presence in recent cycles is the signal
+    pub fn has_availability(&self) -> bool {
+        !matches!(self, SystemSensorType::ConfigInfo | SystemSensorType::SystemInfo)
+    }
+
+    // Home Assistant integration the sensor is discovered as
+    pub fn discovery_domain(&self) -> DiscoveryDomain {
+        match self {
+            SystemSensorType::NetworkLinkStatus | SystemSensorType::EntropyAlert => DiscoveryDomain::BinarySensor,
+            _ => DiscoveryDomain::Sensor,
+        }
+    }
+
+    // Values that never change while the daemon runs; their state is retained and published once
+    pub fn is_static(&self) -> bool {
+        matches!(self, SystemSensorType::SystemInfo)
+    }
+
+    pub fn publish_mode(&self) -> PublishMode {
+        match self {
+            _ if self.is_static() => PublishMode::Once,
+            SystemSensorType::Heartbeat => PublishMode::EveryCycle,
+            _ => PublishMode::OnChange,
         }
     }
 }
-pub fn get_all_sensors() -> Vec<SystemSensor> {
-    let temp_sensors = collect_all_temperatures();
-    let system_sensors = collect_system_stats();
-    let fan_sensors = collect_all_fans();
 
-    temp_sensors.into_iter().chain(system_sensors).chain(fan_sensors).collect()
+// Shared JSON representation used by every output format
+impl From<&SystemSensor> for serde_json::Value {
+    fn from(sensor: &SystemSensor) -> Self {
+        json!({
+            "name": sensor.name,
+            "value": sensor.value,
+            "unit": sensor.unit,
+            "label": sensor.label,
+            "extra": sensor.extra,
+            "type": sensor.sensor_type,
+            "attributes": sensor.attributes,
+        })
+    }
+}
+
+impl Serialize for SystemSensor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde_json::Value::from(self).serialize(serializer)
+    }
+}
+
+pub type CollectFuture<'a> = Pin<Box<dyn Future<Output = Vec<SystemSensor>> + Send + 'a>>;
+
+// A source of sensors run every cycle by get_all_sensors. Collectors read sysfs with a
+// timeout, so collect returns a boxed future to keep the trait usable as `dyn`
+pub trait SensorCollector: Send + Sync {
+    fn collect(&self) -> CollectFuture<'_>;
+    fn name(&self) -> &str;
+}
+
+// Collectors for the enabled sensor categories; rebuilt whenever the config is reloaded
+pub fn build_collectors(config: &DaemonConfig) -> Vec<Box<dyn SensorCollector>> {
+    let hwmon_cache_ttl = Duration::from_secs(config.hwmon_device_cache_secs);
+    let read_timeout = Duration::from_millis(config.sensors.read_timeout_ms);
+    let mut collectors: Vec<Box<dyn SensorCollector>> = Vec::new();
+
+    if config.sensors.enable_temperature {
+        collectors.push(Box::new(TemperatureCollector {
+            temperature_limits: config.sensors.temperature_limits,
+            hwmon_cache_ttl,
+            read_timeout,
+        }));
+    }
+    collectors.push(Box::new(SystemStatsCollector {
+        sensors_config: config.sensors.clone(),
+        read_timeout,
+    }));
+    if config.sensors.enable_fan {
+        collectors.push(Box::new(FanCollector {
+            fan_config: config.sensors.fan.clone(),
+            fan_pwm: config.sensors.fan_pwm,
+            hwmon_cache_ttl,
+            read_timeout,
+        }));
+    }
+    if config.sensors.enable_temperature && config.sensors.thermal_zones {
+        collectors.push(Box::new(ThermalZoneCollector { read_timeout }));
+    }
+    if !config.command_sensors.is_empty() {
+        collectors.push(Box::new(CommandCollector {
+            commands: config.command_sensors.clone(),
+        }));
+    }
+    if !config.json_file_sensors.is_empty() {
+        collectors.push(Box::new(JsonFileCollector {
+            files: config.json_file_sensors.clone(),
+        }));
+    }
+    collectors.push(Box::new(NetworkCollector {
+        sensors_config: config.sensors.clone(),
+        read_timeout,
+    }));
+    collectors.push(Box::new(WifiCollector {
+        sensors_config: config.sensors.clone(),
+        read_timeout,
+    }));
+    collectors.push(Box::new(DiskIoCollector {
+        sensors_config: config.sensors.clone(),
+        read_timeout,
+    }));
+    if config.sensors.psi {
+        collectors.push(Box::new(PressureStallCollector { read_timeout }));
+    }
+    if config.sensors.cpu_frequency {
+        collectors.push(Box::new(CpuFrequencyCollector { read_timeout }));
+    }
+    if config.sensors.voltage {
+        collectors.push(Box::new(VoltageCollector {
+            hwmon_cache_ttl,
+            read_timeout,
+        }));
+    }
+    collectors.push(Box::new(PowerCollector {
+        hwmon_cache_ttl,
+        read_timeout,
+    }));
+    collectors.push(Box::new(AmdgpuCollector {
+        hwmon_cache_ttl,
+        read_timeout,
+    }));
+    collectors.push(Box::new(RaplCollector { read_timeout }));
+    collectors.push(Box::new(PowerSupplyCollector { read_timeout }));
+    // Probes are discovered on the bus, [[onewire_sensors]] only names them
+    collectors.push(Box::new(OneWireCollector {
+        sensor_configs: config.onewire_sensors.clone(),
+        read_timeout,
+    }));
+    if config.sensors.enable_iio_sensors {
+        collectors.push(Box::new(IioCollector { read_timeout }));
+    }
+    if !config.watch_processes.is_empty() {
+        collectors.push(Box::new(ProcessCollector {
+            watch_processes: config.watch_processes.clone(),
+        }));
+    }
+    if config.sensors.smart {
+        collectors.push(Box::new(SmartCollector {
+            devices: config.smart_devices.clone(),
+        }));
+    }
+    if !config.remote_devices.is_empty() {
+        collectors.push(Box::new(RemoteCollector {
+            devices: config.remote_devices.clone(),
+        }));
+    }
+    collectors
+}
+
+#[tracing::instrument(name = "collect_sensors", level = "debug", skip_all)]
+pub async fn get_all_sensors(config: &DaemonConfig, collectors: &[Box<dyn SensorCollector>]) -> Vec<SystemSensor> {
+    let mut sensors: Vec<SystemSensor> = Vec::new();
+    let read_timeout = Duration::from_millis(config.sensors.read_timeout_ms);
+
+    for collector in collectors {
+        let collected = collector.collect().await;
+        tracing::trace!(collector = collector.name(), count = collected.len(), "Collected sensors");
+        sensors.extend(collected);
+    }
+    // The daemon's own state rather than readings of the system
+    sensors.extend(collect_ping(&config.ping));
+    sensors.extend(collect_system_info(read_timeout).await);
+    sensors.extend(collect_config_info(config));
+    if config.daemon.self_monitoring {
+        sensors.extend(collect_daemon_metrics());
+    }
+
+    let mut sensors = dedup_sensors(sensors, config.sensors.dedup_strategy);
+    convert_temperatures(&mut sensors, config.sensors.temperature_unit);
+    for sensor in &mut sensors {
+        apply_calibration(sensor, &config.calibration);
+    }
+
+    let mut sensors = apply_filters(sensors, &config.filters);
+
+    for sensor in &mut sensors {
+        apply_labels(sensor, &config.sensors.labels, &config.sensor_labels);
+    }
+
+    if config.auto_scale_units {
+        let prefixes = SCALE_PREFIXES.get_or_init(|| Mutex::new(HashMap::new()));
+        let mut prefixes = prefixes.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        for sensor in &mut sensors {
+            let (value, unit) = auto_scale_stable(&sensor.name, sensor.value, &sensor.unit, &mut prefixes);
+            sensor.value = value;
+            sensor.unit = unit;
+        }
+    }
+
+    sensors
+}
+
+// The same chip can show up under several hwmon paths (e.g. coretemp and k10temp both
+// loaded), producing sensors that would overwrite each other in Home Assistant
+fn dedup_sensors(sensors: Vec<SystemSensor>, strategy: DedupStrategy) -> Vec<SystemSensor> {
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    let mut unique: Vec<SystemSensor> = Vec::with_capacity(sensors.len());
+
+    for sensor in sensors {
+        match positions.entry(sensor.name.clone()) {
+            Entry::Vacant(entry) => {
+                entry.insert(unique.len());
+                unique.push(sensor);
+            }
+            Entry::Occupied(entry) => {
+                warn_duplicate(&sensor.name);
+                let kept = &mut unique[*entry.get()];
+                let replace = match strategy {
+                    DedupStrategy::First => false,
+                    DedupStrategy::Last => true,
+                    DedupStrategy::Max => sensor.value > kept.value,
+                    DedupStrategy::Min => sensor.value < kept.value,
+                };
+                if replace {
+                    *kept = sensor;
+                }
+            }
+        }
+    }
+    unique
+}
+
+fn warn_duplicate(name: &str) {
+    let warned = WARNED_DUPLICATES.get_or_init(|| Mutex::new(HashSet::new()));
+    let mut warned = warned.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if warned.insert(name.to_string()) {
+        tracing::warn!(sensor = %name, "Duplicate sensor name, keeping a single value per dedup_strategy");
+    }
+}
+
+// Attach the latest values received on external MQTT topics to matching sensors
+pub fn apply_mqtt_attributes(
+    sensors: &mut [SystemSensor],
+    attribute_configs: &[MqttAttributeConfig],
+    received_values: &HashMap<String, String>,
+) {
+    for attribute in attribute_configs {
+        let Some(value) = received_values.get(&attribute.mqtt_topic) else {
+            continue;
+        };
+        for sensor in sensors.iter_mut() {
+            if matches_pattern(&attribute.sensor_pattern, &sensor.name) {
+                sensor
+                    .attributes
+                    .insert(attribute.attribute_name.clone(), value.clone());
+            }
+        }
+    }
 }
 
 pub fn generate_payloads<'a>(
@@ -57,10 +435,213 @@ pub fn generate_payloads<'a>(
     config: &'a DaemonConfig,
     device_info: &'a DeviceInfo,
 ) -> impl Iterator<Item = MqttSensorTopics> + 'a {
-    sensors.iter().map(move |sensor| MqttSensorTopics {
-        name: sensor.name.clone(),
-        state: system_state(sensor, &config.device.name),
-        discovery: system_discovery_config(sensor, &config.device.name, device_info),
-        availability: system_sensor_availability(sensor, &config.device.name, true),
+    sensors.iter().map(move |sensor| {
+        let sensor = &smooth_sensor(sensor, &config.smoothing);
+        let history = record_history(sensor, config.history.window_size);
+        MqttSensorTopics {
+            name: sensor.name.clone(),
+            value: sensor.extra.is_none().then_some(sensor.value),
+            publish_mode: sensor.sensor_type.publish_mode(),
+            state: system_state(
+                sensor,
+                history,
+                config.mqtt.payload_format,
+                &config.mqtt.discovery_prefix,
+                &config.device.name,
+                config.state_qos().into(),
+            ),
+            discovery: system_discovery_config(
+                sensor,
+                DiscoveryOptions {
+                    domain: sensor.sensor_type.discovery_domain(),
+                    entity_category: config.entity_category_override(&sensor.name),
+                    payload_format: config.mqtt.payload_format,
+                    availability_mode: config.mqtt.availability_mode,
+                },
+                &config.mqtt.discovery_prefix,
+                &config.device.name,
+                device_info,
+                config.mqtt.use_device_availability,
+                config.discovery_qos().into(),
+            ),
+            // In device mode the device-level topic is the only availability published
+            availability: (sensor.sensor_type.has_availability()
+                && config.mqtt.availability_mode == AvailabilityMode::PerSensor)
+                .then(|| {
+                    system_sensor_availability(
+                        sensor,
+                        &config.mqtt.discovery_prefix,
+                        &config.device.name,
+                        true,
+                        config.availability_qos().into(),
+                    )
+                }),
+        }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ProcessMetric, WatchProcessConfig};
+
+    fn temperature(name: &str, value: f64) -> SystemSensor {
+        SystemSensor {
+            name: name.to_string(),
+            label: None,
+            value,
+            unit: "°C".to_string(),
+            sensor_type: SystemSensorType::Temperature,
+            attributes: HashMap::new(),
+            extra: None,
+        }
+    }
+
+    #[test]
+    fn test_dedup_sensors() {
+        let sensors = || {
+            vec![
+                temperature("k10temp_1", 45.0),
+                temperature("nvme_1", 38.0),
+                temperature("k10temp_1", 47.0),
+            ]
+        };
+
+        let first = dedup_sensors(sensors(), DedupStrategy::default());
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0].name, "k10temp_1");
+        assert_eq!(first[0].value, 45.0);
+        assert_eq!(first[1].name, "nvme_1");
+
+        assert_eq!(dedup_sensors(sensors(), DedupStrategy::Last)[0].value, 47.0);
+        assert_eq!(dedup_sensors(sensors(), DedupStrategy::Max)[0].value, 47.0);
+        assert_eq!(dedup_sensors(sensors(), DedupStrategy::Min)[0].value, 45.0);
+    }
+
+    #[test]
+    fn test_build_collectors() {
+        let names = |config: &DaemonConfig| -> Vec<String> {
+            build_collectors(config).iter().map(|c| c.name().to_string()).collect()
+        };
+        let mut config = DaemonConfig::default();
+        config.sensors.enable_temperature = true;
+        config.sensors.enable_fan = true;
+        config.sensors.psi = false;
+        config.sensors.cpu_frequency = false;
+        config.sensors.voltage = false;
+        config.sensors.enable_iio_sensors = false;
+        config.sensors.smart = false;
+        let always = ["network", "wifi", "disk_io", "power", "amdgpu", "rapl", "power_supply", "onewire"];
+        assert_eq!(names(&config), [&["temperature", "system", "fan", "thermal_zone"][..], &always].concat());
+
+        config.sensors.enable_temperature = false;
+        config.sensors.enable_fan = false;
+        assert_eq!(names(&config), [&["system"][..], &always].concat());
+
+        config.sensors.psi = true;
+        config.sensors.smart = true;
+        config.watch_processes.push(WatchProcessConfig {
+            name: "nginx".to_string(),
+            metrics: vec![ProcessMetric::Cpu],
+        });
+        assert_eq!(
+            names(&config),
+            [&["system", "network", "wifi", "disk_io", "psi"][..], &always[3..], &["process", "smart"]].concat()
+        );
+    }
+
+    #[test]
+    fn test_publish_mode() {
+        assert_eq!(SystemSensorType::SystemInfo.publish_mode(), PublishMode::Once);
+        assert_eq!(SystemSensorType::Heartbeat.publish_mode(), PublishMode::EveryCycle);
+        assert_eq!(SystemSensorType::CpuUsage.publish_mode(), PublishMode::OnChange);
+    }
+
+    #[test]
+    fn test_sensor_type_round_trip() {
+        let all_types = [
+            SystemSensorType::CpuUsage,
+            SystemSensorType::CpuCoreUsage,
+            SystemSensorType::LoadAverage,
+            SystemSensorType::MemoryUsage,
+            SystemSensorType::DiskUsage,
+            SystemSensorType::MemoryUsed,
+            SystemSensorType::MemoryTotal,
+            SystemSensorType::SwapUsage,
+            SystemSensorType::SwapUsed,
+            SystemSensorType::SwapTotal,
+            SystemSensorType::DiskUsed,
+            SystemSensorType::DiskTotal,
+            SystemSensorType::Temperature,
+            SystemSensorType::Fan,
+            SystemSensorType::FanPercent,
+            SystemSensorType::FileDescriptors,
+            SystemSensorType::Entropy,
+            SystemSensorType::EntropyAlert,
+            SystemSensorType::Humidity,
+            SystemSensorType::ConfigInfo,
+            SystemSensorType::Uptime,
+            SystemSensorType::Voltage,
+            SystemSensorType::Power,
+            SystemSensorType::NetworkRx,
+            SystemSensorType::NetworkTx,
+            SystemSensorType::NetworkRxTotal,
+            SystemSensorType::NetworkTxTotal,
+            SystemSensorType::DiskRead,
+            SystemSensorType::DiskWrite,
+            SystemSensorType::DiskReadIops,
+            SystemSensorType::DiskWriteIops,
+            SystemSensorType::DiskIoWait,
+            SystemSensorType::CpuFrequency,
+            SystemSensorType::BatteryPercent,
+            SystemSensorType::BatteryState,
+            SystemSensorType::BatteryEnergy,
+            SystemSensorType::PowerSupplyOnline,
+            SystemSensorType::ProcessCount,
+            SystemSensorType::ThreadCount,
+            SystemSensorType::PressureStall,
+            SystemSensorType::WatchedProcess,
+            SystemSensorType::SystemInfo,
+            SystemSensorType::FanPwm,
+            SystemSensorType::TemperatureLimit,
+            SystemSensorType::NetworkLinkStatus,
+            SystemSensorType::SmartAttribute,
+            SystemSensorType::WifiSignal,
+            SystemSensorType::DiskInodes,
+            SystemSensorType::Heartbeat,
+            SystemSensorType::PingLatency,
+            SystemSensorType::DaemonMetric,
+            SystemSensorType::Generic,
+        ];
+
+        for sensor_type in all_types {
+            let serialized = serde_json::to_value(&sensor_type).unwrap();
+            let name = serialized.as_str().unwrap();
+            assert_eq!(name, name.to_lowercase());
+            assert_eq!(serde_json::from_value::<SystemSensorType>(serialized).unwrap(), sensor_type);
+        }
+        assert_eq!(serde_json::to_value(SystemSensorType::CpuUsage).unwrap(), "cpu_usage");
+    }
+
+    #[test]
+    fn test_sensor_to_json() {
+        let sensor = SystemSensor {
+            name: "k10temp_1".to_string(),
+            label: Some("Tctl".to_string()),
+            value: 54.5,
+            unit: "°C".to_string(),
+            sensor_type: SystemSensorType::Temperature,
+            attributes: HashMap::from([("source".to_string(), "hwmon".to_string())]),
+            extra: None,
+        };
+
+        let value = serde_json::Value::from(&sensor);
+        assert_eq!(value["name"], "k10temp_1");
+        assert_eq!(value["value"], 54.5);
+        assert_eq!(value["unit"], "°C");
+        assert_eq!(value["label"], "Tctl");
+        assert_eq!(value["type"], "temperature");
+        assert_eq!(value["attributes"]["source"], "hwmon");
+        assert_eq!(serde_json::to_value(&sensor).unwrap(), value);
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use serde_json::Value;
+use tokio::process::Command;
+use crate::config::SmartDeviceConfig;
+use crate::sensors::{CollectFuture, SensorCollector, SystemSensor};
+use crate::sensors::SystemSensorType::{SmartAttribute, Temperature};
+
+const SMARTCTL: &str = "smartctl";
+// smartctl talks to the drive itself, which takes far longer than a sysfs read
+const SMARTCTL_TIMEOUT: Duration = Duration::from_secs(10);
+// Exit status bits 0 and 1: bad command line, or the device couldn't be opened
+const SMARTCTL_FATAL_BITS: i32 = 0b11;
+// ATA attribute ids and the sensor suffix they are published as
+const ATA_ATTRIBUTES: [(u64, &str); 3] = [
+    (5, "reallocated_sectors"),
+    (197, "pending_sectors"),
+    (198, "offline_uncorrectable"),
+];
+
+// Devices already reported as failing, so the warning isn't repeated every cycle
+static WARNED_DEVICES: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+// SMART attributes of the drives in [[smart_devices]], read through smartctl
+pub struct SmartCollector {
+    pub devices: Vec<SmartDeviceConfig>,
+}
+
+impl SensorCollector for SmartCollector {
+    fn collect(&self) -> CollectFuture<'_> {
+        Box::pin(collect_smart(&self.devices))
+    }
+
+    fn name(&self) -> &str {
+        "smart"
+    }
+}
+
+async fn collect_smart(devices: &[SmartDeviceConfig]) -> Vec<SystemSensor> {
+    let mut sensors = Vec::new();
+    for device in devices {
+        match run_smartctl(&device.path).await {
+            Ok(output) => sensors.extend(smart_sensors(&device_slug(&device.path), &output)),
+            Err(e) => warn_device(&device.path, &e),
+        }
+    }
+    sensors
+}
+
+// smartctl needs root (or CAP_SYS_RAWIO); without it the device can't be opened and the
+// JSON output only carries the error message
+async fn run_smartctl(device: &str) -> Result<Value, String> {
+    let command = Command::new(SMARTCTL).args(["-j", "-A", "-H", device]).kill_on_drop(true).output();
+    let output = match tokio::time::timeout(SMARTCTL_TIMEOUT, command).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => return Err(format!("failed to run {}: {}", SMARTCTL, e)),
+        Err(_) => return Err(format!("{} timed out", SMARTCTL)),
+    };
+    let json: Value = serde_json::from_slice(&output.stdout).map_err(|e| format!("invalid {} output: {}", SMARTCTL, e))?;
+    if output.status.code().is_none_or(|code| code & SMARTCTL_FATAL_BITS != 0) {
+        return Err(smartctl_messages(&json).unwrap_or_else(|| format!("{} exited with {}", SMARTCTL, output.status)));
+    }
+    Ok(json)
+}
+
+fn smartctl_messages(json: &Value) -> Option<String> {
+    let messages: Vec<&str> = json["smartctl"]["messages"]
+        .as_array()?
+        .iter()
+        .filter_map(|message| message["string"].as_str())
+        .collect();
+    (!messages.is_empty()).then(|| messages.join("; "))
+}
+
+fn warn_device(device: &str, error: &str) {
+    let warned = WARNED_DEVICES.get_or_init(|| Mutex::new(HashSet::new()));
+    let mut warned = warned.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if warned.insert(device.to_string()) {
+        tracing::warn!(device = %device, error = %error, "Failed to read SMART data, is smartctl installed and running as root?");
+    }
+}
+
+// "/dev/sda" -> "sda", "/dev/disk/by-id/ata-WDC_WD40" -> "ata_wdc_wd40"
+fn device_slug(path: &str) -> String {
+    let name = Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string());
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect()
+}
+
+// ATA drives report a table of attributes, NVMe drives a fixed health log; temperature and
+// power on hours are normalised by smartctl for both
+fn smart_sensors(device: &str, json: &Value) -> Vec<SystemSensor> {
+    let mut sensors = Vec::new();
+    let sensor = |suffix: &str, value: f64, unit: &str| SystemSensor {
+        name: format!("smart_{}_{}", device, suffix),
+        label: None,
+        value,
+        unit: unit.to_string(),
+        sensor_type: if suffix == "temperature" { Temperature } else { SmartAttribute },
+        attributes: HashMap::new(),
+        extra: None,
+    };
+
+    if let Some(temperature) = json["temperature"]["current"].as_f64() {
+        sensors.push(sensor("temperature", temperature, "°C"));
+    }
+    if let Some(hours) = json["power_on_time"]["hours"].as_f64() {
+        sensors.push(sensor("power_on_hours", hours, "h"));
+    }
+    if let Some(passed) = json["smart_status"]["passed"].as_bool() {
+        sensors.push(sensor("health_passed", if passed { 1.0 } else { 0.0 }, ""));
+    }
+    if let Some(table) = json["ata_smart_attributes"]["table"].as_array() {
+        for (id, suffix) in ATA_ATTRIBUTES {
+            let raw = table
+                .iter()
+                .find(|attribute| attribute["id"].as_u64() == Some(id))
+                .and_then(|attribute| attribute["raw"]["value"].as_f64());
+            if let Some(raw) = raw {
+                sensors.push(sensor(suffix, raw, ""));
+            }
+        }
+    }
+    let nvme_log = &json["nvme_smart_health_information_log"];
+    if let Some(media_errors) = nvme_log["media_errors"].as_f64() {
+        sensors.push(sensor("media_errors", media_errors, ""));
+    }
+    if let Some(used) = nvme_log["percentage_used"].as_f64() {
+        sensors.push(sensor("percentage_used", used, "%"));
+    }
+    sensors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn values(sensors: &[SystemSensor]) -> Vec<(&str, f64)> {
+        sensors.iter().map(|s| (s.name.as_str(), s.value)).collect()
+    }
+
+    #[test]
+    fn test_ata_smart_sensors() {
+        let output = json!({
+            "smart_status": { "passed": true },
+            "temperature": { "current": 34 },
+            "power_on_time": { "hours": 18210 },
+            "ata_smart_attributes": { "table": [
+                { "id": 5, "name": "Reallocated_Sector_Ct", "raw": { "value": 8 } },
+                { "id": 9, "name": "Power_On_Hours", "raw": { "value": 18210 } },
+                { "id": 197, "name": "Current_Pending_Sector", "raw": { "value": 0 } }
+            ]}
+        });
+
+        let sensors = smart_sensors("sda", &output);
+        assert_eq!(
+            values(&sensors),
+            [
+                ("smart_sda_temperature", 34.0),
+                ("smart_sda_power_on_hours", 18210.0),
+                ("smart_sda_health_passed", 1.0),
+                ("smart_sda_reallocated_sectors", 8.0),
+                ("smart_sda_pending_sectors", 0.0),
+            ]
+        );
+        assert_eq!(sensors[0].sensor_type, Temperature);
+        assert_eq!(sensors[1].sensor_type, SmartAttribute);
+    }
+
+    #[test]
+    fn test_nvme_smart_sensors() {
+        let output = json!({
+            "temperature": { "current": 41 },
+            "nvme_smart_health_information_log": { "media_errors": 0, "percentage_used": 3 }
+        });
+
+        assert_eq!(
+            values(&smart_sensors("nvme0", &output)),
+            [
+                ("smart_nvme0_temperature", 41.0),
+                ("smart_nvme0_media_errors", 0.0),
+                ("smart_nvme0_percentage_used", 3.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_smartctl_messages() {
+        let output = json!({ "smartctl": { "messages": [
+            { "string": "Smartctl open device: /dev/sda failed: Permission denied", "severity": "error" }
+        ]}});
+        assert_eq!(
+            smartctl_messages(&output).unwrap(),
+            "Smartctl open device: /dev/sda failed: Permission denied"
+        );
+        assert!(smartctl_messages(&json!({})).is_none());
+    }
+
+    #[test]
+    fn test_device_slug() {
+        assert_eq!(device_slug("/dev/sda"), "sda");
+        assert_eq!(device_slug("/dev/disk/by-id/ata-WDC_WD40"), "ata_wdc_wd40");
+    }
+}
@@ -0,0 +1,120 @@
+use crate::config::SmoothingConfig;
+use crate::sensors::{SystemSensor, SystemSensorType};
+use crate::utils::matches_pattern;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+// Smoothed value of every sensor, keyed by sensor name
+static EMA_STATE: OnceLock<Mutex<HashMap<String, f64>>> = OnceLock::new();
+
+// Exponential moving average: alpha 1.0 passes values through unchanged,
+// smaller values weigh history more heavily
+pub fn apply_ema(name: &str, new_value: f64, state: &mut HashMap<String, f64>, alpha: f64) -> f64 {
+    let smoothed = match state.get(name) {
+        Some(previous) => alpha * new_value + (1.0 - alpha) * previous,
+        None => new_value,
+    };
+    state.insert(name.to_string(), smoothed);
+    smoothed
+}
+
+// The first matching override wins, otherwise the global ema_alpha applies
+fn alpha_for(sensor: &SystemSensor, config: &SmoothingConfig) -> f64 {
+    config
+        .overrides
+        .iter()
+        .find(|o| {
+            o.sensor_pattern
+                .as_ref()
+                .is_none_or(|pattern| matches_pattern(pattern, &sensor.name))
+                && o.sensor_type.as_ref().is_none_or(|t| *t == sensor.sensor_type)
+        })
+        .map_or(config.ema_alpha, |o| o.ema_alpha)
+}
+
+pub fn smooth_sensor(sensor: &SystemSensor, config: &SmoothingConfig) -> SystemSensor {
+    let mut sensor = sensor.clone();
+    let alpha = alpha_for(&sensor, config);
+    // Smoothing the heartbeat timestamp would make it lag behind the clock, and a smoothed
+    // counter would no longer be a count
+    let unsmoothed = matches!(sensor.sensor_type, SystemSensorType::Heartbeat | SystemSensorType::DaemonMetric);
+    if sensor.extra.is_some() || unsmoothed || alpha >= 1.0 {
+        return sensor;
+    }
+
+    let mut state = EMA_STATE
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|p| p.into_inner());
+    sensor.value = apply_ema(&sensor.name, sensor.value, &mut state, alpha);
+    sensor
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SmoothingOverrideConfig;
+    use crate::sensors::SystemSensorType;
+
+    #[test]
+    fn test_apply_ema_known_alpha() {
+        let mut state = HashMap::new();
+
+        assert_eq!(apply_ema("fan", 1000.0, &mut state, 0.5), 1000.0);
+        assert_eq!(apply_ema("fan", 1200.0, &mut state, 0.5), 1100.0);
+        assert_eq!(apply_ema("fan", 1200.0, &mut state, 0.5), 1150.0);
+        assert_eq!(apply_ema("fan", 800.0, &mut state, 0.5), 975.0);
+    }
+
+    #[test]
+    fn test_apply_ema_converges_to_constant_input() {
+        let mut state = HashMap::new();
+        apply_ema("temp", 40.0, &mut state, 0.2);
+
+        let mut smoothed = 0.0;
+        for _ in 0..50 {
+            smoothed = apply_ema("temp", 50.0, &mut state, 0.2);
+        }
+        // The remaining error shrinks by (1 - alpha) every step
+        assert!((smoothed - 50.0).abs() < 10.0 * 0.8f64.powi(49));
+    }
+
+    #[test]
+    fn test_apply_ema_alpha_one_passes_through() {
+        let mut state = HashMap::new();
+        apply_ema("temp", 40.0, &mut state, 1.0);
+        assert_eq!(apply_ema("temp", 55.0, &mut state, 1.0), 55.0);
+    }
+
+    #[test]
+    fn test_alpha_for_overrides() {
+        let config = SmoothingConfig {
+            ema_alpha: 1.0,
+            overrides: vec![
+                SmoothingOverrideConfig {
+                    sensor_pattern: Some("nct6797_*".to_string()),
+                    sensor_type: None,
+                    ema_alpha: 0.3,
+                },
+                SmoothingOverrideConfig {
+                    sensor_pattern: None,
+                    sensor_type: Some(SystemSensorType::Fan),
+                    ema_alpha: 0.5,
+                },
+            ],
+        };
+        let sensor = |name: &str, sensor_type| SystemSensor {
+            name: name.to_string(),
+            label: None,
+            value: 0.0,
+            unit: "".to_string(),
+            sensor_type,
+            attributes: HashMap::new(),
+            extra: None,
+        };
+
+        assert_eq!(alpha_for(&sensor("nct6797_1", SystemSensorType::Temperature), &config), 0.3);
+        assert_eq!(alpha_for(&sensor("it8688_fan", SystemSensorType::Fan), &config), 0.5);
+        assert_eq!(alpha_for(&sensor("cpu_usage", SystemSensorType::CpuUsage), &config), 1.0);
+    }
+}
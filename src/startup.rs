@@ -0,0 +1,146 @@
+use crate::config::{DaemonConfig, StartupCheckMode};
+use crate::sensors::{SystemSensor, SystemSensorType};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StartupCheckLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct StartupWarning {
+    pub level: StartupCheckLevel,
+    pub message: String,
+}
+
+impl StartupWarning {
+    fn new(level: StartupCheckLevel, message: &str) -> Self {
+        Self {
+            level,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for StartupWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let level = match self.level {
+            StartupCheckLevel::Info => "INFO",
+            StartupCheckLevel::Warning => "WARN",
+            StartupCheckLevel::Error => "ERROR",
+        };
+        write!(f, "[{}] {}", level, self.message)
+    }
+}
+
+impl DaemonConfig {
+    pub fn run_startup_checks(&self, sensors: &[SystemSensor]) -> Vec<StartupWarning> {
+        let has_type = |sensor_type: SystemSensorType| {
+            sensors.iter().any(|sensor| sensor.sensor_type == sensor_type)
+        };
+
+        let mut warnings = Vec::new();
+
+        if self.sensors.enable_temperature && !has_type(SystemSensorType::Temperature) {
+            warnings.push(StartupWarning::new(
+                StartupCheckLevel::Warning,
+                "Temperature sensors are enabled but none were found (check hwmon permissions)",
+            ));
+        }
+        if self.sensors.enable_fan && !has_type(SystemSensorType::Fan) {
+            warnings.push(StartupWarning::new(
+                StartupCheckLevel::Info,
+                "Fan sensors are enabled but none were found",
+            ));
+        }
+        if !has_type(SystemSensorType::CpuUsage) {
+            warnings.push(StartupWarning::new(
+                StartupCheckLevel::Error,
+                "No CPU usage sensor was collected",
+            ));
+        }
+        if !has_type(SystemSensorType::MemoryUsage) {
+            warnings.push(StartupWarning::new(
+                StartupCheckLevel::Error,
+                "No memory usage sensor was collected",
+            ));
+        }
+
+        warnings
+    }
+}
+
+// Logs every startup check result and returns false when the daemon should exit
+pub fn report_startup_checks(config: &DaemonConfig, warnings: &[StartupWarning]) -> bool {
+    for warning in warnings {
+        match warning.level {
+            StartupCheckLevel::Info => tracing::info!("Startup check: {}", warning),
+            StartupCheckLevel::Warning => tracing::warn!("Startup check: {}", warning),
+            StartupCheckLevel::Error => tracing::error!("Startup check: {}", warning),
+        }
+    }
+
+    let has_errors = warnings
+        .iter()
+        .any(|warning| warning.level == StartupCheckLevel::Error);
+    !(has_errors && config.startup_check_mode == StartupCheckMode::Fail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn sensor(name: &str, sensor_type: SystemSensorType) -> SystemSensor {
+        SystemSensor {
+            name: name.to_string(),
+            label: None,
+            value: 0.0,
+            unit: "".to_string(),
+            sensor_type,
+            attributes: HashMap::new(),
+            extra: None,
+        }
+    }
+
+    #[test]
+    fn test_startup_checks_missing_sensors() {
+        let config = DaemonConfig::default();
+        let warnings = config.run_startup_checks(&[]);
+
+        let levels: Vec<StartupCheckLevel> = warnings.iter().map(|w| w.level.clone()).collect();
+        assert_eq!(
+            levels,
+            vec![
+                StartupCheckLevel::Warning,
+                StartupCheckLevel::Info,
+                StartupCheckLevel::Error,
+                StartupCheckLevel::Error
+            ]
+        );
+    }
+
+    #[test]
+    fn test_startup_checks_all_present() {
+        let mut config = DaemonConfig::default();
+        config.sensors.enable_fan = false;
+        let sensors = [
+            sensor("cpu_usage", SystemSensorType::CpuUsage),
+            sensor("memory_usage", SystemSensorType::MemoryUsage),
+            sensor("k10temp_1", SystemSensorType::Temperature),
+        ];
+
+        assert!(config.run_startup_checks(&sensors).is_empty());
+    }
+
+    #[test]
+    fn test_fail_mode_stops_on_errors() {
+        let mut config = DaemonConfig::default();
+        let warnings = config.run_startup_checks(&[]);
+        assert!(report_startup_checks(&config, &warnings));
+
+        config.startup_check_mode = StartupCheckMode::Fail;
+        assert!(!report_startup_checks(&config, &warnings));
+    }
+}
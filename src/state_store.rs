@@ -0,0 +1,166 @@
+use crate::config::{write_to_file_atomic, ConfigError};
+use crate::mqtt_client::MqttSensorTopics;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+// What the previous run published for a sensor. The discovery payload is stored as a hash,
+// so a changed discovery config (new device info, unit, ...) is still re-sent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SensorState {
+    pub discovery_hash: String,
+    pub last_state: Option<String>,
+}
+
+pub fn load_state(path: &Path) -> HashMap<String, SensorState> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return HashMap::new(),
+        Err(e) => {
+            tracing::warn!(path = %path.display(), error = %e, "Failed to read state file, re-sending discovery");
+            return HashMap::new();
+        }
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        tracing::warn!(path = %path.display(), error = %e, "Ignoring invalid state file");
+        HashMap::new()
+    })
+}
+
+pub fn save_state(path: &Path, sensors: &HashMap<String, SensorState>) -> Result<(), ConfigError> {
+    let content = serde_json::to_string(sensors)
+        .map_err(|e| ConfigError::Invalid(format!("failed to serialize sensor state: {}", e)))?;
+    write_to_file_atomic(path, &content)
+}
+
+fn discovery_hash(discovery_payload: &str) -> String {
+    Sha256::digest(discovery_payload.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+// Sensors discovered by the previous run, and what this run has published so far
+pub struct StateStore {
+    path: PathBuf,
+    restored: HashMap<String, SensorState>,
+    current: HashMap<String, SensorState>,
+    changed: bool,
+}
+
+impl StateStore {
+    pub fn open(path: &Path) -> Self {
+        let restored = load_state(path);
+        if !restored.is_empty() {
+            tracing::info!(path = %path.display(), sensors = restored.len(), "Loaded sensor state");
+        }
+        Self {
+            path: path.to_path_buf(),
+            restored,
+            current: HashMap::new(),
+            changed: false,
+        }
+    }
+
+    // Without a persistent session the broker can't be trusted to still have our discovery
+    pub fn discard_restored(&mut self) {
+        self.restored.clear();
+    }
+
+    // The previous state of a sensor whose discovery payload hasn't changed since
+    pub fn take_restored(&mut self, payload: &MqttSensorTopics) -> Option<SensorState> {
+        let previous = self.restored.remove(&payload.name)?;
+        (previous.discovery_hash == discovery_hash(&payload.discovery.payload)).then_some(previous)
+    }
+
+    pub fn record(&mut self, payload: &MqttSensorTopics) {
+        let discovery_hash = discovery_hash(&payload.discovery.payload);
+        // Only newly discovered sensors trigger a write, last values are saved on shutdown
+        if self
+            .current
+            .get(&payload.name)
+            .is_none_or(|state| state.discovery_hash != discovery_hash)
+        {
+            self.changed = true;
+        }
+        self.current.insert(
+            payload.name.clone(),
+            SensorState {
+                discovery_hash,
+                last_state: Some(payload.state.payload.clone()),
+            },
+        );
+    }
+
+    pub fn save_if_changed(&mut self) {
+        if self.changed {
+            self.save();
+        }
+    }
+
+    pub fn save(&mut self) {
+        match save_state(&self.path, &self.current) {
+            Ok(()) => self.changed = false,
+            Err(e) => tracing::warn!(error = %e, "Failed to save sensor state"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mqtt_client::{MqttPayload, PublishMode};
+    use rumqttc::QoS;
+
+    fn topics(name: &str, discovery: &str, state: &str) -> MqttSensorTopics {
+        let payload = |payload: &str| MqttPayload {
+            topic: format!("homeassistant/sensor/orbiq_test/{}", name),
+            payload: payload.to_string(),
+            retain: false,
+            qos: QoS::AtLeastOnce,
+            payload_bytes: None,
+        };
+        MqttSensorTopics {
+            name: name.to_string(),
+            value: None,
+            publish_mode: PublishMode::OnChange,
+            state: payload(state),
+            discovery: payload(discovery),
+            availability: None,
+        }
+    }
+
+    #[test]
+    fn test_state_store_round_trip() {
+        let path = std::env::temp_dir().join("orbiq_state_store_test.json");
+        let _ = fs::remove_file(&path);
+
+        let mut store = StateStore::open(&path);
+        store.record(&topics("cpu_usage", r#"{"name":"CPU Usage"}"#, r#"{"value":12.5}"#));
+        store.record(&topics("memory_usage", r#"{"name":"Memory Usage"}"#, r#"{"value":40.0}"#));
+        store.save_if_changed();
+
+        let mut store = StateStore::open(&path);
+        fs::remove_file(&path).unwrap();
+
+        let previous = store.take_restored(&topics("cpu_usage", r#"{"name":"CPU Usage"}"#, "")).unwrap();
+        assert_eq!(previous.last_state.as_deref(), Some(r#"{"value":12.5}"#));
+        // A changed discovery payload has to be re-sent
+        assert!(store.take_restored(&topics("memory_usage", r#"{"name":"RAM"}"#, "")).is_none());
+        assert!(store.take_restored(&topics("swap_usage", "{}", "")).is_none());
+    }
+
+    #[test]
+    fn test_missing_or_invalid_state_file() {
+        let path = std::env::temp_dir().join("orbiq_state_store_invalid.json");
+        let _ = fs::remove_file(&path);
+        assert!(load_state(&path).is_empty());
+
+        fs::write(&path, "not json").unwrap();
+        assert!(load_state(&path).is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,138 @@
+use crate::config::{ConfigError, StatsdConfig};
+use crate::sensors::SystemSensor;
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+
+// Keeps datagrams below a typical 1500 byte MTU
+const MAX_DATAGRAM_BYTES: usize = 1432;
+const FAILURE_LOG_INTERVAL: Duration = Duration::from_secs(300);
+
+// `:`, `|` and `@` delimit the value, type and sample rate
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if matches!(c, ':' | '|' | '@' | '#' | ' ' | '\n') { '_' } else { c })
+        .collect()
+}
+
+// `{prefix}.{device_name}.{sensor_name}:{value}|g` with optional DogStatsD tags
+pub fn gauge_line(prefix: &str, device_name: &str, sensor: &SystemSensor, tags: &[String]) -> Option<String> {
+    if sensor.extra.is_some() {
+        return None;
+    }
+    let mut line = format!(
+        "{}.{}.{}:{}|g",
+        prefix,
+        sanitize(device_name),
+        sanitize(&sensor.name),
+        sensor.value
+    );
+    if !tags.is_empty() {
+        line.push_str("|#");
+        line.push_str(&tags.join(","));
+    }
+    Some(line)
+}
+
+// Packs newline separated lines into as few datagrams as possible
+fn batch_lines(lines: impl Iterator<Item = String>) -> Vec<String> {
+    let mut batches: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for line in lines {
+        if !current.is_empty() && current.len() + line.len() + 1 > MAX_DATAGRAM_BYTES {
+            batches.push(std::mem::take(&mut current));
+        }
+        current.push_str(&line);
+        current.push('\n');
+    }
+    if !current.is_empty() {
+        batches.push(current);
+    }
+    batches
+}
+
+// Send errors are only counted, UDP delivery isn't guaranteed anyway
+pub struct StatsdWriter {
+    socket: UdpSocket,
+    target: String,
+    prefix: String,
+    tags: Vec<String>,
+    failures: u64,
+    last_failure_log: Instant,
+}
+
+impl StatsdWriter {
+    pub async fn from_config(config: &StatsdConfig) -> Result<Option<Self>, ConfigError> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let socket = UdpSocket::bind(("0.0.0.0", 0))
+            .await
+            .map_err(|e| ConfigError::Invalid(format!("statsd: failed to open UDP socket: {}", e)))?;
+        Ok(Some(StatsdWriter {
+            socket,
+            target: format!("{}:{}", config.host, config.port),
+            prefix: config.prefix.clone(),
+            tags: config.tags.clone(),
+            failures: 0,
+            last_failure_log: Instant::now(),
+        }))
+    }
+
+    pub async fn write(&mut self, sensors: &[SystemSensor], device_name: &str) {
+        let lines = sensors
+            .iter()
+            .filter_map(|sensor| gauge_line(&self.prefix, device_name, sensor, &self.tags));
+        for batch in batch_lines(lines) {
+            if self.socket.send_to(batch.as_bytes(), self.target.as_str()).await.is_err() {
+                self.failures += 1;
+            }
+        }
+
+        if self.failures > 0 && self.last_failure_log.elapsed() >= FAILURE_LOG_INTERVAL {
+            tracing::warn!(failures = self.failures, target = %self.target, "StatsD datagrams failed to send");
+            self.failures = 0;
+            self.last_failure_log = Instant::now();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sensors::SystemSensorType;
+    use std::collections::HashMap;
+
+    fn sensor(name: &str, value: f64) -> SystemSensor {
+        SystemSensor {
+            name: name.to_string(),
+            label: None,
+            value,
+            unit: "%".to_string(),
+            sensor_type: SystemSensorType::CpuUsage,
+            attributes: HashMap::new(),
+            extra: None,
+        }
+    }
+
+    #[test]
+    fn test_gauge_line() {
+        assert_eq!(
+            gauge_line("orbiq", "myhost", &sensor("cpu_usage", 23.4), &[]).as_deref(),
+            Some("orbiq.myhost.cpu_usage:23.4|g")
+        );
+        let tags = ["env:prod".to_string(), "rack:a1".to_string()];
+        assert_eq!(
+            gauge_line("orbiq", "my host", &sensor("cpu_usage", 5.0), &tags).as_deref(),
+            Some("orbiq.my_host.cpu_usage:5|g|#env:prod,rack:a1")
+        );
+    }
+
+    #[test]
+    fn test_batch_lines_splits_at_datagram_size() {
+        let line = "x".repeat(600);
+        let batches = batch_lines(std::iter::repeat_n(line, 5));
+        assert_eq!(batches.len(), 3);
+        assert!(batches.iter().all(|batch| batch.len() <= MAX_DATAGRAM_BYTES));
+        assert_eq!(batches.iter().map(|batch| batch.lines().count()).sum::<usize>(), 5);
+    }
+}
@@ -0,0 +1,42 @@
+use std::path::Path;
+use std::time::Duration;
+use tokio::time;
+
+// Reads a sysfs/procfs attribute, trimmed. Some drivers block reads indefinitely (e.g. a
+// sleeping NVMe drive), so a read taking longer than `timeout` is abandoned and the
+// sensor skipped for this cycle. Other read errors are not logged since many attributes
+// are optional.
+pub async fn read_attribute(path: &Path, timeout: Duration) -> Option<String> {
+    match time::timeout(timeout, tokio::fs::read_to_string(path)).await {
+        Ok(Ok(content)) => Some(content.trim().to_string()),
+        Ok(Err(_)) => None,
+        Err(_) => {
+            tracing::warn!(path = %path.display(), timeout_ms = timeout.as_millis() as u64, "Sensor read timed out, skipping it this cycle");
+            None
+        }
+    }
+}
+
+pub async fn read_number(path: &Path, timeout: Duration) -> Option<f64> {
+    read_attribute(path, timeout).await?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[tokio::test]
+    async fn test_read_attribute() {
+        let dir = std::env::temp_dir().join("orbiq_sysfs_test");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("temp1_input"), "45500\n").unwrap();
+
+        let timeout = Duration::from_millis(500);
+        assert_eq!(read_attribute(&dir.join("temp1_input"), timeout).await.as_deref(), Some("45500"));
+        assert_eq!(read_number(&dir.join("temp1_input"), timeout).await, Some(45500.0));
+        assert_eq!(read_attribute(&dir.join("missing"), timeout).await, None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,5 +1,22 @@
-use sysinfo::{Disks, System};
-use crate::sensors::{SystemSensor, SystemSensorType};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use nix::sys::statvfs::statvfs;
+use sysinfo::{Disks, Process, System};
+use crate::config::{DaemonConfig, SensorsConfig};
+use crate::naming::sanitize_sensor_name;
+use crate::sensors::{CollectFuture, SensorCollector, SystemSensor, SystemSensorType};
+use crate::sysfs::read_attribute;
+
+const FILE_NR_PATH: &str = "/proc/sys/fs/file-nr";
+const ENTROPY_AVAIL_PATH: &str = "/proc/sys/kernel/random/entropy_avail";
+const ENTROPY_POOLSIZE_PATH: &str = "/proc/sys/kernel/random/poolsize";
+const HOSTNAME_PATH: &str = "/proc/sys/kernel/hostname";
+const OS_RELEASE_PATH: &str = "/etc/os-release";
+
+// Hostname, OS release and kernel don't change while the daemon runs, so they are read once
+static SYSTEM_INFO: OnceLock<Vec<SystemSensor>> = OnceLock::new();
 
 // Helper function to round to specified decimal places
 fn round_to_decimals(value: f64, decimals: u32) -> f64 {
@@ -7,9 +24,24 @@ fn round_to_decimals(value: f64, decimals: u32) -> f64 {
     (value * multiplier).round() / multiplier
 }
 
+// CPU, memory, swap, disk, file descriptor, entropy and uptime sensors
+pub struct SystemStatsCollector {
+    pub sensors_config: SensorsConfig,
+    pub read_timeout: Duration,
+}
 
+impl SensorCollector for SystemStatsCollector {
+    fn collect(&self) -> CollectFuture<'_> {
+        Box::pin(collect_system_stats(&self.sensors_config, self.read_timeout))
+    }
 
-pub fn collect_system_stats() -> Vec<SystemSensor> {
+    fn name(&self) -> &str {
+        "system"
+    }
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
+async fn collect_system_stats(sensors_config: &SensorsConfig, read_timeout: Duration) -> Vec<SystemSensor> {
     let mut system = System::new_all();
     system.refresh_all();
 
@@ -23,8 +55,25 @@ pub fn collect_system_stats() -> Vec<SystemSensor> {
         value: round_to_decimals(cpu_usage as f64, 1),
         unit: "%".to_string(),
         sensor_type: SystemSensorType::CpuUsage,
+        attributes: HashMap::new(),
+        extra: None,
     });
 
+    // Per-core usage is opt-in since large machines would flood the broker
+    if sensors_config.per_core_cpu {
+        for (index, cpu) in system.cpus().iter().enumerate() {
+            sensors.push(SystemSensor {
+                name: format!("cpu_core_{}_usage", index),
+                label: None,
+                value: round_to_decimals(cpu.cpu_usage() as f64, 1),
+                unit: "%".to_string(),
+                sensor_type: SystemSensorType::CpuCoreUsage,
+                attributes: HashMap::new(),
+                extra: None,
+            });
+        }
+    }
+
     // Memory usage - rounded to 1 decimal place
     let total_memory = system.total_memory();
     let used_memory = system.used_memory();
@@ -41,6 +90,8 @@ pub fn collect_system_stats() -> Vec<SystemSensor> {
         value: memory_usage_percent,
         unit: "%".to_string(),
         sensor_type: SystemSensorType::MemoryUsage,
+        attributes: HashMap::new(),
+        extra: None,
     });
 
     sensors.push(SystemSensor {
@@ -49,6 +100,8 @@ pub fn collect_system_stats() -> Vec<SystemSensor> {
         value: round_to_decimals((used_memory as f64) / (1024.0 * 1024.0 * 1024.0), 2),
         unit: "GB".to_string(),
         sensor_type: SystemSensorType::MemoryUsed,
+        attributes: HashMap::new(),
+        extra: None,
     });
 
     sensors.push(SystemSensor {
@@ -57,8 +110,99 @@ pub fn collect_system_stats() -> Vec<SystemSensor> {
         value: round_to_decimals((total_memory as f64) / (1024.0 * 1024.0 * 1024.0), 2),
         unit: "GB".to_string(),
         sensor_type: SystemSensorType::MemoryTotal,
+        attributes: HashMap::new(),
+        extra: None,
+    });
+
+    sensors.push(SystemSensor {
+        name: "system_uptime".to_string(),
+        label: None,
+        value: System::uptime() as f64,
+        unit: "s".to_string(),
+        sensor_type: SystemSensorType::Uptime,
+        attributes: HashMap::new(),
+        extra: None,
+    });
+
+    // Unix timestamp of this cycle, published every cycle for watchdog automations
+    let heartbeat = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_secs());
+    sensors.push(SystemSensor {
+        name: "device_heartbeat".to_string(),
+        label: None,
+        value: heartbeat as f64,
+        unit: "".to_string(),
+        sensor_type: SystemSensorType::Heartbeat,
+        attributes: HashMap::new(),
+        extra: None,
     });
 
+    // Load averages
+    let load_average = System::load_average();
+    let windows = [
+        ("1m", load_average.one),
+        ("5m", load_average.five),
+        ("15m", load_average.fifteen),
+    ];
+    for (window, value) in windows {
+        sensors.push(SystemSensor {
+            name: format!("load_avg_{}", window),
+            label: None,
+            value: round_to_decimals(value, 2),
+            unit: "".to_string(),
+            sensor_type: SystemSensorType::LoadAverage,
+            attributes: HashMap::new(),
+            extra: None,
+        });
+    }
+
+    // Swap - skipped entirely on machines without swap so it never shows up in discovery
+    let total_swap = system.total_swap();
+    let used_swap = system.used_swap();
+    if total_swap > 0 {
+        let swap_usage_percent =
+            round_to_decimals((used_swap as f64 / total_swap as f64) * 100.0, 1);
+
+        sensors.push(SystemSensor {
+            name: "swap_usage".to_string(),
+            label: None,
+            value: swap_usage_percent,
+            unit: "%".to_string(),
+            sensor_type: SystemSensorType::SwapUsage,
+            attributes: HashMap::new(),
+            extra: None,
+        });
+
+        sensors.push(SystemSensor {
+            name: "swap_used".to_string(),
+            label: None,
+            value: round_to_decimals((used_swap as f64) / (1024.0 * 1024.0 * 1024.0), 2),
+            unit: "GB".to_string(),
+            sensor_type: SystemSensorType::SwapUsed,
+            attributes: HashMap::new(),
+            extra: None,
+        });
+
+        sensors.push(SystemSensor {
+            name: "swap_total".to_string(),
+            label: None,
+            value: round_to_decimals((total_swap as f64) / (1024.0 * 1024.0 * 1024.0), 2),
+            unit: "GB".to_string(),
+            sensor_type: SystemSensorType::SwapTotal,
+            attributes: HashMap::new(),
+            extra: None,
+        });
+    }
+
+    // sysinfo lists threads as processes too on Linux, so only count the thread group leaders
+    let processes: Vec<&Process> = system
+        .processes()
+        .values()
+        .filter(|process| process.thread_kind().is_none())
+        .collect();
+    sensors.extend(process_sensors(processes.len(), count_threads(&processes)));
+
     // Disk usage for all mounted disks
     let disks = Disks::new_with_refreshed_list();
     for disk in &disks {
@@ -66,10 +210,7 @@ pub fn collect_system_stats() -> Vec<SystemSensor> {
         let name_suffix = if mount_point == "/" {
             "root".to_string()
         } else {
-            mount_point
-                .replace(['/', ' '], "_")
-                .trim_matches('_')
-                .to_string()
+            sanitize_sensor_name(&mount_point.replace('/', "_"))
         };
 
         let total_space = disk.total_space();
@@ -89,6 +230,8 @@ pub fn collect_system_stats() -> Vec<SystemSensor> {
             value: usage_percent,
             unit: "%".to_string(),
             sensor_type: SystemSensorType::DiskUsage,
+            attributes: HashMap::new(),
+            extra: None,
         });
 
         sensors.push(SystemSensor {
@@ -97,6 +240,8 @@ pub fn collect_system_stats() -> Vec<SystemSensor> {
             value: round_to_decimals((used_space as f64) / (1024.0 * 1024.0 * 1024.0), 2),
             unit: "GB".to_string(),
             sensor_type: SystemSensorType::DiskUsed,
+            attributes: HashMap::new(),
+            extra: None,
         });
 
         sensors.push(SystemSensor {
@@ -105,8 +250,337 @@ pub fn collect_system_stats() -> Vec<SystemSensor> {
             value: round_to_decimals((total_space as f64) / (1024.0 * 1024.0 * 1024.0), 2),
             unit: "GB".to_string(),
             sensor_type: SystemSensorType::DiskTotal,
+            attributes: HashMap::new(),
+            extra: None,
+        });
+
+        if sensors_config.disk_inodes {
+            // fsfilcnt_t is only 32 bits wide on some targets
+            #[allow(clippy::unnecessary_cast)]
+            let inode_usage = statvfs(disk.mount_point())
+                .ok()
+                .and_then(|stats| inode_usage_percent(stats.files() as u64, stats.files_free() as u64));
+            if let Some(percent) = inode_usage {
+                sensors.push(SystemSensor {
+                    name: format!("disk_inodes_pct_{}", name_suffix),
+                    label: None,
+                    value: percent,
+                    unit: "%".to_string(),
+                    sensor_type: SystemSensorType::DiskInodes,
+                    attributes: HashMap::new(),
+                    extra: None,
+                });
+            }
+        }
+    }
+
+    if let Some(content) = read_attribute(Path::new(FILE_NR_PATH), read_timeout).await {
+        sensors.extend(file_descriptor_sensors(&content));
+    }
+
+    if let Some(entropy_avail) = read_attribute(Path::new(ENTROPY_AVAIL_PATH), read_timeout).await {
+        let poolsize = read_attribute(Path::new(ENTROPY_POOLSIZE_PATH), read_timeout)
+            .await
+            .unwrap_or_default();
+        sensors.extend(entropy_sensors(
+            &entropy_avail,
+            &poolsize,
+            sensors_config.low_entropy_threshold,
+        ));
+    }
+
+    sensors
+}
+
+// Diagnostic sensors identifying the running configuration
+pub fn collect_config_info(config: &DaemonConfig) -> Vec<SystemSensor> {
+    let mut sensors = Vec::new();
+
+    if let Some(hash) = config.fingerprint() {
+        sensors.push(SystemSensor {
+            name: "orbiq_config_hash".to_string(),
+            label: None,
+            value: 0.0,
+            unit: "".to_string(),
+            sensor_type: SystemSensorType::ConfigInfo,
+            attributes: HashMap::new(),
+            extra: Some(hash),
         });
     }
 
+    let config_file = match &config.source_path {
+        Some(path) => path.display().to_string(),
+        None => "defaults".to_string(),
+    };
+    sensors.push(SystemSensor {
+        name: "orbiq_config_file".to_string(),
+        label: None,
+        value: 0.0,
+        unit: "".to_string(),
+        sensor_type: SystemSensorType::ConfigInfo,
+        attributes: HashMap::new(),
+        extra: Some(config_file),
+    });
+
     sensors
-}
\ No newline at end of file
+}
+
+// Text sensors describing the machine, read on the first call and cached afterwards
+pub async fn collect_system_info(read_timeout: Duration) -> Vec<SystemSensor> {
+    if let Some(sensors) = SYSTEM_INFO.get() {
+        return sensors.clone();
+    }
+
+    let hostname = read_attribute(Path::new(HOSTNAME_PATH), read_timeout)
+        .await
+        .or_else(System::host_name);
+    let os_release = read_attribute(Path::new(OS_RELEASE_PATH), read_timeout)
+        .await
+        .and_then(|content| parse_pretty_name(&content))
+        .or_else(System::long_os_version);
+
+    let sensors = [
+        ("system_hostname", hostname),
+        ("os_release", os_release),
+        ("kernel_version", System::kernel_version()),
+    ]
+    .into_iter()
+    .filter_map(|(name, text)| {
+        Some(SystemSensor {
+            name: name.to_string(),
+            label: None,
+            value: 0.0,
+            unit: "".to_string(),
+            sensor_type: SystemSensorType::SystemInfo,
+            attributes: HashMap::new(),
+            extra: Some(text.filter(|text| !text.is_empty())?),
+        })
+    })
+    .collect::<Vec<_>>();
+
+    SYSTEM_INFO.get_or_init(|| sensors).clone()
+}
+
+// PRETTY_NAME="Ubuntu 24.04.1 LTS" -> Ubuntu 24.04.1 LTS
+fn parse_pretty_name(os_release: &str) -> Option<String> {
+    os_release.lines().find_map(|line| {
+        let value = line.trim().strip_prefix("PRETTY_NAME=")?;
+        Some(value.trim_matches(|c| c == '"' || c == '\'').to_string())
+    })
+}
+
+// Filesystems without a fixed inode table (btrfs, most pseudo filesystems) report 0 inodes
+fn inode_usage_percent(total: u64, free: u64) -> Option<f64> {
+    if total == 0 {
+        return None;
+    }
+    let used = total.saturating_sub(free);
+    Some(round_to_decimals(used as f64 / total as f64 * 100.0, 1))
+}
+
+// Parses the three values of /proc/sys/fs/file-nr: allocated, unused and max
+fn parse_file_nr(content: &str) -> Option<(u64, u64, u64)> {
+    let mut values = content.split_whitespace().map(|v| v.parse::<u64>().ok());
+    let allocated = values.next()??;
+    let unused = values.next()??;
+    let max = values.next()??;
+    Some((allocated, unused, max))
+}
+
+fn file_descriptor_sensors(content: &str) -> Vec<SystemSensor> {
+    let Some((allocated, unused, max)) = parse_file_nr(content) else {
+        return Vec::new();
+    };
+
+    let open = allocated.saturating_sub(unused);
+    let usage_percent = if max > 0 {
+        round_to_decimals((open as f64 / max as f64) * 100.0, 1)
+    } else {
+        0.0
+    };
+
+    vec![
+        SystemSensor {
+            name: "fd_open_count".to_string(),
+            label: None,
+            value: open as f64,
+            unit: "".to_string(),
+            sensor_type: SystemSensorType::FileDescriptors,
+            attributes: HashMap::new(),
+            extra: None,
+        },
+        SystemSensor {
+            name: "fd_max_count".to_string(),
+            label: None,
+            value: max as f64,
+            unit: "".to_string(),
+            sensor_type: SystemSensorType::FileDescriptors,
+            attributes: HashMap::new(),
+            extra: None,
+        },
+        SystemSensor {
+            name: "fd_usage_pct".to_string(),
+            label: None,
+            value: usage_percent,
+            unit: "%".to_string(),
+            sensor_type: SystemSensorType::FileDescriptors,
+            attributes: HashMap::new(),
+            extra: None,
+        },
+    ]
+}
+
+// tasks() excludes the main thread and is None on platforms without per-process task lists
+fn count_threads(processes: &[&Process]) -> Option<u64> {
+    let mut total = 0;
+    let mut any_tasks = false;
+    for process in processes {
+        let tasks = match process.tasks() {
+            Some(tasks) => {
+                any_tasks = true;
+                tasks.len() as u64
+            }
+            None => 0,
+        };
+        total += tasks + 1;
+    }
+    any_tasks.then_some(total)
+}
+
+fn process_sensors(process_count: usize, thread_count: Option<u64>) -> Vec<SystemSensor> {
+    let mut sensors = vec![SystemSensor {
+        name: "process_count".to_string(),
+        label: None,
+        value: process_count as f64,
+        unit: "".to_string(),
+        sensor_type: SystemSensorType::ProcessCount,
+        attributes: HashMap::new(),
+        extra: None,
+    }];
+
+    if let Some(threads) = thread_count {
+        sensors.push(SystemSensor {
+            name: "thread_count".to_string(),
+            label: None,
+            value: threads as f64,
+            unit: "".to_string(),
+            sensor_type: SystemSensorType::ThreadCount,
+            attributes: HashMap::new(),
+            extra: None,
+        });
+    }
+
+    sensors
+}
+
+fn entropy_sensors(entropy_avail: &str, poolsize: &str, low_threshold: u64) -> Vec<SystemSensor> {
+    let Ok(entropy_bits) = entropy_avail.trim().parse::<u64>() else {
+        return Vec::new();
+    };
+
+    let mut sensors = vec![SystemSensor {
+        name: "kernel_entropy_bits".to_string(),
+        label: None,
+        value: entropy_bits as f64,
+        unit: "bits".to_string(),
+        sensor_type: SystemSensorType::Entropy,
+        attributes: HashMap::new(),
+        extra: None,
+    }];
+
+    if let Ok(pool_bits) = poolsize.trim().parse::<u64>()
+        && pool_bits > 0
+    {
+        sensors.push(SystemSensor {
+            name: "entropy_pct".to_string(),
+            label: None,
+            value: round_to_decimals((entropy_bits as f64 / pool_bits as f64) * 100.0, 1),
+            unit: "%".to_string(),
+            sensor_type: SystemSensorType::Entropy,
+            attributes: HashMap::new(),
+            extra: None,
+        });
+    }
+
+    sensors.push(SystemSensor {
+        name: "low_entropy_alert".to_string(),
+        label: None,
+        value: if entropy_bits < low_threshold { 1.0 } else { 0.0 },
+        unit: "".to_string(),
+        sensor_type: SystemSensorType::EntropyAlert,
+        attributes: HashMap::new(),
+        extra: None,
+    });
+
+    sensors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inode_usage_percent() {
+        assert_eq!(inode_usage_percent(1000, 250), Some(75.0));
+        assert_eq!(inode_usage_percent(3, 2), Some(33.3));
+        assert_eq!(inode_usage_percent(0, 0), None);
+    }
+
+    #[test]
+    fn test_parse_file_nr() {
+        assert_eq!(parse_file_nr("9344\t0\t1000\n"), Some((9344, 0, 1000)));
+        assert_eq!(parse_file_nr("garbage"), None);
+    }
+
+    #[test]
+    fn test_file_descriptor_sensors() {
+        let sensors = file_descriptor_sensors("2500\t500\t10000\n");
+        assert_eq!(sensors.len(), 3);
+        assert_eq!(sensors[0].name, "fd_open_count");
+        assert_eq!(sensors[0].value, 2000.0);
+        assert_eq!(sensors[1].name, "fd_max_count");
+        assert_eq!(sensors[1].value, 10000.0);
+        assert_eq!(sensors[2].name, "fd_usage_pct");
+        assert_eq!(sensors[2].value, 20.0);
+    }
+
+    #[test]
+    fn test_parse_pretty_name() {
+        let os_release = "NAME=\"Ubuntu\"\nVERSION_ID=\"24.04\"\nPRETTY_NAME=\"Ubuntu 24.04.1 LTS\"\nID=ubuntu\n";
+        assert_eq!(parse_pretty_name(os_release), Some("Ubuntu 24.04.1 LTS".to_string()));
+        assert_eq!(parse_pretty_name("PRETTY_NAME='Debian GNU/Linux 12'"), Some("Debian GNU/Linux 12".to_string()));
+        assert_eq!(parse_pretty_name("NAME=Arch"), None);
+    }
+
+    #[test]
+    fn test_process_sensors() {
+        let sensors = process_sensors(312, Some(1480));
+        assert_eq!(sensors.len(), 2);
+        assert_eq!(sensors[0].name, "process_count");
+        assert_eq!(sensors[0].value, 312.0);
+        assert_eq!(sensors[1].name, "thread_count");
+        assert_eq!(sensors[1].value, 1480.0);
+
+        let sensors = process_sensors(312, None);
+        assert_eq!(sensors.len(), 1);
+        assert_eq!(sensors[0].sensor_type, SystemSensorType::ProcessCount);
+    }
+
+    #[test]
+    fn test_entropy_sensors() {
+        let sensors = entropy_sensors("1024\n", "4096\n", 128);
+        assert_eq!(sensors.len(), 3);
+        assert_eq!(sensors[0].name, "kernel_entropy_bits");
+        assert_eq!(sensors[0].value, 1024.0);
+        assert_eq!(sensors[1].name, "entropy_pct");
+        assert_eq!(sensors[1].value, 25.0);
+        assert_eq!(sensors[2].name, "low_entropy_alert");
+        assert_eq!(sensors[2].value, 0.0);
+        assert_eq!(sensors[2].sensor_type, SystemSensorType::EntropyAlert);
+
+        let sensors = entropy_sensors("64\n", "", 128);
+        assert_eq!(sensors.len(), 2);
+        assert_eq!(sensors[1].name, "low_entropy_alert");
+        assert_eq!(sensors[1].value, 1.0);
+    }
+}
@@ -1,87 +1,170 @@
+use std::collections::HashMap;
 use std::fs;
-use std::path::{Path};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::config::TemperatureUnit;
 use crate::hwmon_devices::{discover_hwmon_devices, HwmonDevice};
-use crate::sensors::SystemSensor;
-use crate::sensors::SystemSensorType::Temperature;
+use crate::naming::sanitize_sensor_name;
+use crate::sensors::{CollectFuture, SensorCollector, SystemSensor};
+use crate::sysfs::read_attribute;
+use crate::sensors::SystemSensorType::{Temperature, TemperatureLimit};
 
 const TEMP_FILE_PREFIX: &str = "temp";
 const TEMP_FILE_SUFFIX: &str = "_input";
 const MILLIDEGREE_TO_CELSIUS: f32 = 1000.0;
+// Companion limit files published as <sensor>_max / <sensor>_crit
+const LIMIT_SUFFIXES: [&str; 2] = ["max", "crit"];
+// Intel per-package and per-core sensors, labelled "Package id N" / "Core N"
+const CORETEMP_DEVICE: &str = "coretemp";
+// AMD GPUs label their sensors "edge", "junction" and "mem"
+const AMDGPU_DEVICE: &str = "amdgpu";
 
+pub struct TemperatureCollector {
+    pub temperature_limits: bool,
+    pub hwmon_cache_ttl: Duration,
+    pub read_timeout: Duration,
+}
+
+impl SensorCollector for TemperatureCollector {
+    fn collect(&self) -> CollectFuture<'_> {
+        Box::pin(collect_all_temperatures(self.temperature_limits, self.hwmon_cache_ttl, self.read_timeout))
+    }
+
+    fn name(&self) -> &str {
+        "temperature"
+    }
+}
 
-pub fn collect_all_temperatures() -> Vec<SystemSensor> {
+#[tracing::instrument(name = "collect_temperatures", level = "debug", skip_all)]
+async fn collect_all_temperatures(
+    temperature_limits: bool,
+    hwmon_cache_ttl: Duration,
+    read_timeout: Duration,
+) -> Vec<SystemSensor> {
     let mut sensors = Vec::new();
 
-    match discover_hwmon_devices() {
+    match discover_hwmon_devices(hwmon_cache_ttl) {
         Ok(devices) => {
             for device in devices {
-                let device_sensors = scan_device_temperatures(&device);
+                let device_sensors = scan_device_temperatures(&device, temperature_limits, read_timeout).await;
                 sensors.extend(device_sensors);
             }
         }
         Err(e) => {
-            eprintln!("Failed to discover hwmon devices: {}", e);
+            tracing::warn!(error = %e, "Failed to discover hwmon devices");
         }
     }
 
     sensors
 }
 
-fn scan_device_temperatures(device: &HwmonDevice) -> Vec<SystemSensor> {
+async fn scan_device_temperatures(
+    device: &HwmonDevice,
+    temperature_limits: bool,
+    read_timeout: Duration,
+) -> Vec<SystemSensor> {
     let mut sensors = Vec::new();
 
     match fs::read_dir(&device.path) {
         Ok(entries) => {
             for entry in entries.flatten() {
-                if let Some(sensor) = process_temperature_file(&entry.path(), device) {
+                if let Some(sensor) = process_temperature_file(&entry.path(), device, read_timeout).await {
+                    if temperature_limits {
+                        sensors.extend(temperature_limit_sensors(&entry.path(), &sensor, read_timeout).await);
+                    }
                     sensors.push(sensor);
                 }
             }
         }
         Err(e) => {
-            eprintln!(
-                "Failed to read device directory {}: {}",
-                device.path.display(),
-                e
-            );
+            tracing::warn!(device = %device.path.display(), error = %e, "Failed to read device directory");
         }
     }
 
     sensors
 }
 
-fn process_temperature_file(file_path: &Path, device: &HwmonDevice) -> Option<SystemSensor> {
+async fn process_temperature_file(file_path: &Path, device: &HwmonDevice, read_timeout: Duration) -> Option<SystemSensor> {
     let filename = file_path.file_name()?.to_string_lossy();
 
     if !is_temperature_file(&filename) {
         return None;
     }
 
-    let temperature = read_temperature_value(file_path)?;
+    let temperature = read_temperature_value(file_path, read_timeout).await?;
     let temp_number = extract_temperature_number(&filename)?;
-    let sensor_name = format!("{}_{}", device.name, temp_number);
-    let label = get_temperature_label(file_path);
+    let sensor_name = sanitize_sensor_name(&format!("{}_{}", device.name, temp_number));
+    let label = get_temperature_label(file_path, read_timeout)
+        .await
+        .map(|label| device_label(&device.name, &label).unwrap_or(label));
     
     Some(SystemSensor {
         name: sensor_name,
         label,
         value: temperature as f64,
-        unit: "°C".parse().unwrap(),
+        unit: "°C".to_string(),
         sensor_type: Temperature,
+        attributes: HashMap::new(),
+        extra: None,
     })
 }
 
-fn is_temperature_file(filename: &str) -> bool {
+async fn temperature_limit_sensors(file_path: &Path, sensor: &SystemSensor, read_timeout: Duration) -> Vec<SystemSensor> {
+    let mut sensors = Vec::new();
+    for suffix in LIMIT_SUFFIXES {
+        let Some(limit_path) = limit_file_path(file_path, suffix) else {
+            continue;
+        };
+        let Some(limit) = read_temperature_value(&limit_path, read_timeout).await else {
+            continue;
+        };
+        sensors.push(SystemSensor {
+            name: format!("{}_{}", sensor.name, suffix),
+            label: sensor.label.as_ref().map(|label| limit_label(label, suffix)),
+            value: limit as f64,
+            unit: "°C".to_string(),
+            sensor_type: TemperatureLimit,
+            attributes: HashMap::new(),
+            extra: None,
+        });
+    }
+    sensors
+}
+
+// Every collector reports Celsius; hwmon, iio, SMART, 1-Wire and remote temperatures
+// are converted together once collected
+pub fn convert_temperatures(sensors: &mut [SystemSensor], unit: TemperatureUnit) {
+    if unit == TemperatureUnit::C {
+        return;
+    }
+    for sensor in sensors.iter_mut().filter(|sensor| sensor.unit == "°C") {
+        sensor.value = (celsius_to_fahrenheit(sensor.value) * 100.0).round() / 100.0;
+        sensor.unit = "°F".to_string();
+    }
+}
+
+fn celsius_to_fahrenheit(celsius: f64) -> f64 {
+    (celsius * 9.0 / 5.0) + 32.0
+}
+
+// temp1_input -> temp1_max
+fn limit_file_path(file_path: &Path, suffix: &str) -> Option<PathBuf> {
+    let filename = file_path.file_name()?.to_string_lossy();
+    let base = filename.strip_suffix(TEMP_FILE_SUFFIX)?;
+    Some(file_path.with_file_name(format!("{}_{}", base, suffix)))
+}
+
+pub fn is_temperature_file(filename: &str) -> bool {
     filename.starts_with(TEMP_FILE_PREFIX) && filename.ends_with(TEMP_FILE_SUFFIX)
 }
 
-fn read_temperature_value(file_path: &Path) -> Option<f32> {
-    let temp_raw = fs::read_to_string(file_path).ok()?;
+async fn read_temperature_value(file_path: &Path, read_timeout: Duration) -> Option<f32> {
+    let temp_raw = read_attribute(file_path, read_timeout).await?;
     let temp_millidegrees = temp_raw.trim().parse::<f32>().ok()?;
     Some(temp_millidegrees / MILLIDEGREE_TO_CELSIUS)
 }
 
-fn extract_temperature_number(filename: &str) -> Option<String> {
+pub fn extract_temperature_number(filename: &str) -> Option<String> {
     Some(
         filename
             .replace(TEMP_FILE_PREFIX, "")
@@ -89,12 +172,49 @@ fn extract_temperature_number(filename: &str) -> Option<String> {
     )
 }
 
-fn get_temperature_label(file_path: &Path) -> Option<String> {
+// Readable names for drivers whose hwmon labels mean little on their own
+pub fn device_label(device_name: &str, label: &str) -> Option<String> {
+    match device_name {
+        CORETEMP_DEVICE => coretemp_label(label),
+        AMDGPU_DEVICE => amdgpu_label(label),
+        _ => None,
+    }
+}
+
+fn amdgpu_label(label: &str) -> Option<String> {
+    let sensor = match label {
+        "edge" => "Edge",
+        "junction" => "Junction",
+        "mem" => "Memory",
+        _ => return None,
+    };
+    Some(format!("AMD GPU {} Temperature", sensor))
+}
+
+// coretemp numbers its tempN files across all packages, so the label is the only place
+// saying which core a reading belongs to
+fn coretemp_label(label: &str) -> Option<String> {
+    if let Some(package) = label.strip_prefix("Package id ") {
+        return Some(format!("Intel CPU Package {} Temperature", package.trim()));
+    }
+    let core = label.strip_prefix("Core ")?.trim();
+    core.parse::<u32>().ok()?;
+    Some(format!("Intel Core {} Temperature", core))
+}
+
+// Keeps a limit apart from the reading it belongs to when both share an hwmon label
+fn limit_label(label: &str, suffix: &str) -> String {
+    match suffix {
+        "crit" => format!("{} Critical", label),
+        _ => format!("{} Max", label),
+    }
+}
+
+async fn get_temperature_label(file_path: &Path, read_timeout: Duration) -> Option<String> {
     let filename = file_path.file_name()?.to_string_lossy();
     let label_filename = filename.replace("_input", "_label");
     let label_path = file_path.with_file_name(label_filename);
-    let label_raw = fs::read_to_string(label_path).ok()?;
-    Some(label_raw.trim().to_string())
+    read_attribute(&label_path, read_timeout).await
 }
 
 #[cfg(test)]
@@ -109,6 +229,57 @@ mod tests {
         assert!(!is_temperature_file("fan1_input"));
     }
 
+    #[test]
+    fn test_celsius_to_fahrenheit() {
+        assert_eq!(celsius_to_fahrenheit(0.0), 32.0);
+        assert_eq!(celsius_to_fahrenheit(100.0), 212.0);
+        assert_eq!(celsius_to_fahrenheit(-40.0), -40.0);
+        assert_eq!(celsius_to_fahrenheit(37.0), 98.6);
+    }
+
+    #[test]
+    fn test_convert_temperatures() {
+        let sensor = |unit: &str, value| SystemSensor {
+            name: "k10temp_1".to_string(),
+            label: None,
+            value,
+            unit: unit.to_string(),
+            sensor_type: Temperature,
+            attributes: HashMap::new(),
+            extra: None,
+        };
+        let mut sensors = vec![sensor("°C", 45.5), sensor("%", 45.5)];
+
+        convert_temperatures(&mut sensors, TemperatureUnit::C);
+        assert_eq!(sensors[0].value, 45.5);
+
+        convert_temperatures(&mut sensors, TemperatureUnit::F);
+        assert_eq!((sensors[0].value, sensors[0].unit.as_str()), (113.9, "°F"));
+        assert_eq!((sensors[1].value, sensors[1].unit.as_str()), (45.5, "%"));
+    }
+
+    #[test]
+    fn test_limit_file_path() {
+        let path = Path::new("/sys/class/hwmon/hwmon2/temp3_input");
+        assert_eq!(
+            limit_file_path(path, "crit"),
+            Some(PathBuf::from("/sys/class/hwmon/hwmon2/temp3_crit"))
+        );
+        assert_eq!(limit_file_path(Path::new("/sys/class/hwmon/hwmon2/temp3_max"), "crit"), None);
+    }
+
+    #[test]
+    fn test_device_label() {
+        assert_eq!(coretemp_label("Package id 0").as_deref(), Some("Intel CPU Package 0 Temperature"));
+        assert_eq!(coretemp_label("Core 3").as_deref(), Some("Intel Core 3 Temperature"));
+        assert_eq!(coretemp_label("Core"), None);
+        assert_eq!(coretemp_label("Tctl"), None);
+        assert_eq!(device_label("amdgpu", "junction").as_deref(), Some("AMD GPU Junction Temperature"));
+        assert_eq!(device_label("amdgpu", "mem").as_deref(), Some("AMD GPU Memory Temperature"));
+        assert_eq!(device_label("nvme", "Composite"), None);
+        assert_eq!(limit_label("Intel Core 3 Temperature", "crit"), "Intel Core 3 Temperature Critical");
+    }
+
     #[test]
     fn test_extract_temperature_number() {
         assert_eq!(
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::naming::sanitize_sensor_name;
+use crate::sensors::{CollectFuture, SensorCollector, SystemSensor};
+use crate::sensors::SystemSensorType::Temperature;
+use crate::sysfs::{read_attribute, read_number};
+
+const THERMAL_PATH: &str = "/sys/class/thermal";
+const THERMAL_ZONE_PREFIX: &str = "thermal_zone";
+const MILLIDEGREE_TO_CELSIUS: f64 = 1000.0;
+
+// Thermal zones that aren't exposed through hwmon, e.g. x86_pkg_temp
+pub struct ThermalZoneCollector {
+    pub read_timeout: Duration,
+}
+
+impl SensorCollector for ThermalZoneCollector {
+    fn collect(&self) -> CollectFuture<'_> {
+        Box::pin(collect_thermal_zones(Path::new(THERMAL_PATH), self.read_timeout))
+    }
+
+    fn name(&self) -> &str {
+        "thermal_zone"
+    }
+}
+
+// Zones sharing a type get the same name and are resolved by sensors.dedup_strategy
+async fn collect_thermal_zones(thermal_path: &Path, read_timeout: Duration) -> Vec<SystemSensor> {
+    let mut sensors = Vec::new();
+    for zone in thermal_zones(thermal_path) {
+        // Some zones report EINVAL or ENODATA while their sensor is powered down
+        let Some(millidegrees) = read_number(&zone.join("temp"), read_timeout).await else {
+            continue;
+        };
+        let Some(zone_type) = read_attribute(&zone.join("type"), read_timeout).await else {
+            continue;
+        };
+        sensors.push(SystemSensor {
+            name: thermal_zone_name(&zone_type),
+            label: None,
+            value: millidegrees / MILLIDEGREE_TO_CELSIUS,
+            unit: "°C".to_string(),
+            sensor_type: Temperature,
+            attributes: HashMap::new(),
+            extra: None,
+        });
+    }
+    sensors
+}
+
+// thermal_zone0, thermal_zone1, ..., thermal_zone10 in numeric order. Zones registered
+// with hwmon (a hwmonN entry in the zone) are skipped, the temperature collector reports them
+fn thermal_zones(thermal_path: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(thermal_path) else {
+        return Vec::new();
+    };
+    let mut zones: Vec<(u32, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let number = name.strip_prefix(THERMAL_ZONE_PREFIX)?.parse().ok()?;
+            (!has_hwmon(&entry.path())).then(|| (number, entry.path()))
+        })
+        .collect();
+    zones.sort();
+    zones.into_iter().map(|(_, path)| path).collect()
+}
+
+fn has_hwmon(zone: &Path) -> bool {
+    fs::read_dir(zone)
+        .map(|entries| entries.flatten().any(|entry| entry.file_name().to_string_lossy().starts_with("hwmon")))
+        .unwrap_or(false)
+}
+
+// "x86_pkg_temp" -> "thermal_x86_pkg_temp", "cpu-thermal" -> "thermal_cpu_thermal"
+fn thermal_zone_name(zone_type: &str) -> String {
+    sanitize_sensor_name(&format!("thermal_{}", zone_type.trim().to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thermal_zone_name() {
+        assert_eq!(thermal_zone_name("x86_pkg_temp\n"), "thermal_x86_pkg_temp");
+        assert_eq!(thermal_zone_name("cpu-thermal"), "thermal_cpu_thermal");
+        assert_eq!(thermal_zone_name("INT3400 Thermal"), "thermal_int3400_thermal");
+    }
+
+    #[tokio::test]
+    async fn test_collect_thermal_zones() {
+        let root = std::env::temp_dir().join(format!("orbiq_thermal_{}", std::process::id()));
+        let zone = |number: u32, zone_type: &str, temp: &str| {
+            let path = root.join(format!("thermal_zone{}", number));
+            fs::create_dir_all(&path).unwrap();
+            fs::write(path.join("type"), zone_type).unwrap();
+            fs::write(path.join("temp"), temp).unwrap();
+        };
+        zone(10, "iwlwifi_1", "41000\n");
+        zone(2, "x86_pkg_temp", "52500\n");
+        zone(3, "INT3400 Thermal", "");
+        zone(0, "acpitz", "27800\n");
+        fs::create_dir_all(root.join("thermal_zone0/hwmon1")).unwrap();
+        fs::create_dir_all(root.join("cooling_device0")).unwrap();
+
+        let sensors = collect_thermal_zones(&root, Duration::from_millis(500)).await;
+        let values: Vec<(&str, f64)> = sensors.iter().map(|s| (s.name.as_str(), s.value)).collect();
+        assert_eq!(values, [("thermal_x86_pkg_temp", 52.5), ("thermal_iwlwifi_1", 41.0)]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,158 @@
+use crate::config::{ConfigError, TlsConfig};
+use rumqttc::tokio_rustls::rustls::client::danger::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+};
+use rumqttc::tokio_rustls::rustls::crypto::ring::default_provider;
+use rumqttc::tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rumqttc::tokio_rustls::rustls::{ClientConfig, DigitallySignedStruct, SignatureScheme};
+use rumqttc::{TlsConfiguration, Transport};
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use std::sync::Arc;
+
+// Returns None for plain TCP, i.e. when no TLS option is set
+pub fn transport_for(tls: &TlsConfig) -> Result<Option<Transport>, ConfigError> {
+    if !tls.is_enabled() {
+        return Ok(None);
+    }
+
+    let client_auth = match (&tls.client_cert, &tls.client_key) {
+        (Some(cert), Some(key)) => Some((read_tls_file(cert)?, read_tls_file(key)?)),
+        (None, None) => None,
+        _ => {
+            return Err(ConfigError::Invalid(
+                "mqtt.tls.client_cert and mqtt.tls.client_key must be set together".to_string(),
+            ));
+        }
+    };
+
+    let tls_config = if tls.insecure {
+        TlsConfiguration::Rustls(Arc::new(insecure_client_config(client_auth)?))
+    } else {
+        let Some(ca_cert) = &tls.ca_cert else {
+            return Err(ConfigError::Invalid(
+                "mqtt.tls.ca_cert is required unless mqtt.tls.insecure = true".to_string(),
+            ));
+        };
+        TlsConfiguration::Simple {
+            ca: read_tls_file(ca_cert)?,
+            alpn: None,
+            client_auth,
+        }
+    };
+
+    Ok(Some(Transport::Tls(tls_config)))
+}
+
+fn read_tls_file(path: &Path) -> Result<Vec<u8>, ConfigError> {
+    fs::read(path).map_err(|e| ConfigError::TlsIo(path.to_path_buf(), e))
+}
+
+fn insecure_client_config(client_auth: Option<(Vec<u8>, Vec<u8>)>) -> Result<ClientConfig, ConfigError> {
+    let builder = ClientConfig::builder()
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(NoCertificateVerification));
+
+    let Some((cert, key)) = client_auth else {
+        return Ok(builder.with_no_client_auth());
+    };
+
+    let invalid = |what: &str| ConfigError::Invalid(format!("mqtt.tls: no valid {} found", what));
+    let certs = rustls_pemfile::certs(&mut Cursor::new(cert))
+        .collect::<Result<Vec<CertificateDer<'static>>, _>>()
+        .map_err(|_| invalid("client certificate"))?;
+    let key: PrivateKeyDer<'static> = rustls_pemfile::private_key(&mut Cursor::new(key))
+        .ok()
+        .flatten()
+        .ok_or_else(|| invalid("client key"))?;
+
+    builder
+        .with_client_auth_cert(certs, key)
+        .map_err(|e| ConfigError::Invalid(format!("mqtt.tls: {}", e)))
+}
+
+// Accepts any broker certificate, only used with mqtt.tls.insecure = true
+#[derive(Debug)]
+struct NoCertificateVerification;
+
+impl ServerCertVerifier for NoCertificateVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rumqttc::tokio_rustls::rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rumqttc::tokio_rustls::rustls::Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rumqttc::tokio_rustls::rustls::Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        default_provider().signature_verification_algorithms.supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_plain_tcp_without_tls_options() {
+        assert!(transport_for(&TlsConfig::default()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_ca_cert_selects_tls_transport() {
+        let path = std::env::temp_dir().join("orbiq_tls_ca_test.pem");
+        fs::write(&path, "-----BEGIN CERTIFICATE-----\n").unwrap();
+
+        let tls = TlsConfig {
+            ca_cert: Some(path.clone()),
+            ..TlsConfig::default()
+        };
+        let transport = transport_for(&tls);
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(transport, Ok(Some(Transport::Tls(TlsConfiguration::Simple { .. })))));
+    }
+
+    #[test]
+    fn test_missing_ca_cert_is_tls_io_error() {
+        let tls = TlsConfig {
+            ca_cert: Some(PathBuf::from("/nonexistent/ca.pem")),
+            ..TlsConfig::default()
+        };
+
+        assert!(matches!(transport_for(&tls), Err(ConfigError::TlsIo(..))));
+    }
+
+    #[test]
+    fn test_insecure_without_ca_cert() {
+        let tls = TlsConfig {
+            insecure: true,
+            ..TlsConfig::default()
+        };
+
+        assert!(matches!(transport_for(&tls), Ok(Some(Transport::Tls(TlsConfiguration::Rustls(_))))));
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+// Base units that can safely take an SI prefix. Percentages, temperatures,
+// RPM and already-prefixed units like "GB" are left untouched.
+const SCALABLE_UNITS: [&str; 6] = ["W", "V", "A", "J", "Hz", "B"];
+
+pub fn auto_scale(value: f64, unit: &str) -> (f64, String) {
+    scale_with_prefix(value, unit, si_prefix(value))
+}
+
+// Like auto_scale, but the prefix is picked on the first reading of `key` and kept after that.
+// Home Assistant keeps the unit of the first discovery, so a value crossing 1000 must not
+// switch W to kW between cycles.
+pub fn auto_scale_stable(
+    key: &str,
+    value: f64,
+    unit: &str,
+    prefixes: &mut HashMap<String, &'static str>,
+) -> (f64, String) {
+    if let Some(prefix) = prefixes.get(key) {
+        return scale_with_prefix(value, unit, prefix);
+    }
+    prefixes.insert(key.to_string(), si_prefix(value));
+    auto_scale(value, unit)
+}
+
+fn si_prefix(value: f64) -> &'static str {
+    let magnitude = value.abs();
+    if magnitude > 1_000_000.0 {
+        "M"
+    } else if magnitude > 1_000.0 {
+        "k"
+    } else if magnitude > 0.0 && magnitude < 0.001 {
+        "m"
+    } else {
+        ""
+    }
+}
+
+fn scale_with_prefix(value: f64, unit: &str, prefix: &str) -> (f64, String) {
+    if !SCALABLE_UNITS.contains(&unit) {
+        return (value, unit.to_string());
+    }
+
+    let value = match prefix {
+        "M" => value / 1_000_000.0,
+        "k" => value / 1_000.0,
+        "m" => value * 1_000.0,
+        _ => value,
+    };
+    (value, format!("{}{}", prefix, unit))
+}
+
+// Simple glob matching supporting `*` (any run of characters) and `?` (any single character)
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<usize> = None;
+    let mut star_match = 0;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some(p);
+            star_match = n;
+            p += 1;
+        } else if let Some(star_pos) = star {
+            p = star_pos + 1;
+            star_match += 1;
+            n = star_match;
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_scale_mega() {
+        assert_eq!(auto_scale(2_500_000.0, "W"), (2.5, "MW".to_string()));
+        assert_eq!(auto_scale(1_000_000.0, "W"), (1_000.0, "kW".to_string()));
+    }
+
+    #[test]
+    fn test_auto_scale_kilo() {
+        assert_eq!(auto_scale(1_500.0, "B"), (1.5, "kB".to_string()));
+        assert_eq!(auto_scale(1_000.0, "B"), (1_000.0, "B".to_string()));
+    }
+
+    #[test]
+    fn test_auto_scale_milli() {
+        assert_eq!(auto_scale(0.0005, "V"), (0.5, "mV".to_string()));
+        assert_eq!(auto_scale(0.001, "V"), (0.001, "V".to_string()));
+        assert_eq!(auto_scale(0.0, "V"), (0.0, "V".to_string()));
+    }
+
+    #[test]
+    fn test_auto_scale_stable_keeps_first_prefix() {
+        let mut prefixes = HashMap::new();
+        assert_eq!(auto_scale_stable("psu_power", 950.0, "W", &mut prefixes), (950.0, "W".to_string()));
+        assert_eq!(auto_scale_stable("psu_power", 1_050.0, "W", &mut prefixes), (1_050.0, "W".to_string()));
+
+        assert_eq!(auto_scale_stable("gpu_power", 1_500.0, "W", &mut prefixes), (1.5, "kW".to_string()));
+        assert_eq!(auto_scale_stable("gpu_power", 900.0, "W", &mut prefixes), (0.9, "kW".to_string()));
+    }
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("nvme_*", "nvme_1"));
+        assert!(matches_pattern("*", "cpu_usage"));
+        assert!(matches_pattern("k10temp_?", "k10temp_1"));
+        assert!(matches_pattern("disk_*_root", "disk_usage_root"));
+        assert!(matches_pattern("cpu_usage", "cpu_usage"));
+        assert!(!matches_pattern("k10temp_?", "k10temp_12"));
+        assert!(!matches_pattern("nvme_*", "k10temp_1"));
+    }
+
+    #[test]
+    fn test_auto_scale_skips_unscalable_units() {
+        assert_eq!(auto_scale(5_000.0, "%"), (5_000.0, "%".to_string()));
+        assert_eq!(auto_scale(0.0001, "°C"), (0.0001, "°C".to_string()));
+        assert_eq!(auto_scale(2_000.0, "RPM"), (2_000.0, "RPM".to_string()));
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use crate::hwmon_devices::{discover_hwmon_devices, HwmonDevice};
+use crate::sensors::{CollectFuture, SensorCollector, SystemSensor};
+use crate::sysfs::read_attribute;
+use crate::sensors::SystemSensorType::Voltage;
+
+const VOLTAGE_FILE_PREFIX: &str = "in";
+const VOLTAGE_FILE_SUFFIX: &str = "_input";
+const MILLIVOLT_TO_VOLT: f64 = 1000.0;
+
+// hwmon in*_input rails, e.g. Vcore and +12V
+pub struct VoltageCollector {
+    pub hwmon_cache_ttl: Duration,
+    pub read_timeout: Duration,
+}
+
+impl SensorCollector for VoltageCollector {
+    fn collect(&self) -> CollectFuture<'_> {
+        Box::pin(collect_all_voltages(self.hwmon_cache_ttl, self.read_timeout))
+    }
+
+    fn name(&self) -> &str {
+        "voltage"
+    }
+}
+
+async fn collect_all_voltages(hwmon_cache_ttl: Duration, read_timeout: Duration) -> Vec<SystemSensor> {
+    let mut sensors = Vec::new();
+
+    match discover_hwmon_devices(hwmon_cache_ttl) {
+        Ok(devices) => {
+            for device in devices {
+                let device_sensors = scan_device_voltages(&device, read_timeout).await;
+                sensors.extend(device_sensors);
+            }
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to discover hwmon devices");
+        }
+    }
+
+    sensors
+}
+
+async fn scan_device_voltages(device: &HwmonDevice, read_timeout: Duration) -> Vec<SystemSensor> {
+    let mut sensors = Vec::new();
+
+    match fs::read_dir(&device.path) {
+        Ok(entries) => {
+            for entry in entries.flatten() {
+                if let Some(sensor) = process_voltage_file(&entry.path(), device, read_timeout).await {
+                    sensors.push(sensor);
+                }
+            }
+        }
+        Err(e) => {
+            tracing::warn!(device = %device.path.display(), error = %e, "Failed to read device directory");
+        }
+    }
+
+    sensors
+}
+
+async fn process_voltage_file(file_path: &Path, device: &HwmonDevice, read_timeout: Duration) -> Option<SystemSensor> {
+    let filename = file_path.file_name()?.to_string_lossy();
+
+    if !is_voltage_file(&filename) {
+        return None;
+    }
+
+    let voltage = read_voltage_value(file_path, read_timeout).await?;
+    let voltage_number = extract_voltage_number(&filename)?;
+    let sensor_name = format!("{}_{}_voltage", device.name, voltage_number);
+    let label = get_voltage_label(file_path, read_timeout).await;
+
+    Some(SystemSensor {
+        name: sensor_name,
+        label,
+        value: voltage,
+        unit: "V".to_string(),
+        sensor_type: Voltage,
+        attributes: HashMap::new(),
+        extra: None,
+    })
+}
+
+// "in0_input".."inN_input"; other files starting with "in" (e.g. "intrusion0_alarm") are skipped
+fn is_voltage_file(filename: &str) -> bool {
+    extract_voltage_number(filename).is_some()
+}
+
+async fn read_voltage_value(file_path: &Path, read_timeout: Duration) -> Option<f64> {
+    let voltage_raw = read_attribute(file_path, read_timeout).await?;
+    let voltage_millivolts = voltage_raw.trim().parse::<f64>().ok()?;
+    Some(voltage_millivolts / MILLIVOLT_TO_VOLT)
+}
+
+fn extract_voltage_number(filename: &str) -> Option<String> {
+    let number = filename
+        .strip_prefix(VOLTAGE_FILE_PREFIX)?
+        .strip_suffix(VOLTAGE_FILE_SUFFIX)?;
+
+    if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) {
+        Some(number.to_string())
+    } else {
+        None
+    }
+}
+
+async fn get_voltage_label(file_path: &Path, read_timeout: Duration) -> Option<String> {
+    let filename = file_path.file_name()?.to_string_lossy();
+    let label_filename = filename.replace(VOLTAGE_FILE_SUFFIX, "_label");
+    let label_path = file_path.with_file_name(label_filename);
+    read_attribute(&label_path, read_timeout).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_voltage_file() {
+        assert!(is_voltage_file("in0_input"));
+        assert!(is_voltage_file("in12_input"));
+        assert!(!is_voltage_file("in0_max"));
+        assert!(!is_voltage_file("intrusion0_input"));
+        assert!(!is_voltage_file("temp1_input"));
+    }
+
+    #[test]
+    fn test_extract_voltage_number() {
+        assert_eq!(extract_voltage_number("in1_input"), Some("1".to_string()));
+        assert_eq!(extract_voltage_number("in12_input"), Some("12".to_string()));
+    }
+}
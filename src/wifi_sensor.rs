@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+use crate::config::SensorsConfig;
+use crate::naming::sanitize_sensor_name;
+use crate::network_sensor::should_monitor;
+use crate::sensors::{CollectFuture, SensorCollector, SystemSensor};
+use crate::sensors::SystemSensorType::WifiSignal;
+use crate::sysfs::read_attribute;
+
+const WIRELESS_PATH: &str = "/proc/net/wireless";
+// Link quality is reported on a 0-70 scale by most drivers
+const MAX_LINK_QUALITY: f64 = 70.0;
+// Drivers that don't measure noise report -256 dBm
+const NOISE_UNAVAILABLE: f64 = -256.0;
+
+// Signal, link quality and noise of the wireless interfaces
+pub struct WifiCollector {
+    pub sensors_config: SensorsConfig,
+    pub read_timeout: Duration,
+}
+
+impl SensorCollector for WifiCollector {
+    fn collect(&self) -> CollectFuture<'_> {
+        Box::pin(collect_wifi(&self.sensors_config, self.read_timeout))
+    }
+
+    fn name(&self) -> &str {
+        "wifi"
+    }
+}
+
+// Wired-only systems have no /proc/net/wireless, which yields no sensors
+async fn collect_wifi(sensors_config: &SensorsConfig, read_timeout: Duration) -> Vec<SystemSensor> {
+    let path = Path::new(WIRELESS_PATH);
+    if !path.exists() {
+        return Vec::new();
+    }
+    let Some(content) = read_attribute(path, read_timeout).await else {
+        return Vec::new();
+    };
+
+    parse_wireless(&content)
+        .into_iter()
+        .filter(|reading| should_monitor(&reading.interface, sensors_config))
+        .flat_map(|reading| reading.sensors())
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+struct WirelessReading {
+    interface: String,
+    quality: f64,
+    signal_dbm: f64,
+    noise_dbm: f64,
+}
+
+impl WirelessReading {
+    fn sensors(&self) -> Vec<SystemSensor> {
+        let sensor = |suffix: &str, value: f64, unit: &str| SystemSensor {
+            name: sanitize_sensor_name(&format!("wifi_{}_{}", self.interface, suffix)),
+            label: None,
+            value,
+            unit: unit.to_string(),
+            sensor_type: WifiSignal,
+            attributes: HashMap::new(),
+            extra: None,
+        };
+
+        let mut sensors = vec![
+            sensor("signal_dbm", self.signal_dbm, "dBm"),
+            sensor("quality_pct", quality_percent(self.quality), "%"),
+        ];
+        if self.noise_dbm > NOISE_UNAVAILABLE {
+            sensors.push(sensor("noise_dbm", self.noise_dbm, "dBm"));
+        }
+        sensors
+    }
+}
+
+// After two header lines each interface looks like
+// " wlan0: 0000   70.  -39.  -256        0      0      0      0      0        0"
+fn parse_wireless(content: &str) -> Vec<WirelessReading> {
+    content
+        .lines()
+        .skip(2)
+        .filter_map(|line| {
+            let (interface, fields) = line.split_once(':')?;
+            let values: Vec<f64> = fields
+                .split_whitespace()
+                .skip(1) // Status
+                .take(3)
+                .filter_map(|field| field.trim_end_matches('.').parse().ok())
+                .collect();
+            let [quality, signal_dbm, noise_dbm] = values[..] else {
+                return None;
+            };
+            Some(WirelessReading {
+                interface: interface.trim().to_string(),
+                quality,
+                signal_dbm,
+                noise_dbm,
+            })
+        })
+        .collect()
+}
+
+fn quality_percent(quality: f64) -> f64 {
+    (quality / MAX_LINK_QUALITY * 100.0).clamp(0.0, 100.0).round()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIRELESS: &str = "Inter-| sta-|   Quality        |   Discarded packets               | Missed | WE
+ face | tus | link level noise |  nwid  crypt   frag  retry   misc | beacon | 22
+ wlan0: 0000   56.  -54.  -256        0      0      0      0     12        0
+ wlp3s0: 0000   70.  -39.  -92.        0      0      0      0      0        0
+";
+
+    #[test]
+    fn test_parse_wireless() {
+        let readings = parse_wireless(WIRELESS);
+        assert_eq!(
+            readings[1],
+            WirelessReading {
+                interface: "wlp3s0".to_string(),
+                quality: 70.0,
+                signal_dbm: -39.0,
+                noise_dbm: -92.0,
+            }
+        );
+
+        let names: Vec<(String, f64)> = readings[0].sensors().into_iter().map(|s| (s.name, s.value)).collect();
+        assert_eq!(
+            names,
+            [
+                ("wifi_wlan0_signal_dbm".to_string(), -54.0),
+                ("wifi_wlan0_quality_pct".to_string(), 80.0),
+            ]
+        );
+        assert_eq!(readings[1].sensors().len(), 3);
+    }
+
+    #[test]
+    fn test_parse_wireless_without_interfaces() {
+        assert!(parse_wireless(&WIRELESS.lines().take(2).collect::<Vec<_>>().join("\n")).is_empty());
+    }
+}